@@ -0,0 +1,39 @@
+//! Throws arbitrary bytes at the decoders, every input must be either decoded or rejected with an error.
+//!
+//! `cargo +nightly fuzz run decode` (from `ido-with-vesting/`)
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use solana_program::{program_pack::Pack, pubkey::Pubkey};
+use ido_with_vesting::{
+    instruction::IDOInstruction,
+    events::IDOEvent,
+    state::{
+        IDOConfigAccount,
+        IDOVestingAccount,
+        IDOAuctionAccount,
+        IDOBidAccount
+    }
+};
+
+
+fuzz_target!(|data: &[u8]| {
+    // decoded instruction is packed back into exactly the same bytes
+    if let Ok(ix) = IDOInstruction::unpack(data) {
+        assert_eq!(ix.pack(), data);
+    }
+
+    if let Ok(config_account) = IDOConfigAccount::unpack(data) {
+        assert_eq!(bytemuck::bytes_of(&config_account), data);
+    }
+
+    if let Ok(vesting_account) = IDOVestingAccount::unpack(data) {
+        assert_eq!(bytemuck::bytes_of(&vesting_account), data);
+    }
+
+    let _ = IDOConfigAccount::from_v1(data);
+    let _ = IDOVestingAccount::from_v1(data, Pubkey::default(), Pubkey::default());
+    let _ = IDOAuctionAccount::unpack_from_slice(data);
+    let _ = IDOBidAccount::unpack_from_slice(data);
+    let _ = IDOEvent::unpack(data);
+});
@@ -0,0 +1,114 @@
+use solana_program::{sysvar::clock::Clock, entrypoint::ProgramResult, program_error::ProgramError};
+use super::{
+    state::{IDOAuctionAccount, IDOBidAccount},
+    error::IDOProgramError,
+    constants::AUCTION_PRICE_LEVELS
+};
+
+
+impl IDOAuctionAccount {
+    /// Maps `price` to the index of the demand book.
+    ///
+    /// Only prices that lay exactly on the grid `min_price + level * tick_size` are accepted.
+    pub fn price_level(&self, price: u32) -> Result<usize, IDOProgramError> {
+        let offset: u32 = price
+            .checked_sub(self.min_price)
+            .ok_or(IDOProgramError::InvalidBidPrice)?;
+
+        let level: u32 = offset / self.tick_size;
+
+        if level * self.tick_size != offset {
+            return Err(IDOProgramError::InvalidBidPrice);
+        }
+
+        let level: usize = level as usize;
+
+        if level >= AUCTION_PRICE_LEVELS {
+            return Err(IDOProgramError::InvalidBidPrice);
+        }
+
+        Ok(level)
+    }
+
+    /// Defines the uniform clearing price, i.e. the highest price level at which the cumulative demand
+    /// covers the whole `supply`.
+    ///
+    /// If the auction is undersubscribed, every bid is filled at `min_price`.
+    pub fn settle(&mut self) -> ProgramResult {
+        if self.is_settled {
+            return Err(IDOProgramError::AuctionAlreadySettled.into());
+        }
+
+        // undersubscribed by default => level 0 is filled completely, so is every level above it
+        let mut clearing_level: usize = 0;
+        let mut clearing_level_fill: u64 = self.demand[0];
+        let mut cumulative_demand: u64 = 0;
+
+        for level in (0..AUCTION_PRICE_LEVELS).rev() {
+            let level_demand: u64 = self.demand[level];
+            let demand_above: u64 = cumulative_demand;
+
+            cumulative_demand = cumulative_demand
+                .checked_add(level_demand)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+
+            if cumulative_demand >= self.supply {
+                clearing_level = level;
+                clearing_level_fill = self.supply - demand_above;
+                break;
+            }
+        }
+
+        self.clearing_level = clearing_level as u8;
+        self.clearing_level_fill = clearing_level_fill;
+        self.clearing_price = (clearing_level as u32)
+            .checked_mul(self.tick_size)
+            .and_then(|offset| offset.checked_add(self.min_price))
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        self.is_settled = true;
+
+        Ok(())
+    }
+
+    /// Returns the amount of tokens the `bid` wins:
+    /// 1. Bids above the clearing price are filled completely.
+    /// 2. Bids exactly at the clearing price share `clearing_level_fill` pro-rata (rounded down).
+    /// 3. Bids below the clearing price lose.
+    pub fn define_bid_fill(&self, bid: &IDOBidAccount) -> Result<u64, ProgramError> {
+        if !self.is_settled {
+            return Err(IDOProgramError::AuctionNotSettled.into());
+        }
+
+        let level: usize = self.price_level(bid.max_price)?;
+        let clearing_level: usize = self.clearing_level as usize;
+
+        Ok(if level > clearing_level {
+            bid.quantity
+        } else if level == clearing_level {
+            let level_demand: u64 = self.demand[level];
+
+            if level_demand == 0 {
+                0
+            } else {
+                (bid.quantity as u128 * self.clearing_level_fill as u128 / level_demand as u128) as u64
+            }
+        } else {
+            0
+        })
+    }
+
+    /// Tokens the settled auction distributes at most: the whole supply, unless it's undersubscribed.
+    ///
+    /// Note, the pro-rata fills at the clearing price are rounded down, so the bids may get slightly less.
+    pub fn filled_supply(&self) -> u64 {
+        let demand: u64 = self.demand
+            .iter()
+            .fold(0u64, |demand, level_demand| demand.saturating_add(*level_demand));
+
+        demand.min(self.supply)
+    }
+
+    pub fn is_bidding_active(&self, clock: &Clock) -> bool {
+        clock.unix_timestamp < self.bidding_end_ts
+    }
+}
\ No newline at end of file
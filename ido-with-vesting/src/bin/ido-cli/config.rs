@@ -0,0 +1,80 @@
+//! Settings shared with the Solana CLI: `json_rpc_url` & `keypair_path` of `~/.config/solana/cli/config.yml`.
+use std::path::PathBuf;
+
+
+const DEFAULT_RPC_URL: &str = "http://127.0.0.1:8899";
+
+pub struct CliConfig {
+    pub json_rpc_url: String,
+    pub keypair_path: String
+}
+
+impl CliConfig {
+    /// Falls back to the Solana CLI defaults if the default config file doesn't exist,
+    /// explicitly provided `config_path` must exist.
+    pub fn load(config_path: Option<&str>) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut config: Self = Self {
+            json_rpc_url: DEFAULT_RPC_URL.to_string(),
+            keypair_path: solana_config_dir().join("id.json").to_string_lossy().into_owned()
+        };
+
+        let path: PathBuf = match config_path {
+            Some(config_path) => PathBuf::from(config_path),
+            None => {
+                let default_path: PathBuf = solana_config_dir().join("cli").join("config.yml");
+                if !default_path.exists() {
+                    return Ok(config);
+                }
+                default_path
+            }
+        };
+
+        let content: String = std::fs::read_to_string(&path)
+            .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+
+        // the config is a flat YAML map, so there is no need in the YAML parser for 2 fields
+        for line in content.lines() {
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value: String = value.trim().trim_matches(|c| c == '"' || c == '\'').to_string();
+
+            match key.trim() {
+                "json_rpc_url" if !value.is_empty() => config.json_rpc_url = value,
+                "keypair_path" if !value.is_empty() => config.keypair_path = value,
+                _ => ()
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+/// Accepts the URL or the moniker of the Solana CLI: `localhost`, `devnet`, `testnet`, `mainnet-beta` or their first letter.
+pub fn normalize_url(url: &str) -> String {
+    match url {
+        "l" | "localhost" => DEFAULT_RPC_URL,
+        "d" | "devnet" => "https://api.devnet.solana.com",
+        "t" | "testnet" => "https://api.testnet.solana.com",
+        "m" | "mainnet-beta" => "https://api.mainnet-beta.solana.com",
+        url => url
+    }.to_string()
+}
+
+/// Expands the leading `~` the same way the Solana CLI does.
+pub fn expand_tilde(path: &str) -> PathBuf {
+    match path.strip_prefix("~/") {
+        Some(rest) => home_dir().join(rest),
+        None => PathBuf::from(path)
+    }
+}
+
+fn solana_config_dir() -> PathBuf {
+    home_dir().join(".config").join("solana")
+}
+
+fn home_dir() -> PathBuf {
+    std::env::var_os("HOME")
+        .map(PathBuf::from)
+        .unwrap_or_default()
+}
@@ -0,0 +1,524 @@
+//! `ido-cli` launches & operates the sales of `ido-with-vesting` program.
+//!
+//! RPC URL & keypair are taken from the Solana CLI config, unless `--url` or `--keypair` is provided.
+//! All token amounts are represented without decimals (whole tokens), prices - in LAMPORTS per token.
+mod config;
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use ido_with_vesting::{
+    pda,
+    instruction,
+    token::to_base_units,
+    vesting::LinearVestingStrategy,
+    state::{
+        IDOAuctionAccount,
+        IDOBidAccount,
+        IDOConfigAccount,
+        IDOVestingAccount,
+        TransferFeePayer
+    },
+    client::{
+        ProgramClient,
+        ProgramClientError,
+        ProgramClientTransport
+    },
+    inspect::AccountInspection
+};
+use spl_token_2022::{
+    state::Mint,
+    extension::StateWithExtensions,
+    ID as SPL_TOKEN_2022_ID
+};
+use spl_token::ID as SPL_TOKEN_ID;
+use solana_sdk::{
+    account::Account,
+    pubkey::Pubkey,
+    program_pack::Pack,
+    signature::Signature,
+    instruction::Instruction,
+    commitment_config::CommitmentConfig,
+    signer::{keypair::{Keypair, read_keypair_file}, Signer}
+};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use config::{CliConfig, normalize_url, expand_tilde};
+
+
+#[derive(Parser)]
+#[command(name = "ido-cli", version, about = "Launch & operate IDO sales with linear vesting")]
+struct Cli {
+    /// Solana CLI config file [default: ~/.config/solana/cli/config.yml]
+    #[arg(long, short = 'C', global = true)]
+    config: Option<String>,
+    /// RPC URL or moniker (localhost, devnet, testnet, mainnet-beta) [default: from the Solana CLI config]
+    #[arg(long, short = 'u', global = true)]
+    url: Option<String>,
+    /// Payer keypair file [default: from the Solana CLI config]
+    #[arg(long, short = 'k', global = true)]
+    keypair: Option<String>,
+
+    #[command(subcommand)]
+    command: Command
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print the addresses derived from the mint, no RPC requests are made
+    Pda {
+        #[arg(long)]
+        mint: Pubkey,
+        /// Vesting & Bid owner [default: payer]
+        #[arg(long)]
+        owner: Option<Pubkey>
+    },
+    /// Start the fixed price sale, the supply is deposited from the payer's ATA
+    Init {
+        #[arg(long)]
+        mint: Pubkey,
+        /// Supply deposited into the treasury
+        #[arg(long)]
+        amount: u64,
+        /// LAMPORTS per token
+        #[arg(long)]
+        price: u32,
+        #[command(flatten)]
+        vesting: VestingArgs
+    },
+    /// Start the uniform-price batch auction, the supply is deposited from the payer's ATA
+    InitAuction {
+        #[arg(long)]
+        mint: Pubkey,
+        /// Supply deposited into the treasury
+        #[arg(long)]
+        amount: u64,
+        /// LAMPORTS per token of the lowest price level
+        #[arg(long)]
+        min_price: u32,
+        /// LAMPORTS between the price levels
+        #[arg(long)]
+        tick_size: u32,
+        /// Bidding window, e.g. `2h`, vesting is measured from its end
+        #[arg(long, value_parser = humantime::parse_duration)]
+        bidding: Duration,
+        #[command(flatten)]
+        vesting: VestingArgs
+    },
+    /// Buy tokens at the fixed price
+    Buy {
+        #[arg(long)]
+        mint: Pubkey,
+        #[arg(long)]
+        amount: u64
+    },
+    /// Claim the unlocked tokens of the payer's vesting
+    Claim {
+        #[arg(long)]
+        mint: Pubkey,
+        /// Wallet, whose ATA receives the tokens [default: payer]
+        #[arg(long)]
+        recipient: Option<Pubkey>
+    },
+    /// Withdraw the raised LAMPORTS & (after the vesting end) the unsold supply, the payer must be the sale's authority
+    Withdraw {
+        #[arg(long)]
+        mint: Pubkey
+    },
+    /// Place the auction bid, `quantity * max_price` LAMPORTS are escrowed
+    PlaceBid {
+        #[arg(long)]
+        mint: Pubkey,
+        #[arg(long)]
+        quantity: u64,
+        #[arg(long)]
+        max_price: u32
+    },
+    /// Define the clearing price once the bidding window is over
+    SettleAuction {
+        #[arg(long)]
+        mint: Pubkey
+    },
+    /// Turn the settled bid into the vesting position & refund the rest
+    SettleBid {
+        #[arg(long)]
+        mint: Pubkey,
+        /// [default: payer]
+        #[arg(long)]
+        bidder: Option<Pubkey>
+    },
+    /// Decode any account owned by the program as a table or JSON
+    Inspect {
+        address: Pubkey,
+        #[arg(long)]
+        json: bool
+    },
+    /// Print the sale state & the owner's vesting
+    Status {
+        #[arg(long)]
+        mint: Pubkey,
+        /// Vesting & Bid owner [default: payer]
+        #[arg(long)]
+        owner: Option<Pubkey>
+    }
+}
+
+#[derive(Args)]
+struct VestingArgs {
+    /// Vesting duration, e.g. `30days` or `1h 30m`
+    #[arg(long, value_parser = humantime::parse_duration)]
+    vesting: Duration,
+    /// Period between the unlocks, e.g. `1day`
+    #[arg(long, value_parser = humantime::parse_duration)]
+    unlock_period: Duration,
+    /// Cliff duration, vesting starts right away if omitted
+    #[arg(long, value_parser = humantime::parse_duration)]
+    cliff: Option<Duration>,
+    /// Who absorbs Token-2022 transfer fee on claim
+    #[arg(long, value_enum, default_value_t = FeePayer::Recipient)]
+    transfer_fee_payer: FeePayer
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum FeePayer {
+    Recipient,
+    Treasury
+}
+
+impl From<FeePayer> for TransferFeePayer {
+    fn from(value: FeePayer) -> Self {
+        match value {
+            FeePayer::Recipient => Self::Recipient,
+            FeePayer::Treasury => Self::Treasury
+        }
+    }
+}
+
+impl VestingArgs {
+    /// Durations are measured from `start_ts`.
+    fn strategy(&self, start_ts: i64) -> LinearVestingStrategy {
+        LinearVestingStrategy {
+            cliff_end_ts: self.cliff.map_or(0, |cliff| start_ts + cliff.as_secs() as i64),
+            vesting_end_ts: start_ts + self.vesting.as_secs() as i64,
+            unlock_period: self.unlock_period.as_secs() as i64
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    if let Err(e) = run(Cli::parse()).await {
+        eprintln!("error: {}", e);
+        std::process::exit(1);
+    }
+}
+
+async fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
+    let cli_config: CliConfig = CliConfig::load(cli.config.as_deref())?;
+
+    let url: String = normalize_url(cli.url.as_deref().unwrap_or(&cli_config.json_rpc_url));
+    let keypair_path: String = cli.keypair.unwrap_or(cli_config.keypair_path);
+    // status & pda don't sign anything, so the missing keypair is reported only by the commands that need it
+    let payer: Result<Keypair, String> = read_keypair_file(expand_tilde(&keypair_path))
+        .map_err(|e| format!("failed to read keypair {}: {}", keypair_path, e));
+
+    let rpc_client: RpcClient = RpcClient::new_with_commitment(url, CommitmentConfig::confirmed());
+
+    match cli.command {
+        Command::Pda { mint, owner } => {
+            let owner: Option<Pubkey> = owner.or(payer.as_ref().ok().map(Keypair::pubkey));
+            print_pdas(&mint, owner.as_ref());
+        },
+        Command::Init { mint, amount, price, vesting } => {
+            let payer: Keypair = payer?;
+            let (token_program_id, decimals) = fetch_mint(&rpc_client, &mint).await?;
+            let mut program_client: ProgramClient = ProgramClient::new(ProgramClientTransport::Rpc(&rpc_client), &payer, mint, token_program_id);
+
+            print_pdas(&mint, None);
+            let signature: Signature = program_client.initialize(
+                to_base_units(amount, decimals)?,
+                price,
+                &vesting.strategy(now_ts()),
+                vesting.transfer_fee_payer.into()
+            ).await?;
+            print_signature(signature);
+        },
+        Command::InitAuction { mint, amount, min_price, tick_size, bidding, vesting } => {
+            let payer: Keypair = payer?;
+            let (token_program_id, decimals) = fetch_mint(&rpc_client, &mint).await?;
+            let mut program_client: ProgramClient = ProgramClient::new(ProgramClientTransport::Rpc(&rpc_client), &payer, mint, token_program_id);
+
+            let bidding_end_ts: i64 = now_ts() + bidding.as_secs() as i64;
+            let initialize_auction_ix: Instruction = instruction::create_initialize_auction_for_mint(
+                to_base_units(amount, decimals)?,
+                min_price,
+                tick_size,
+                bidding_end_ts,
+                &vesting.strategy(bidding_end_ts),
+                vesting.transfer_fee_payer.into(),
+                &payer.pubkey(),
+                &mint,
+                &token_program_id
+            );
+
+            print_pdas(&mint, None);
+            print_signature(program_client.process_instructions(&[initialize_auction_ix]).await?);
+        },
+        Command::Buy { mint, amount } => {
+            let payer: Keypair = payer?;
+            let (token_program_id, _) = fetch_mint(&rpc_client, &mint).await?;
+            let mut program_client: ProgramClient = ProgramClient::new(ProgramClientTransport::Rpc(&rpc_client), &payer, mint, token_program_id);
+
+            print_pdas(&mint, Some(&payer.pubkey()));
+            print_signature(program_client.buy(amount).await?);
+        },
+        Command::Claim { mint, recipient } => {
+            let payer: Keypair = payer?;
+            let (token_program_id, _) = fetch_mint(&rpc_client, &mint).await?;
+            let mut program_client: ProgramClient = ProgramClient::new(ProgramClientTransport::Rpc(&rpc_client), &payer, mint, token_program_id);
+            let recipient: Pubkey = recipient.unwrap_or(payer.pubkey());
+
+            print_pdas(&mint, Some(&payer.pubkey()));
+            println!("{:<16}{}", "recipient ata:", pda::find_ata(&recipient, &mint, &token_program_id).0);
+            print_signature(program_client.claim(&recipient).await?);
+        },
+        Command::Withdraw { mint } => {
+            let payer: Keypair = payer?;
+            let (token_program_id, _) = fetch_mint(&rpc_client, &mint).await?;
+            let mut program_client: ProgramClient = ProgramClient::new(ProgramClientTransport::Rpc(&rpc_client), &payer, mint, token_program_id);
+
+            print_pdas(&mint, None);
+            println!("{:<16}{}", "authority ata:", pda::find_ata(&payer.pubkey(), &mint, &token_program_id).0);
+            print_signature(program_client.withdraw().await?);
+        },
+        Command::PlaceBid { mint, quantity, max_price } => {
+            let payer: Keypair = payer?;
+            let (token_program_id, _) = fetch_mint(&rpc_client, &mint).await?;
+            let mut program_client: ProgramClient = ProgramClient::new(ProgramClientTransport::Rpc(&rpc_client), &payer, mint, token_program_id);
+            let place_bid_ix: Instruction = instruction::create_place_bid_for_mint(quantity, max_price, &payer.pubkey(), &mint);
+
+            print_pdas(&mint, Some(&payer.pubkey()));
+            print_signature(program_client.process_instructions(&[place_bid_ix]).await?);
+        },
+        Command::SettleAuction { mint } => {
+            let payer: Keypair = payer?;
+            let (token_program_id, _) = fetch_mint(&rpc_client, &mint).await?;
+            let mut program_client: ProgramClient = ProgramClient::new(ProgramClientTransport::Rpc(&rpc_client), &payer, mint, token_program_id);
+            let settle_auction_ix: Instruction = instruction::create_settle_auction_for_mint(&mint);
+
+            print_pdas(&mint, None);
+            print_signature(program_client.process_instructions(&[settle_auction_ix]).await?);
+        },
+        Command::SettleBid { mint, bidder } => {
+            let payer: Keypair = payer?;
+            let (token_program_id, _) = fetch_mint(&rpc_client, &mint).await?;
+            let mut program_client: ProgramClient = ProgramClient::new(ProgramClientTransport::Rpc(&rpc_client), &payer, mint, token_program_id);
+            let bidder: Pubkey = bidder.unwrap_or(payer.pubkey());
+            let settle_bid_ix: Instruction = instruction::create_settle_bid_for_mint(&payer.pubkey(), &bidder, &mint);
+
+            print_pdas(&mint, Some(&bidder));
+            print_signature(program_client.process_instructions(&[settle_bid_ix]).await?);
+        },
+        Command::Inspect { address, json } => {
+            // nothing is signed, so any keypair fits & neither the mint nor the token program is used,
+            // since the vesting progress is defined by the mint the Vesting stores
+            let payer: Keypair = payer.unwrap_or_else(|_| Keypair::new());
+            let mut program_client: ProgramClient = ProgramClient::new(ProgramClientTransport::Rpc(&rpc_client), &payer, Pubkey::default(), SPL_TOKEN_2022_ID);
+            let inspection: AccountInspection = program_client.inspect(&address).await?;
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&inspection.to_json())?);
+            } else {
+                print!("{}", inspection);
+            }
+        },
+        Command::Status { mint, owner } => {
+            let owner: Option<Pubkey> = owner.or(payer.as_ref().ok().map(Keypair::pubkey));
+            // nothing is signed, so any keypair fits
+            let payer: Keypair = payer.unwrap_or_else(|_| Keypair::new());
+            let (token_program_id, decimals) = fetch_mint(&rpc_client, &mint).await?;
+            let mut program_client: ProgramClient = ProgramClient::new(ProgramClientTransport::Rpc(&rpc_client), &payer, mint, token_program_id);
+
+            print_pdas(&mint, owner.as_ref());
+            print_status(&mut program_client, &mint, owner.as_ref(), decimals).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Token program is the owner of the mint account, so it isn't passed explicitly.
+async fn fetch_mint(rpc_client: &RpcClient, mint: &Pubkey) -> Result<(Pubkey, u8), Box<dyn std::error::Error>> {
+    let mint_account: Account = rpc_client.get_account(mint).await?;
+
+    if mint_account.owner != SPL_TOKEN_ID && mint_account.owner != SPL_TOKEN_2022_ID {
+        return Err(format!("{} is not a mint: owned by {}", mint, mint_account.owner).into());
+    }
+    let decimals: u8 = StateWithExtensions::<Mint>::unpack(&mint_account.data)?.base.decimals;
+
+    Ok((mint_account.owner, decimals))
+}
+
+async fn print_status(
+    program_client: &mut ProgramClient<'_>,
+    mint: &Pubkey,
+    owner: Option<&Pubkey>,
+    decimals: u8
+) -> Result<(), ProgramClientError> {
+    let config_account: IDOConfigAccount = program_client.fetch_config().await?;
+    let vesting_strategy: LinearVestingStrategy = config_account.vesting_strategy;
+
+    println!();
+    println!("{:<16}{}", "price:", format_price(config_account.lamports_per_token));
+    println!("{:<16}{}", "deposited:", format_amount(config_account.deposited_amount, decimals));
+    println!("{:<16}{}", "sold:", config_account.sold_amount);
+    println!("{:<16}{}", "reserved:", format_amount(config_account.reserved_amount, decimals));
+    println!("{:<16}{:?}", "fee payer:", config_account.transfer_fee_payer().ok());
+    println!("{:<16}{}", "authority:", config_account.authority);
+    println!("{:<16}{}", "cliff end:", format_ts(vesting_strategy.cliff_end_ts));
+    println!("{:<16}{}", "vesting end:", format_ts(vesting_strategy.vesting_end_ts));
+    println!("{:<16}{} x {}", "unlocks:", config_account.unlocks, humantime::format_duration(Duration::from_secs(vesting_strategy.unlock_period as u64)));
+
+    if let Some(auction_data) = fetch_optional(program_client, &pda::find_auction(mint).0).await? {
+        let auction_account: IDOAuctionAccount = IDOAuctionAccount::unpack(&auction_data)
+            .map_err(|error| ProgramClientError::InvalidAccountData { address: pda::find_auction(mint).0, error })?;
+
+        println!();
+        println!("{:<16}{}", "bidding end:", format_ts(auction_account.bidding_end_ts));
+        println!("{:<16}{}", "supply:", auction_account.supply);
+        println!("{:<16}{} + level * {}", "price levels:", format_price(auction_account.min_price), auction_account.tick_size);
+        println!("{:<16}{}", "settled:", auction_account.is_settled);
+        if auction_account.is_settled {
+            println!("{:<16}{}", "clearing price:", format_price(auction_account.clearing_price));
+        }
+    }
+
+    let Some(owner) = owner else {
+        return Ok(());
+    };
+
+    if let Some(bid_data) = fetch_optional(program_client, &pda::find_bid(owner, mint).0).await? {
+        let bid_account: IDOBidAccount = IDOBidAccount::unpack(&bid_data)
+            .map_err(|error| ProgramClientError::InvalidAccountData { address: pda::find_bid(owner, mint).0, error })?;
+
+        println!();
+        println!("{:<16}{} @ {}", "bid:", bid_account.quantity, format_price(bid_account.max_price));
+    }
+
+    println!();
+    match program_client.fetch_vesting(owner).await {
+        Ok(vesting_account) => print_vesting(&vesting_account),
+        Err(ProgramClientError::AccountNotFound(_)) => println!("{:<16}none", "vesting:"),
+        Err(e) => return Err(e)
+    }
+
+    Ok(())
+}
+
+fn print_vesting(vesting_account: &IDOVestingAccount) {
+    println!("{:<16}{}", "bought:", vesting_account.bought_amount);
+    println!("{:<16}{}", "claimed:", vesting_account.claimed_amount);
+    println!("{:<16}{}", "per unlock:", vesting_account.amount_per_unlock);
+    println!("{:<16}{}", "last claim:", format_ts(vesting_account.last_claim_ts));
+}
+
+async fn fetch_optional(program_client: &mut ProgramClient<'_>, address: &Pubkey) -> Result<Option<Vec<u8>>, ProgramClientError> {
+    match program_client.fetch_account_data(address).await {
+        Ok(data) => Ok(Some(data)),
+        Err(ProgramClientError::AccountNotFound(_)) => Ok(None),
+        Err(e) => Err(e)
+    }
+}
+
+fn print_pdas(mint: &Pubkey, owner: Option<&Pubkey>) {
+    println!("{:<16}{}", "treasury:", pda::find_treasury(mint).0);
+    println!("{:<16}{}", "config:", pda::find_config(mint).0);
+    println!("{:<16}{}", "auction:", pda::find_auction(mint).0);
+
+    if let Some(owner) = owner {
+        println!("{:<16}{}", "vesting:", pda::find_vesting(owner, mint).0);
+        println!("{:<16}{}", "bid:", pda::find_bid(owner, mint).0);
+    }
+}
+
+fn print_signature(signature: Signature) {
+    println!("{:<16}{}", "signature:", signature);
+}
+
+fn format_price(lamports_per_token: u32) -> String {
+    format!("{} LAMPORTS/token", lamports_per_token)
+}
+
+/// `amount` (base units) with the decimal point, mints with more than 19 decimals don't fit `u64` divisor,
+/// so their amount is printed as is.
+fn format_amount(amount: u64, decimals: u8) -> String {
+    match 10u64.checked_pow(decimals as u32) {
+        Some(divisor) if decimals > 0 => format!("{}.{:0width$}", amount / divisor, amount % divisor, width = decimals as usize),
+        _ => amount.to_string()
+    }
+}
+
+fn format_ts(ts: i64) -> String {
+    if ts <= 0 {
+        return "-".to_string();
+    }
+
+    humantime::format_rfc3339_seconds(UNIX_EPOCH + Duration::from_secs(ts as u64)).to_string()
+}
+
+fn now_ts() -> i64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_amount() {
+        assert_eq!(format_amount(1_500, 0), "1500");
+        assert_eq!(format_amount(1_500_000, 6), "1.500000");
+        assert_eq!(format_amount(42, 9), "0.000000042");
+        assert_eq!(format_amount(u64::MAX, 19), "1.8446744073709551615");
+        assert_eq!(format_amount(u64::MAX, 20), u64::MAX.to_string());
+        assert_eq!(format_amount(7, u8::MAX), "7");
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let mint: Pubkey = Pubkey::new_unique();
+        let mint_arg: String = mint.to_string();
+
+        let cli: Cli = Cli::try_parse_from([
+            "ido-cli", "init", "--mint", &mint_arg, "--amount", "1000", "--price", "250",
+            "--vesting", "30days", "--unlock-period", "1day", "--cliff", "1h", "--transfer-fee-payer", "treasury",
+            "--url", "devnet"
+        ]).unwrap();
+        assert_eq!(cli.url.as_deref(), Some("devnet"));
+        match cli.command {
+            Command::Init { mint: parsed_mint, amount, price, vesting } => {
+                assert_eq!((parsed_mint, amount, price), (mint, 1_000, 250));
+                assert_eq!(vesting.strategy(100), LinearVestingStrategy {
+                    cliff_end_ts: 100 + 3_600,
+                    vesting_end_ts: 100 + 30 * 86_400,
+                    unlock_period: 86_400
+                });
+                assert!(matches!(vesting.transfer_fee_payer, FeePayer::Treasury));
+            },
+            _ => panic!("expected `init`")
+        }
+
+        let cli: Cli = Cli::try_parse_from(["ido-cli", "withdraw", "--mint", &mint_arg, "-k", "~/id.json"]).unwrap();
+        assert_eq!(cli.keypair.as_deref(), Some("~/id.json"));
+        assert!(matches!(cli.command, Command::Withdraw { mint: parsed_mint } if parsed_mint == mint));
+
+        let cli: Cli = Cli::try_parse_from(["ido-cli", "inspect", &mint_arg, "--json"]).unwrap();
+        assert!(matches!(cli.command, Command::Inspect { address, json: true } if address == mint));
+
+        // required arguments, malformed pubkeys, durations & numbers are rejected by the parser
+        assert!(Cli::try_parse_from(["ido-cli", "buy", "--mint", &mint_arg]).is_err());
+        assert!(Cli::try_parse_from(["ido-cli", "buy", "--mint", "not-a-pubkey", "--amount", "1"]).is_err());
+        assert!(Cli::try_parse_from(["ido-cli", "buy", "--mint", &mint_arg, "--amount", "-1"]).is_err());
+        assert!(Cli::try_parse_from([
+            "ido-cli", "init", "--mint", &mint_arg, "--amount", "1", "--price", "1", "--vesting", "soon", "--unlock-period", "1day"
+        ]).is_err());
+    }
+}
@@ -0,0 +1,480 @@
+//! Async client over `RpcClient`, `BanksClient` or `ProgramTestContext`.
+//!
+//! `ProgramClient` derives every PDA of the IDO from its mint, assembles & signs the transactions with
+//! a fresh blockhash and decodes the program errors, so the callers don't repeat it per transport.
+//!
+//! Listing helpers are built on `getProgramAccounts`, so they're supported only by `RpcClient`,
+//! the filters are public to be reused with other RPC clients.
+use solana_sdk::{
+    hash::Hash,
+    clock::Clock,
+    pubkey::Pubkey,
+    sysvar,
+    account::{Account, from_account},
+    message::Message,
+    signature::Signature,
+    program_error::ProgramError,
+    instruction::{Instruction, InstructionError},
+    transaction::{Transaction, TransactionError},
+    signer::{keypair::Keypair, Signer}
+};
+use solana_program_test::{
+    BanksClient,
+    BanksClientError,
+    BanksTransactionResultWithMetadata,
+    ProgramTestContext,
+    ProgramTestBanksClientExt
+};
+use solana_client::{
+    client_error::{ClientError, ClientErrorKind},
+    rpc_request::{RpcError, RpcResponseErrorData},
+    nonblocking::rpc_client::RpcClient,
+    rpc_config::RpcProgramAccountsConfig,
+    rpc_filter::{RpcFilterType, Memcmp}
+};
+use crate::{
+    ID as IDO_PROGRAM_ID,
+    pda,
+    instruction,
+    error::IDOProgramError,
+    vesting::LinearVestingStrategy,
+    state::{
+        AccountType,
+        IDOConfigAccount,
+        IDOVestingAccount,
+        TransferFeePayer,
+        ACCOUNT_VERSION
+    }
+};
+use spl_token_2022::offchain::{AccountDataResult, AccountFetchError};
+#[cfg(feature = "inspect")]
+use crate::inspect::AccountInspection;
+
+
+#[derive(Debug)]
+pub enum ProgramClientError {
+    Rpc(Box<ClientError>),
+    Banks(Box<BanksClientError>),
+    /// The program rejected the instruction with one of its own errors.
+    Program(IDOProgramError),
+    /// Any other transaction error, e.g. insufficient funds or the error of a foreign program.
+    ///
+    /// Custom errors of the invoked token programs are reported under the program's instruction,
+    /// so they're told apart by the logs & end up here, even if the code is shared with `IDOProgramError`.
+    Transaction(TransactionError),
+    /// The extra accounts required by the mint's transfer hook couldn't be resolved.
+    TransferHookAccounts(AccountFetchError),
+    AccountNotFound(Pubkey),
+    InvalidAccountData {
+        address: Pubkey,
+        error: ProgramError
+    },
+    /// The RPC method isn't provided by the transport, e.g. `getProgramAccounts` by `BanksClient`.
+    UnsupportedMethod(&'static str)
+}
+
+impl From<ClientError> for ProgramClientError {
+    fn from(value: ClientError) -> Self {
+        Self::Rpc(Box::new(value))
+    }
+}
+
+impl From<BanksClientError> for ProgramClientError {
+    fn from(value: BanksClientError) -> Self {
+        Self::Banks(Box::new(value))
+    }
+}
+
+impl From<std::io::Error> for ProgramClientError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Banks(Box::new(BanksClientError::Io(value)))
+    }
+}
+
+impl std::error::Error for ProgramClientError {}
+impl std::fmt::Display for ProgramClientError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Rpc(err) => write!(f, "{}", err),
+            Self::Banks(err) => write!(f, "{}", err),
+            Self::Program(err) => write!(f, "program error: {:?} ({})", err, err),
+            Self::Transaction(err) => write!(f, "transaction error: {}", err),
+            Self::TransferHookAccounts(err) => write!(f, "transfer hook accounts: {}", err),
+            Self::AccountNotFound(address) => write!(f, "account not found: {}", address),
+            Self::InvalidAccountData { address, error } => write!(f, "invalid account data {}: {}", address, error),
+            Self::UnsupportedMethod(method) => write!(f, "{} isn't supported by the transport", method)
+        }
+    }
+}
+
+impl ProgramClientError {
+    /// Transaction error, the transport failed with.
+    pub fn transaction_error(&self) -> Option<TransactionError> {
+        match self {
+            Self::Rpc(err) => err.get_transaction_error(),
+            Self::Banks(err) => match err.as_ref() {
+                BanksClientError::TransactionError(err)
+                | BanksClientError::SimulationError { err, .. } => Some(err.clone()),
+                _ => None
+            },
+            Self::Transaction(err) => Some(err.clone()),
+            _ => None
+        }
+    }
+
+    /// Decodes the custom error of the program out of the transport error & the transaction logs.
+    ///
+    /// A CPI that fails makes every caller fail with the same code, so the error belongs to the program
+    /// only if the first failed program in the logs is the program itself. Without logs it isn't decoded.
+    fn decode(self, logs: &[String]) -> Self {
+        let Some(tx_error) = self.transaction_error() else {
+            return self;
+        };
+
+        if let TransactionError::InstructionError(_, InstructionError::Custom(code)) = tx_error {
+            let failed_program_id: Option<Pubkey> = logs
+                .iter()
+                .find_map(|log| log.strip_prefix("Program ")?.split_once(" failed: ")?.0.parse().ok());
+
+            if let (Some(IDO_PROGRAM_ID), Ok(error)) = (failed_program_id, IDOProgramError::try_from(code)) {
+                return Self::Program(error);
+            }
+        }
+
+        Self::Transaction(tx_error)
+    }
+}
+
+pub enum ProgramClientTransport<'a> {
+    Rpc(&'a RpcClient),
+    Banks(&'a mut BanksClient),
+    ProgramTest(&'a mut ProgramTestContext)
+}
+
+impl ProgramClientTransport<'_> {
+    /// Identical transactions signed with the same blockhash have the same signature, so `BanksClient` waits for a new one.
+    async fn get_new_latest_blockhash(&mut self, last_blockhash: Option<Hash>) -> Result<Hash, ProgramClientError> {
+        Ok(match (self, last_blockhash) {
+            (Self::Rpc(client), _) => client.get_latest_blockhash().await?,
+            (Self::Banks(client), Some(last_blockhash)) => client.get_new_latest_blockhash(&last_blockhash).await?,
+            (Self::Banks(client), None) => client.get_latest_blockhash().await?,
+            (Self::ProgramTest(context), _) => context.get_new_latest_blockhash().await?
+        })
+    }
+
+    /// Errors of the program are decoded by the logs, i.e. the preflight logs of `RpcClient`.
+    async fn process_transaction(&mut self, tx: Transaction) -> Result<(), ProgramClientError> {
+        let banks_client: &BanksClient = match self {
+            Self::Rpc(client) => {
+                return match client.send_and_confirm_transaction(&tx).await {
+                    Ok(_) => Ok(()),
+                    Err(err) => {
+                        let logs: Vec<String> = match err.kind() {
+                            ClientErrorKind::RpcError(RpcError::RpcResponseError {
+                                data: RpcResponseErrorData::SendTransactionPreflightFailure(simulation), ..
+                            }) => simulation.logs.clone().unwrap_or_default(),
+                            _ => Vec::new()
+                        };
+
+                        Err(ProgramClientError::from(err).decode(&logs))
+                    }
+                };
+            },
+            Self::Banks(client) => client,
+            Self::ProgramTest(context) => &context.banks_client
+        };
+
+        let BanksTransactionResultWithMetadata { result, metadata } = banks_client.process_transaction_with_metadata(tx).await?;
+        let logs: Vec<String> = metadata.map(|metadata| metadata.log_messages).unwrap_or_default();
+
+        result.map_err(|err| ProgramClientError::Transaction(err).decode(&logs))
+    }
+
+    async fn get_account(&mut self, address: &Pubkey) -> Result<Option<Account>, ProgramClientError> {
+        Ok(match self {
+            Self::Rpc(client) => client.get_account_with_commitment(address, client.commitment()).await?.value,
+            Self::Banks(client) => client.get_account(*address).await?,
+            Self::ProgramTest(context) => context.banks_client.get_account(*address).await?
+        })
+    }
+
+    /// Data of the account, as expected by `add_transfer_hook_accounts`.
+    async fn get_account_data(&self, address: Pubkey) -> AccountDataResult {
+        Ok(match self {
+            Self::Rpc(client) => client
+                .get_account_with_commitment(&address, client.commitment())
+                .await?
+                .value
+                .map(|account| account.data),
+            Self::Banks(client) => client.get_account(address).await?.map(|account| account.data),
+            Self::ProgramTest(context) => context.banks_client.get_account(address).await?.map(|account| account.data)
+        })
+    }
+
+    async fn get_program_accounts(&mut self, filters: Vec<RpcFilterType>) -> Result<Vec<(Pubkey, Account)>, ProgramClientError> {
+        match self {
+            Self::Rpc(client) => {
+                let config: RpcProgramAccountsConfig = RpcProgramAccountsConfig {
+                    filters: Some(filters),
+                    ..RpcProgramAccountsConfig::default()
+                };
+                Ok(client.get_program_accounts_with_config(&IDO_PROGRAM_ID, config).await?)
+            },
+            Self::Banks(_) | Self::ProgramTest(_) => Err(ProgramClientError::UnsupportedMethod("getProgramAccounts"))
+        }
+    }
+
+    async fn get_clock(&mut self) -> Result<Clock, ProgramClientError> {
+        match self {
+            Self::Rpc(client) => {
+                let clock_account: Account = client.get_account(&sysvar::clock::ID).await?;
+                from_account::<Clock, _>(&clock_account).ok_or(ProgramClientError::InvalidAccountData {
+                    address: sysvar::clock::ID,
+                    error: ProgramError::InvalidAccountData
+                })
+            },
+            Self::Banks(client) => Ok(client.get_sysvar::<Clock>().await?),
+            Self::ProgramTest(context) => Ok(context.banks_client.get_sysvar::<Clock>().await?)
+        }
+    }
+}
+
+/// Accounts of the current layout of `account_type`: `dataSize` & `memcmp` of the `[account_type, version]` header.
+pub fn account_type_filters(account_type: AccountType) -> Vec<RpcFilterType> {
+    vec![
+        RpcFilterType::DataSize(account_type.account_len() as u64),
+        RpcFilterType::Memcmp(Memcmp::new_base58_encoded(0, &[account_type as u8, ACCOUNT_VERSION]))
+    ]
+}
+
+/// Configs of every IDO launched by the program.
+pub fn ido_filters() -> Vec<RpcFilterType> {
+    account_type_filters(AccountType::Config)
+}
+
+/// Vestings of every buyer of the IDO defined by `mint`.
+pub fn vesting_filters_for_mint(mint: &Pubkey) -> Vec<RpcFilterType> {
+    let mut filters: Vec<RpcFilterType> = account_type_filters(AccountType::Vesting);
+    filters.push(RpcFilterType::Memcmp(Memcmp::new_base58_encoded(IDOVestingAccount::MINT_OFFSET, mint.as_ref())));
+
+    filters
+}
+
+/// Client of the single IDO, which is defined by its mint.
+pub struct ProgramClient<'a> {
+    transport: ProgramClientTransport<'a>,
+    payer: &'a Keypair,
+    mint_pkey: Pubkey,
+    token_program_id: Pubkey,
+    treasury_pda: Pubkey,
+    config_pda: Pubkey,
+    last_blockhash: Option<Hash>
+}
+
+impl<'a> ProgramClient<'a> {
+    /// `token_program_id` must be the program that owns the mint: either SPL Token or SPL Token 2022.
+    pub fn new(
+        transport: ProgramClientTransport<'a>,
+        payer: &'a Keypair,
+        mint_pkey: Pubkey,
+        token_program_id: Pubkey
+    ) -> Self {
+        Self {
+            transport,
+            payer,
+            mint_pkey,
+            token_program_id,
+            treasury_pda: pda::find_treasury(&mint_pkey).0,
+            config_pda: pda::find_config(&mint_pkey).0,
+            last_blockhash: None
+        }
+    }
+
+    /// E.g. to warp `ProgramTestContext` between the transactions.
+    pub fn transport(&mut self) -> &mut ProgramClientTransport<'a> {
+        &mut self.transport
+    }
+
+    pub fn treasury_pda(&self) -> Pubkey {
+        self.treasury_pda
+    }
+
+    pub fn config_pda(&self) -> Pubkey {
+        self.config_pda
+    }
+
+    pub fn vesting_pda(&self, owner: &Pubkey) -> Pubkey {
+        pda::find_vesting(owner, &self.mint_pkey).0
+    }
+
+    /// Deposits `amount` (with decimals) from the payer's ATA into the treasury.
+    ///
+    /// The extra accounts of the mint's transfer hook are resolved & appended.
+    pub async fn initialize(
+        &mut self,
+        amount: u64,
+        lamports_per_token: u32,
+        vesting_strategy: &LinearVestingStrategy,
+        transfer_fee_payer: TransferFeePayer
+    ) -> Result<Signature, ProgramClientError> {
+        let mut initialize_ido_ix: Instruction = instruction::create_initialize_with_vesting_for_mint(
+            amount,
+            lamports_per_token,
+            vesting_strategy,
+            transfer_fee_payer,
+            &self.payer.pubkey(),
+            &self.mint_pkey,
+            &self.token_program_id
+        );
+
+        self.add_transfer_hook_accounts(&mut initialize_ido_ix).await?;
+        self.process_instructions(&[initialize_ido_ix]).await
+    }
+
+    /// `amount` of tokens without decimals.
+    pub async fn buy(&mut self, amount: u64) -> Result<Signature, ProgramClientError> {
+        let buy_ix: Instruction = instruction::create_buy_with_vesting_for_mint(amount, &self.payer.pubkey(), &self.mint_pkey);
+
+        self.process_instructions(&[buy_ix]).await
+    }
+
+    /// Claims the unlocked portion of the payer's vesting to the `recipient`'s ATA, which is created if it doesn't exist.
+    ///
+    /// The extra accounts of the mint's transfer hook are resolved & appended, see `instruction::add_transfer_hook_accounts`.
+    pub async fn claim(&mut self, recipient: &Pubkey) -> Result<Signature, ProgramClientError> {
+        let mut claim_ix: Instruction = instruction::create_claim_for_mint(
+            &self.payer.pubkey(),
+            recipient,
+            &self.mint_pkey,
+            &self.token_program_id
+        );
+
+        self.add_transfer_hook_accounts(&mut claim_ix).await?;
+        self.process_instructions(&[claim_ix]).await
+    }
+
+    /// Withdraws the raised LAMPORTS & (once the vesting is over) the unsold supply, the payer must be the IDO's authority.
+    ///
+    /// The unsold supply is transferred to the payer's ATA, which must exist.
+    pub async fn withdraw(&mut self) -> Result<Signature, ProgramClientError> {
+        let mut withdraw_ix: Instruction = instruction::create_withdraw_for_mint(
+            &self.payer.pubkey(),
+            &self.mint_pkey,
+            &self.token_program_id
+        );
+
+        self.add_transfer_hook_accounts(&mut withdraw_ix).await?;
+        self.process_instructions(&[withdraw_ix]).await
+    }
+
+    async fn add_transfer_hook_accounts(&self, ix: &mut Instruction) -> Result<(), ProgramClientError> {
+        let transport: &ProgramClientTransport = &self.transport;
+
+        instruction::add_transfer_hook_accounts(ix, |address| transport.get_account_data(address))
+            .await
+            .map_err(ProgramClientError::TransferHookAccounts)
+    }
+
+    pub async fn fetch_config(&mut self) -> Result<IDOConfigAccount, ProgramClientError> {
+        let mint_pkey: Pubkey = self.mint_pkey;
+        self.fetch_config_for_mint(&mint_pkey).await
+    }
+
+    async fn fetch_config_for_mint(&mut self, mint: &Pubkey) -> Result<IDOConfigAccount, ProgramClientError> {
+        let config_pda: Pubkey = pda::find_config(mint).0;
+        let data: Vec<u8> = self.fetch_account_data(&config_pda).await?;
+
+        IDOConfigAccount::unpack(&data).map_err(|error| ProgramClientError::InvalidAccountData { address: config_pda, error })
+    }
+
+    pub async fn fetch_vesting(&mut self, owner: &Pubkey) -> Result<IDOVestingAccount, ProgramClientError> {
+        let vesting_pda: Pubkey = self.vesting_pda(owner);
+        let data: Vec<u8> = self.fetch_account_data(&vesting_pda).await?;
+
+        IDOVestingAccount::unpack(&data).map_err(|error| ProgramClientError::InvalidAccountData { address: vesting_pda, error })
+    }
+
+    pub async fn fetch_account_data(&mut self, address: &Pubkey) -> Result<Vec<u8>, ProgramClientError> {
+        self.transport
+            .get_account(address)
+            .await?
+            .map(|account| account.data)
+            .ok_or(ProgramClientError::AccountNotFound(*address))
+    }
+
+    /// Cluster time, which may differ from the local one, e.g. on `solana-test-validator` or after warping `ProgramTestContext`.
+    pub async fn fetch_clock(&mut self) -> Result<Clock, ProgramClientError> {
+        self.transport.get_clock().await
+    }
+
+    /// Every buyer of the IDO defined by `mint` (not necessarily the client's one), paired with the vesting address.
+    pub async fn list_vestings_for_mint(&mut self, mint: &Pubkey) -> Result<Vec<(Pubkey, IDOVestingAccount)>, ProgramClientError> {
+        self.list_program_accounts(vesting_filters_for_mint(mint), IDOVestingAccount::unpack).await
+    }
+
+    /// Every IDO launched by the program, paired with the config address.
+    pub async fn list_idos(&mut self) -> Result<Vec<(Pubkey, IDOConfigAccount)>, ProgramClientError> {
+        self.list_program_accounts(ido_filters(), IDOConfigAccount::unpack).await
+    }
+
+    async fn list_program_accounts<T>(
+        &mut self,
+        filters: Vec<RpcFilterType>,
+        unpack: fn(&[u8]) -> Result<T, ProgramError>
+    ) -> Result<Vec<(Pubkey, T)>, ProgramClientError> {
+        self.transport
+            .get_program_accounts(filters)
+            .await?
+            .into_iter()
+            .map(|(address, account)| {
+                unpack(&account.data)
+                    .map(|decoded_account| (address, decoded_account))
+                    .map_err(|error| ProgramClientError::InvalidAccountData { address, error })
+            })
+            .collect()
+    }
+
+    /// Decodes any account owned by the program at the cluster time.
+    ///
+    /// The progress of Vesting is defined by the config of the mint it stores, it's omitted if the config doesn't exist.
+    #[cfg(feature = "inspect")]
+    pub async fn inspect(&mut self, address: &Pubkey) -> Result<AccountInspection, ProgramClientError> {
+        let account: Account = self.transport
+            .get_account(address)
+            .await?
+            .ok_or(ProgramClientError::AccountNotFound(*address))?;
+
+        if account.owner != IDO_PROGRAM_ID {
+            return Err(ProgramClientError::InvalidAccountData { address: *address, error: ProgramError::IncorrectProgramId });
+        }
+
+        let config_account: Option<IDOConfigAccount> = match IDOVestingAccount::unpack(&account.data) {
+            Ok(vesting_account) => match self.fetch_config_for_mint(&vesting_account.mint).await {
+                Ok(config_account) => Some(config_account),
+                Err(ProgramClientError::AccountNotFound(_)) => None,
+                Err(e) => return Err(e)
+            },
+            Err(_) => None
+        };
+        let now_ts: i64 = self.fetch_clock().await?.unix_timestamp;
+
+        AccountInspection::new(*address, &account.data, config_account.as_ref(), now_ts)
+            .map_err(|error| ProgramClientError::InvalidAccountData { address: *address, error })
+    }
+
+    /// Signs the instructions by the payer with a fresh blockhash & processes them in a single transaction.
+    pub async fn process_instructions(&mut self, ixs: &[Instruction]) -> Result<Signature, ProgramClientError> {
+        let latest_blockhash: Hash = self.transport.get_new_latest_blockhash(self.last_blockhash).await?;
+        self.last_blockhash = Some(latest_blockhash);
+
+        let message: Message = Message::new(ixs, Some(&self.payer.pubkey()));
+        let mut tx: Transaction = Transaction::new_unsigned(message);
+
+        tx.sign(&[self.payer], latest_blockhash);
+        let signature: Signature = tx.signatures[0];
+
+        self.transport.process_transaction(tx).await?;
+
+        Ok(signature)
+    }
+}
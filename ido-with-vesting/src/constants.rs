@@ -1,5 +1,16 @@
 pub const MAX_UNLOCKS: u8 = 100;
 
+/// Basis points of the whole bought amount, used by the unlock schedule.
+pub const MAX_BPS: u16 = 10_000;
+
+/// Amount of discrete price levels supported by the batch auction's demand book.
+pub const AUCTION_PRICE_LEVELS: usize = 64;
+
+/// Memo attached to claims, whose recipients require incoming transfer memos.
+pub const CLAIM_MEMO: &[u8] = b"IDO vesting claim";
+
 pub const IDO_TREASURY_ACCOUNT_SEED: &[u8] = b"ido-treasury-account";
 pub const IDO_CONFIG_ACCOUNT_SEED: &[u8] = b"ido-config-account";
-pub const IDO_VESTING_ACCOUNT_SEED: &[u8] = b"ido-vesting-account";
\ No newline at end of file
+pub const IDO_VESTING_ACCOUNT_SEED: &[u8] = b"ido-vesting-account";
+pub const IDO_AUCTION_ACCOUNT_SEED: &[u8] = b"ido-auction-account";
+pub const IDO_BID_ACCOUNT_SEED: &[u8] = b"ido-bid-account";
\ No newline at end of file
@@ -2,6 +2,14 @@ use solana_program::{
     program_error::ProgramError,
     account_info::{next_account_info, AccountInfo}
 };
+use crate::state::IDOConfigAccount;
+use super::checks::{
+    check_signer,
+    check_program_or_uninitialized_account,
+    check_token_account,
+    check_system_program,
+    unpack_config_account
+};
 
 
 pub struct IDOBuyWithVestingCtx<'a, 'b> {
@@ -10,7 +18,9 @@ pub struct IDOBuyWithVestingCtx<'a, 'b> {
     pub treasury_info: &'a AccountInfo<'b>,
     pub config_info: &'a AccountInfo<'b>,
     pub mint_info: &'a AccountInfo<'b>,
-    pub system_program_info: &'a AccountInfo<'b>
+    pub system_program_info: &'a AccountInfo<'b>,
+    /// Unpacked during validation, since its Mint & Treasury are cross-checked with the provided accounts.
+    pub config_account: IDOConfigAccount
 }
 
 impl<'a, 'b> TryFrom<&'a [AccountInfo<'b>]> for IDOBuyWithVestingCtx<'a, 'b> {
@@ -19,13 +29,26 @@ impl<'a, 'b> TryFrom<&'a [AccountInfo<'b>]> for IDOBuyWithVestingCtx<'a, 'b> {
     fn try_from(accounts: &'a [AccountInfo<'b>]) -> Result<Self, Self::Error> {
         let accounts_iter = &mut accounts.iter();
         
+        let signer_info: &AccountInfo = next_account_info(accounts_iter)?;
+        let vesting_info: &AccountInfo = next_account_info(accounts_iter)?;
+        let treasury_info: &AccountInfo = next_account_info(accounts_iter)?;
+        let config_info: &AccountInfo = next_account_info(accounts_iter)?;
+        let mint_info: &AccountInfo = next_account_info(accounts_iter)?;
+        let system_program_info: &AccountInfo = next_account_info(accounts_iter)?;
+
+        check_signer(signer_info)?;
+        check_program_or_uninitialized_account(vesting_info)?;
+        check_token_account(treasury_info, mint_info)?;
+        check_system_program(system_program_info)?;
+
         Ok(Self {
-            signer_info: next_account_info(accounts_iter)?,
-            vesting_info: next_account_info(accounts_iter)?,
-            treasury_info: next_account_info(accounts_iter)?,
-            config_info: next_account_info(accounts_iter)?,
-            mint_info: next_account_info(accounts_iter)?,
-            system_program_info: next_account_info(accounts_iter)?
+            signer_info,
+            vesting_info,
+            treasury_info,
+            config_info,
+            mint_info,
+            system_program_info,
+            config_account: unpack_config_account(config_info, mint_info, treasury_info)?
         })
     }    
 }
\ No newline at end of file
@@ -0,0 +1,124 @@
+use std::cell::Ref;
+use solana_program::{
+    pubkey::Pubkey,
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    system_program::ID as SYSTEM_PROGRAM_ID
+};
+use spl_token_2022::{
+    check_spl_token_program_account,
+    state::Account,
+    extension::StateWithExtensions
+};
+use crate::{
+    error::IDOProgramError,
+    state::IDOConfigAccount
+};
+
+
+pub fn check_signer(info: &AccountInfo) -> ProgramResult {
+    if !info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    Ok(())
+}
+
+/// Config, Vesting, Auction & Bid accounts must be owned by the program, otherwise their data can be forged.
+pub fn check_program_account(info: &AccountInfo) -> ProgramResult {
+    if *info.owner != crate::ID {
+        return Err(IDOProgramError::InvalidAccountOwner.into());
+    }
+
+    Ok(())
+}
+
+/// Account that doesn't exist yet is owned by the System Program & has no data.
+pub fn check_uninitialized_account(info: &AccountInfo) -> ProgramResult {
+    if *info.owner != SYSTEM_PROGRAM_ID {
+        return Err(IDOProgramError::InvalidAccountOwner.into());
+    }
+
+    if !info.data_is_empty() {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    Ok(())
+}
+
+/// Used for accounts that are created on the first use (e.g. Vesting PDA on the first buy).
+pub fn check_program_or_uninitialized_account(info: &AccountInfo) -> ProgramResult {
+    if *info.owner == crate::ID {
+        return Ok(());
+    }
+
+    check_uninitialized_account(info)
+}
+
+pub fn check_program_id(info: &AccountInfo, expected_program_id: &Pubkey) -> ProgramResult {
+    if info.key != expected_program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    Ok(())
+}
+
+pub fn check_system_program(info: &AccountInfo) -> ProgramResult {
+    check_program_id(info, &SYSTEM_PROGRAM_ID)
+}
+
+/// Token accounts (e.g. Treasury) must be owned by the same token program that owns the mint.
+pub fn check_token_account(token_account_info: &AccountInfo, mint_info: &AccountInfo) -> ProgramResult {
+    check_spl_token_program_account(mint_info.owner)?;
+
+    if token_account_info.owner != mint_info.owner {
+        return Err(IDOProgramError::InvalidAccountOwner.into());
+    }
+
+    Ok(())
+}
+
+/// Existing recipient token account must belong to the recipient & the mint, so the claim can't be redirected to another wallet.
+pub fn check_recipient_token_account(
+    recipient_token_account_info: &AccountInfo,
+    recipient_info: &AccountInfo,
+    mint_info: &AccountInfo
+) -> ProgramResult {
+    check_token_account(recipient_token_account_info, mint_info)?;
+
+    let recipient_token_account_data_ref: Ref<&mut [u8]> = recipient_token_account_info.data.borrow();
+    let recipient_token_account: Account = StateWithExtensions::<Account>::unpack(*recipient_token_account_data_ref)?.base;
+
+    if recipient_token_account.owner != *recipient_info.key {
+        return Err(IDOProgramError::RecipientOwnerMismatch.into());
+    }
+
+    if recipient_token_account.mint != *mint_info.key {
+        return Err(IDOProgramError::RecipientMintMismatch.into());
+    }
+
+    Ok(())
+}
+
+/// Unpacks Config account & cross-checks the Mint & Treasury it was initialized for.
+pub fn unpack_config_account(
+    config_info: &AccountInfo,
+    mint_info: &AccountInfo,
+    treasury_info: &AccountInfo
+) -> Result<IDOConfigAccount, ProgramError> {
+    check_program_account(config_info)?;
+
+    let config_data_ref: Ref<&mut [u8]> = config_info.data.borrow();
+    let config_account: IDOConfigAccount = *IDOConfigAccount::load(&config_data_ref)?;
+
+    if config_account.mint != *mint_info.key {
+        return Err(IDOProgramError::ConfigMintMismatch.into());
+    }
+
+    if config_account.treasury != *treasury_info.key {
+        return Err(IDOProgramError::ConfigTreasuryMismatch.into());
+    }
+
+    Ok(config_account)
+}
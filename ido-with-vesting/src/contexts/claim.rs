@@ -1,6 +1,21 @@
 use solana_program::{
     program_error::ProgramError,
-    account_info::{next_account_info, AccountInfo}
+    account_info::{next_account_info, AccountInfo},
+    system_program::ID as SYSTEM_PROGRAM_ID
+};
+use crate::{
+    external_ids::{ATA_PROGRAM_ID, MEMO_PROGRAM_ID},
+    state::IDOConfigAccount,
+    token::check_token_program
+};
+use super::checks::{
+    check_signer,
+    check_program_or_uninitialized_account,
+    check_token_account,
+    check_recipient_token_account,
+    check_program_id,
+    check_system_program,
+    unpack_config_account
 };
 
 
@@ -14,7 +29,13 @@ pub struct IDOClaimCtx<'a, 'b> {
     pub mint_info: &'a AccountInfo<'b>,
     pub associated_token_program_info: &'a AccountInfo<'b>,
     pub token_program_info: &'a AccountInfo<'b>,
-    pub system_program_info: &'a AccountInfo<'b>
+    pub system_program_info: &'a AccountInfo<'b>,
+    /// Used only if the recipient's Token-2022 account requires incoming transfer memos, but it's always validated.
+    pub memo_program_info: &'a AccountInfo<'b>,
+    /// Extra accounts required by Token-2022 transfer hook (if the mint has one).
+    pub remaining_accounts: &'a [AccountInfo<'b>],
+    /// Unpacked during validation, since its Mint & Treasury are cross-checked with the provided accounts.
+    pub config_account: IDOConfigAccount
 }
 
 impl<'a, 'b> TryFrom<&'a [AccountInfo<'b>]> for IDOClaimCtx<'a, 'b> {
@@ -23,17 +44,45 @@ impl<'a, 'b> TryFrom<&'a [AccountInfo<'b>]> for IDOClaimCtx<'a, 'b> {
     fn try_from(accounts: &'a [AccountInfo<'b>]) -> Result<Self, Self::Error> {
         let accounts_iter = &mut accounts.iter();
         
+        let signer_info: &AccountInfo = next_account_info(accounts_iter)?;
+        let recipient_info: &AccountInfo = next_account_info(accounts_iter)?;
+        let recipient_ata_info: &AccountInfo = next_account_info(accounts_iter)?;
+        let vesting_info: &AccountInfo = next_account_info(accounts_iter)?;
+        let treasury_info: &AccountInfo = next_account_info(accounts_iter)?;
+        let config_info: &AccountInfo = next_account_info(accounts_iter)?;
+        let mint_info: &AccountInfo = next_account_info(accounts_iter)?;
+        let associated_token_program_info: &AccountInfo = next_account_info(accounts_iter)?;
+        let token_program_info: &AccountInfo = next_account_info(accounts_iter)?;
+        let system_program_info: &AccountInfo = next_account_info(accounts_iter)?;
+        let memo_program_info: &AccountInfo = next_account_info(accounts_iter)?;
+
+        check_signer(signer_info)?;
+        check_program_or_uninitialized_account(vesting_info)?;
+        check_token_program(token_program_info, mint_info)?;
+        check_token_account(treasury_info, mint_info)?;
+        check_program_id(associated_token_program_info, &ATA_PROGRAM_ID)?;
+        check_system_program(system_program_info)?;
+        check_program_id(memo_program_info, &MEMO_PROGRAM_ID)?;
+
+        // recipient ATA is created on the first claim (the ATA program derives its address from the recipient & the mint)
+        if *recipient_ata_info.owner != SYSTEM_PROGRAM_ID {
+            check_recipient_token_account(recipient_ata_info, recipient_info, mint_info)?;
+        }
+
         Ok(Self {
-            signer_info: next_account_info(accounts_iter)?,
-            recipient_info: next_account_info(accounts_iter)?,
-            recipient_ata_info: next_account_info(accounts_iter)?,
-            vesting_info: next_account_info(accounts_iter)?,
-            treasury_info: next_account_info(accounts_iter)?,
-            config_info: next_account_info(accounts_iter)?,
-            mint_info: next_account_info(accounts_iter)?,
-            associated_token_program_info: next_account_info(accounts_iter)?,
-            token_program_info: next_account_info(accounts_iter)?,
-            system_program_info: next_account_info(accounts_iter)?
+            signer_info,
+            recipient_info,
+            recipient_ata_info,
+            vesting_info,
+            treasury_info,
+            config_info,
+            mint_info,
+            associated_token_program_info,
+            token_program_info,
+            system_program_info,
+            memo_program_info,
+            remaining_accounts: accounts_iter.as_slice(),
+            config_account: unpack_config_account(config_info, mint_info, treasury_info)?
         })
     }
 }
\ No newline at end of file
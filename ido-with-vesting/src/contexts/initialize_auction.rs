@@ -0,0 +1,31 @@
+use solana_program::{
+    program_error::ProgramError,
+    account_info::{next_account_info, AccountInfo}
+};
+use super::{
+    IDOInitializeCtx,
+    checks::check_uninitialized_account
+};
+
+
+/// Extends `IDOInitializeCtx` with the Auction PDA, which is expected to be the first account.
+pub struct IDOInitializeAuctionCtx<'a, 'b> {
+    pub auction_info: &'a AccountInfo<'b>,
+    pub ido_ctx: IDOInitializeCtx<'a, 'b>
+}
+
+impl<'a, 'b> TryFrom<&'a [AccountInfo<'b>]> for IDOInitializeAuctionCtx<'a, 'b> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo<'b>]) -> Result<Self, Self::Error> {
+        let accounts_iter = &mut accounts.iter();
+
+        let auction_info: &AccountInfo = next_account_info(accounts_iter)?;
+        check_uninitialized_account(auction_info)?;
+
+        Ok(Self {
+            auction_info,
+            ido_ctx: accounts_iter.as_slice().try_into()?
+        })
+    }
+}
\ No newline at end of file
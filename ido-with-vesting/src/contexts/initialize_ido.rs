@@ -2,6 +2,12 @@ use solana_program::{
     program_error::ProgramError,
     account_info::{next_account_info, AccountInfo}
 };
+use crate::token::check_token_program;
+use super::checks::{
+    check_signer,
+    check_uninitialized_account,
+    check_system_program
+};
 
 
 pub struct IDOInitializeCtx<'a, 'b> {
@@ -10,9 +16,10 @@ pub struct IDOInitializeCtx<'a, 'b> {
     pub treasury_info: &'a AccountInfo<'b>,
     pub config_info: &'a AccountInfo<'b>,
     pub mint_info: &'a AccountInfo<'b>,
-    pub rent_info: &'a AccountInfo<'b>,
     pub token_program_info: &'a AccountInfo<'b>,
-    pub system_program_info: &'a AccountInfo<'b>
+    pub system_program_info: &'a AccountInfo<'b>,
+    /// Extra accounts required by Token-2022 transfer hook (if the mint has one).
+    pub remaining_accounts: &'a [AccountInfo<'b>]
 }
 
 impl<'a, 'b> TryFrom<&'a [AccountInfo<'b>]> for IDOInitializeCtx<'a, 'b> {
@@ -21,15 +28,23 @@ impl<'a, 'b> TryFrom<&'a [AccountInfo<'b>]> for IDOInitializeCtx<'a, 'b> {
     fn try_from(accounts: &'a [AccountInfo<'b>]) -> Result<Self, Self::Error> {
         let accounts_iter = &mut accounts.iter();
 
-        Ok(Self {
+        let ctx: Self = Self {
             signer_info: next_account_info(accounts_iter)?,
             signer_ata_info: next_account_info(accounts_iter)?,
             treasury_info: next_account_info(accounts_iter)?,
             config_info: next_account_info(accounts_iter)?,
             mint_info: next_account_info(accounts_iter)?,
-            rent_info: next_account_info(accounts_iter)?,
             token_program_info: next_account_info(accounts_iter)?,
-            system_program_info: next_account_info(accounts_iter)?
-        })
+            system_program_info: next_account_info(accounts_iter)?,
+            remaining_accounts: accounts_iter.as_slice()
+        };
+
+        check_signer(ctx.signer_info)?;
+        check_uninitialized_account(ctx.treasury_info)?;
+        check_uninitialized_account(ctx.config_info)?;
+        check_token_program(ctx.token_program_info, ctx.mint_info)?;
+        check_system_program(ctx.system_program_info)?;
+
+        Ok(ctx)
     }
 }
\ No newline at end of file
@@ -0,0 +1,43 @@
+use solana_program::{
+    program_error::ProgramError,
+    account_info::{next_account_info, AccountInfo}
+};
+use super::checks::{
+    check_signer,
+    check_program_account,
+    check_system_program
+};
+
+
+pub struct IDOMigrateCtx<'a, 'b> {
+    /// Anyone can migrate the v1 Config (after the vesting end) or Vesting, `signer` only pays for the increased rent.
+    pub signer_info: &'a AccountInfo<'b>,
+    pub account_info: &'a AccountInfo<'b>,
+    /// Mint of the IDO the account belongs to, v1 Config has neither Mint nor Treasury address.
+    pub mint_info: &'a AccountInfo<'b>,
+    pub system_program_info: &'a AccountInfo<'b>,
+    /// Required only by v1 Vesting, which has no Owner, it's validated against the Vesting PDA.
+    pub owner_info: Option<&'a AccountInfo<'b>>
+}
+
+impl<'a, 'b> TryFrom<&'a [AccountInfo<'b>]> for IDOMigrateCtx<'a, 'b> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo<'b>]) -> Result<Self, Self::Error> {
+        let accounts_iter = &mut accounts.iter();
+
+        let ctx: Self = Self {
+            signer_info: next_account_info(accounts_iter)?,
+            account_info: next_account_info(accounts_iter)?,
+            mint_info: next_account_info(accounts_iter)?,
+            system_program_info: next_account_info(accounts_iter)?,
+            owner_info: accounts_iter.next()
+        };
+
+        check_signer(ctx.signer_info)?;
+        check_program_account(ctx.account_info)?;
+        check_system_program(ctx.system_program_info)?;
+
+        Ok(ctx)
+    }
+}
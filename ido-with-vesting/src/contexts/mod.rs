@@ -1,7 +1,20 @@
 mod initialize_ido;
 mod buy_with_vesting;
 mod claim;
+mod initialize_auction;
+mod place_bid;
+mod settle_auction;
+mod settle_bid;
+mod migrate;
+mod withdraw;
+mod checks;
 
 pub use initialize_ido::IDOInitializeCtx;
 pub use buy_with_vesting::IDOBuyWithVestingCtx;
-pub use claim::IDOClaimCtx;
\ No newline at end of file
+pub use claim::IDOClaimCtx;
+pub use initialize_auction::IDOInitializeAuctionCtx;
+pub use place_bid::IDOPlaceBidCtx;
+pub use settle_auction::IDOSettleAuctionCtx;
+pub use settle_bid::IDOSettleBidCtx;
+pub use migrate::IDOMigrateCtx;
+pub use withdraw::IDOWithdrawCtx;
\ No newline at end of file
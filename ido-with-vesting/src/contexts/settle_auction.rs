@@ -0,0 +1,44 @@
+use solana_program::{
+    program_error::ProgramError,
+    account_info::{next_account_info, AccountInfo}
+};
+use crate::state::IDOConfigAccount;
+use super::checks::{
+    check_program_account,
+    check_token_account,
+    unpack_config_account
+};
+
+
+pub struct IDOSettleAuctionCtx<'a, 'b> {
+    pub auction_info: &'a AccountInfo<'b>,
+    pub treasury_info: &'a AccountInfo<'b>,
+    pub config_info: &'a AccountInfo<'b>,
+    pub mint_info: &'a AccountInfo<'b>,
+    /// Unpacked during validation, its stored bumps are used to validate the Treasury & Config PDAs.
+    pub config_account: IDOConfigAccount
+}
+
+impl<'a, 'b> TryFrom<&'a [AccountInfo<'b>]> for IDOSettleAuctionCtx<'a, 'b> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo<'b>]) -> Result<Self, Self::Error> {
+        let accounts_iter = &mut accounts.iter();
+
+        let auction_info: &AccountInfo = next_account_info(accounts_iter)?;
+        let treasury_info: &AccountInfo = next_account_info(accounts_iter)?;
+        let config_info: &AccountInfo = next_account_info(accounts_iter)?;
+        let mint_info: &AccountInfo = next_account_info(accounts_iter)?;
+
+        check_program_account(auction_info)?;
+        check_token_account(treasury_info, mint_info)?;
+
+        Ok(Self {
+            auction_info,
+            treasury_info,
+            config_info,
+            mint_info,
+            config_account: unpack_config_account(config_info, mint_info, treasury_info)?
+        })
+    }
+}
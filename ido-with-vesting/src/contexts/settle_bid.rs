@@ -0,0 +1,67 @@
+use solana_program::{
+    program_error::ProgramError,
+    account_info::{next_account_info, AccountInfo}
+};
+use crate::state::IDOConfigAccount;
+use super::checks::{
+    check_signer,
+    check_program_account,
+    check_program_or_uninitialized_account,
+    check_token_account,
+    check_system_program,
+    unpack_config_account
+};
+
+
+pub struct IDOSettleBidCtx<'a, 'b> {
+    /// Anyone can crank the bid settlement, `signer` only pays for the Vesting PDA rent.
+    pub signer_info: &'a AccountInfo<'b>,
+    pub bidder_info: &'a AccountInfo<'b>,
+    pub bid_info: &'a AccountInfo<'b>,
+    pub vesting_info: &'a AccountInfo<'b>,
+    pub auction_info: &'a AccountInfo<'b>,
+    pub treasury_info: &'a AccountInfo<'b>,
+    pub config_info: &'a AccountInfo<'b>,
+    pub mint_info: &'a AccountInfo<'b>,
+    pub system_program_info: &'a AccountInfo<'b>,
+    /// Unpacked during validation, since its Mint & Treasury are cross-checked with the provided accounts.
+    pub config_account: IDOConfigAccount
+}
+
+impl<'a, 'b> TryFrom<&'a [AccountInfo<'b>]> for IDOSettleBidCtx<'a, 'b> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo<'b>]) -> Result<Self, Self::Error> {
+        let accounts_iter = &mut accounts.iter();
+
+        let signer_info: &AccountInfo = next_account_info(accounts_iter)?;
+        let bidder_info: &AccountInfo = next_account_info(accounts_iter)?;
+        let bid_info: &AccountInfo = next_account_info(accounts_iter)?;
+        let vesting_info: &AccountInfo = next_account_info(accounts_iter)?;
+        let auction_info: &AccountInfo = next_account_info(accounts_iter)?;
+        let treasury_info: &AccountInfo = next_account_info(accounts_iter)?;
+        let config_info: &AccountInfo = next_account_info(accounts_iter)?;
+        let mint_info: &AccountInfo = next_account_info(accounts_iter)?;
+        let system_program_info: &AccountInfo = next_account_info(accounts_iter)?;
+
+        check_signer(signer_info)?;
+        check_program_account(bid_info)?;
+        check_program_or_uninitialized_account(vesting_info)?;
+        check_program_account(auction_info)?;
+        check_token_account(treasury_info, mint_info)?;
+        check_system_program(system_program_info)?;
+
+        Ok(Self {
+            signer_info,
+            bidder_info,
+            bid_info,
+            vesting_info,
+            auction_info,
+            treasury_info,
+            config_info,
+            mint_info,
+            system_program_info,
+            config_account: unpack_config_account(config_info, mint_info, treasury_info)?
+        })
+    }
+}
\ No newline at end of file
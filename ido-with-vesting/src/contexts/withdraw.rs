@@ -0,0 +1,61 @@
+use solana_program::{
+    program_error::ProgramError,
+    account_info::{next_account_info, AccountInfo}
+};
+use crate::{
+    state::IDOConfigAccount,
+    token::check_token_program
+};
+use super::checks::{
+    check_signer,
+    check_token_account,
+    check_recipient_token_account,
+    unpack_config_account
+};
+
+
+pub struct IDOWithdrawCtx<'a, 'b> {
+    /// Must be the config's authority, receives the raised LAMPORTS.
+    pub authority_info: &'a AccountInfo<'b>,
+    /// Receives the unsold supply, so it must be the authority's token account of the mint.
+    pub authority_ata_info: &'a AccountInfo<'b>,
+    pub treasury_info: &'a AccountInfo<'b>,
+    pub config_info: &'a AccountInfo<'b>,
+    pub mint_info: &'a AccountInfo<'b>,
+    pub token_program_info: &'a AccountInfo<'b>,
+    /// Extra accounts required by Token-2022 transfer hook (if the mint has one).
+    pub remaining_accounts: &'a [AccountInfo<'b>],
+    /// Unpacked during validation, since its Mint & Treasury are cross-checked with the provided accounts.
+    pub config_account: IDOConfigAccount
+}
+
+impl<'a, 'b> TryFrom<&'a [AccountInfo<'b>]> for IDOWithdrawCtx<'a, 'b> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo<'b>]) -> Result<Self, Self::Error> {
+        let accounts_iter = &mut accounts.iter();
+
+        let authority_info: &AccountInfo = next_account_info(accounts_iter)?;
+        let authority_ata_info: &AccountInfo = next_account_info(accounts_iter)?;
+        let treasury_info: &AccountInfo = next_account_info(accounts_iter)?;
+        let config_info: &AccountInfo = next_account_info(accounts_iter)?;
+        let mint_info: &AccountInfo = next_account_info(accounts_iter)?;
+        let token_program_info: &AccountInfo = next_account_info(accounts_iter)?;
+
+        check_signer(authority_info)?;
+        check_token_program(token_program_info, mint_info)?;
+        check_token_account(treasury_info, mint_info)?;
+        check_recipient_token_account(authority_ata_info, authority_info, mint_info)?;
+
+        Ok(Self {
+            authority_info,
+            authority_ata_info,
+            treasury_info,
+            config_info,
+            mint_info,
+            token_program_info,
+            remaining_accounts: accounts_iter.as_slice(),
+            config_account: unpack_config_account(config_info, mint_info, treasury_info)?
+        })
+    }
+}
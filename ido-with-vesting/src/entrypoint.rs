@@ -1,13 +1,18 @@
 #![allow(unexpected_cfgs)]
+#[allow(deprecated)]
 use solana_program::{
     pubkey::Pubkey,
     account_info::AccountInfo,
+    program_error::PrintProgramError,
     entrypoint::{
         entrypoint, 
         ProgramResult
     }
 };
-use super::processor::Processor;
+use super::{
+    processor::Processor,
+    error::IDOProgramError
+};
 
 
 entrypoint!(process_instruction);
@@ -17,7 +22,11 @@ pub fn process_instruction(
     accounts: &[AccountInfo],
     data: &[u8]
 ) -> ProgramResult {
-    Processor::process(program_id, accounts, data)?;
+    if let Err(error) = Processor::process(program_id, accounts, data) {
+        #[allow(deprecated)]
+        error.print::<IDOProgramError>();
+        return Err(error);
+    }
 
     Ok(())
 }
\ No newline at end of file
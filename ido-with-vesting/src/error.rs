@@ -1,9 +1,16 @@
 use std::error::Error;
-use solana_program::program_error::ProgramError;
+use num_traits::FromPrimitive;
+use num_derive::FromPrimitive;
+#[allow(deprecated)]
+use solana_program::{
+    msg,
+    decode_error::DecodeError,
+    program_error::{ProgramError, PrintProgramError}
+};
 
 
 #[repr(u32)]
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromPrimitive)]
 pub enum IDOProgramError {
     MaxUnlocksOverflow,
     UnlocksMustNotEqualZero,
@@ -14,7 +21,40 @@ pub enum IDOProgramError {
     VestingPeriodMustBeGreaterThanCliff,
     AlreadyClaimed,
     VestingPeriodEnded,
-    ClaimBeforeBuy
+    ClaimBeforeBuy,
+    BuyNotAllowedInAuctionMode,
+    TickSizeMustNotEqualZero,
+    BiddingEndMustBeGreaterThanNow,
+    CliffMustNotPrecedeBiddingEnd,
+    BidQuantityMustNotEqualZero,
+    InvalidBidPrice,
+    BidAlreadyPlaced,
+    BiddingIsActive,
+    BiddingEnded,
+    AuctionAlreadySettled,
+    AuctionNotSettled,
+    RecipientAccountFrozen,
+    InvalidTreasuryAccount,
+    InvalidConfigAccount,
+    InvalidVestingAccount,
+    InvalidAuctionAccount,
+    InvalidBidAccount,
+    TreasuryMintMismatch,
+    TreasuryOwnerMismatch,
+    InvalidAccountType,
+    UnsupportedAccountVersion,
+    AccountAlreadyMigrated,
+    InvalidAccountOwner,
+    ConfigMintMismatch,
+    ConfigTreasuryMismatch,
+    UnlockPeriodMustBeGreaterThanZero,
+    LamportsPerTokenMustNotEqualZero,
+    SupplyExceeded,
+    RecipientOwnerMismatch,
+    RecipientMintMismatch,
+    InvalidWithdrawAuthority,
+    NothingToWithdraw,
+    SaleIsActive
 }
 
 impl Error for IDOProgramError {}
@@ -30,7 +70,40 @@ impl std::fmt::Display for IDOProgramError {
             Self::VestingPeriodMustBeGreaterThanCliff => "Vesting Period must be greater than Cliff Period.",
             Self::AlreadyClaimed => "Already claimed! No tokens to claim.",
             Self::VestingPeriodEnded => "Vesting Period has ended!",
-            Self::ClaimBeforeBuy => "You must buy tokens first before invoking claim instruction."
+            Self::ClaimBeforeBuy => "You must buy tokens first before invoking claim instruction.",
+            Self::BuyNotAllowedInAuctionMode => "This IDO is sold via batch auction, please place a bid instead.",
+            Self::TickSizeMustNotEqualZero => "Auction Tick Size must not equal zero.",
+            Self::BiddingEndMustBeGreaterThanNow => "Bidding End must be greater than Current Timestamp.",
+            Self::CliffMustNotPrecedeBiddingEnd => "Cliff Period must not end before the Bidding Window is closed.",
+            Self::BidQuantityMustNotEqualZero => "Bid Quantity must not equal zero.",
+            Self::InvalidBidPrice => "Bid Max Price must be one of the auction's price levels (min_price + N * tick_size).",
+            Self::BidAlreadyPlaced => "Bid has already been placed for this auction.",
+            Self::BiddingIsActive => "Bidding Window is still active.",
+            Self::BiddingEnded => "Bidding Window has ended!",
+            Self::AuctionAlreadySettled => "Auction has already been settled.",
+            Self::AuctionNotSettled => "Auction must be settled first before settling the bids.",
+            Self::RecipientAccountFrozen => "Recipient token account is frozen (e.g. by the mint's DefaultAccountState), ask the mint's freeze authority to thaw it or claim to another recipient.",
+            Self::InvalidTreasuryAccount => "Treasury account must be the PDA derived from [\"ido-treasury-account\", mint].",
+            Self::InvalidConfigAccount => "Config account must be the PDA derived from [\"ido-config-account\", treasury].",
+            Self::InvalidVestingAccount => "Vesting account must be the PDA derived from [\"ido-vesting-account\", owner, mint].",
+            Self::InvalidAuctionAccount => "Auction account must be the PDA derived from [\"ido-auction-account\", treasury].",
+            Self::InvalidBidAccount => "Bid account must be the PDA derived from [\"ido-bid-account\", bidder, mint].",
+            Self::TreasuryMintMismatch => "Treasury token account belongs to another mint.",
+            Self::TreasuryOwnerMismatch => "Treasury token account must be owned by the Treasury PDA itself.",
+            Self::InvalidAccountType => "Account type doesn't match the expected one.",
+            Self::UnsupportedAccountVersion => "Account layout is outdated, please invoke Migrate instruction first.",
+            Self::AccountAlreadyMigrated => "Account already has the current layout.",
+            Self::InvalidAccountOwner => "Account is not owned by the expected program.",
+            Self::ConfigMintMismatch => "Config Account belongs to another Mint.",
+            Self::ConfigTreasuryMismatch => "Config Account belongs to another Treasury Account.",
+            Self::UnlockPeriodMustBeGreaterThanZero => "Unlock Period must be greater than zero.",
+            Self::LamportsPerTokenMustNotEqualZero => "Lamports per Token must not equal zero, zero price is reserved for the batch auction mode.",
+            Self::SupplyExceeded => "Amount exceeds the supply left for sale.",
+            Self::RecipientOwnerMismatch => "Recipient token account must be owned by the recipient.",
+            Self::RecipientMintMismatch => "Recipient token account belongs to another mint.",
+            Self::InvalidWithdrawAuthority => "Only the authority that initialized the IDO can withdraw, migrated v1 configs have no authority.",
+            Self::NothingToWithdraw => "Nothing to withdraw: no raised LAMPORTS in the config & the unsold supply is released only after the vesting end.",
+            Self::SaleIsActive => "v1 Config didn't track the sold supply, so it can be migrated only once the sale is over (after the vesting end)."
         };
 
         f.write_str(msg)
@@ -41,4 +114,27 @@ impl From<IDOProgramError> for ProgramError {
     fn from(value: IDOProgramError) -> Self {
         Self::Custom(value as u32)
     }
+}
+
+/// Allows clients to turn `InstructionError::Custom(code)` back into the readable error.
+impl TryFrom<u32> for IDOProgramError {
+    type Error = ProgramError;
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        Self::from_u32(value).ok_or(ProgramError::InvalidArgument)
+    }
+}
+
+#[allow(deprecated)]
+impl DecodeError<IDOProgramError> for IDOProgramError {
+    fn type_of() -> &'static str {
+        "IDOProgramError"
+    }
+}
+
+#[allow(deprecated)]
+impl PrintProgramError for IDOProgramError {
+    fn print<E>(&self) {
+        msg!("Error: {}", self);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,346 @@
+use solana_program::{
+    pubkey::Pubkey,
+    program_error::ProgramError
+};
+#[cfg(target_os = "solana")]
+use solana_program::log::sol_log_data;
+use base64::{engine::general_purpose::STANDARD as BASE64_STANDARD, Engine};
+use super::{
+    state::AccountType,
+    utils::{Reader, ReadBytes}
+};
+
+
+const PROGRAM_DATA_LOG_PREFIX: &str = "Program data: ";
+
+/// Events emitted with `sol_log_data`, so indexers don't have to diff account states.
+///
+/// Every event is logged as a single base64 encoded field: the 1-byte discriminator followed by the
+/// little-endian payload. Discriminators are stable & must never be reused.
+///
+/// Token amounts are expressed in base units of the mint (i.e. with decimals), prices in LAMPORTS per whole token.
+#[derive(Debug, Clone, PartialEq)]
+pub enum IDOEvent {
+    IdoInitialized {
+        mint: Pubkey,
+        authority: Pubkey,
+        /// Base units the treasury actually received, i.e. without the transfer fee.
+        deposited_amount: u64,
+        /// LAMPORTS per whole token, 0 for the auction mode config.
+        lamports_per_token: u32
+    },
+
+    Purchased {
+        mint: Pubkey,
+        buyer: Pubkey,
+        /// Base units added to the buyer's vesting.
+        amount: u64,
+        /// LAMPORTS paid to the treasury.
+        lamports: u64
+    },
+
+    Claimed {
+        mint: Pubkey,
+        buyer: Pubkey,
+        recipient: Pubkey,
+        /// Base units of the claimed portion, with `TransferFeePayer::Recipient` the recipient receives it minus the fee.
+        amount: u64
+    },
+
+    AuctionInitialized {
+        mint: Pubkey,
+        /// Base units for sale, i.e. the whole tokens the treasury can deliver.
+        supply: u64,
+        /// LAMPORTS per whole token at the lowest price level.
+        min_price: u32,
+        /// LAMPORTS per whole token between the price levels.
+        tick_size: u32,
+        bidding_end_ts: i64
+    },
+
+    BidPlaced {
+        mint: Pubkey,
+        bidder: Pubkey,
+        /// Base units the bidder bids for.
+        quantity: u64,
+        /// LAMPORTS per whole token.
+        max_price: u32
+    },
+
+    AuctionSettled {
+        mint: Pubkey,
+        /// LAMPORTS per whole token every winning bid pays.
+        clearing_price: u32,
+        /// Base units shared pro-rata by the bids at the clearing price.
+        clearing_level_fill: u64
+    },
+
+    BidSettled {
+        mint: Pubkey,
+        bidder: Pubkey,
+        /// Base units added to the bidder's vesting.
+        filled_amount: u64,
+        /// LAMPORTS paid to the treasury for the filled amount.
+        filled_cost: u64,
+        /// LAMPORTS returned to the bidder, including the rent of the closed Bid PDA.
+        refund_amount: u64
+    },
+
+    Migrated {
+        account: Pubkey,
+        account_type: AccountType,
+        /// v1 layouts had no header, so they are reported as 1.
+        from_version: u8,
+        to_version: u8
+    },
+
+    Withdrawn {
+        mint: Pubkey,
+        authority: Pubkey,
+        /// LAMPORTS released to the authority.
+        lamports: u64,
+        /// Base units that left the treasury.
+        amount: u64
+    }
+}
+
+impl IDOEvent {
+    pub fn discriminator(&self) -> u8 {
+        match self {
+            Self::IdoInitialized { .. } => 0,
+            Self::Purchased { .. } => 1,
+            Self::Claimed { .. } => 2,
+            Self::AuctionInitialized { .. } => 3,
+            Self::BidPlaced { .. } => 4,
+            Self::AuctionSettled { .. } => 5,
+            Self::BidSettled { .. } => 6,
+            Self::Migrated { .. } => 7,
+            Self::Withdrawn { .. } => 8
+        }
+    }
+
+    pub fn pack(&self) -> Vec<u8> {
+        let mut data: Vec<u8> = Vec::with_capacity(105);
+        data.push(self.discriminator());
+
+        match self {
+            Self::IdoInitialized { mint, authority, deposited_amount, lamports_per_token } => {
+                data.extend_from_slice(mint.as_ref());
+                data.extend_from_slice(authority.as_ref());
+                data.extend_from_slice(&deposited_amount.to_le_bytes());
+                data.extend_from_slice(&lamports_per_token.to_le_bytes());
+            },
+            Self::Purchased { mint, buyer, amount, lamports } => {
+                data.extend_from_slice(mint.as_ref());
+                data.extend_from_slice(buyer.as_ref());
+                data.extend_from_slice(&amount.to_le_bytes());
+                data.extend_from_slice(&lamports.to_le_bytes());
+            },
+            Self::Claimed { mint, buyer, recipient, amount } => {
+                data.extend_from_slice(mint.as_ref());
+                data.extend_from_slice(buyer.as_ref());
+                data.extend_from_slice(recipient.as_ref());
+                data.extend_from_slice(&amount.to_le_bytes());
+            },
+            Self::AuctionInitialized { mint, supply, min_price, tick_size, bidding_end_ts } => {
+                data.extend_from_slice(mint.as_ref());
+                data.extend_from_slice(&supply.to_le_bytes());
+                data.extend_from_slice(&min_price.to_le_bytes());
+                data.extend_from_slice(&tick_size.to_le_bytes());
+                data.extend_from_slice(&bidding_end_ts.to_le_bytes());
+            },
+            Self::BidPlaced { mint, bidder, quantity, max_price } => {
+                data.extend_from_slice(mint.as_ref());
+                data.extend_from_slice(bidder.as_ref());
+                data.extend_from_slice(&quantity.to_le_bytes());
+                data.extend_from_slice(&max_price.to_le_bytes());
+            },
+            Self::AuctionSettled { mint, clearing_price, clearing_level_fill } => {
+                data.extend_from_slice(mint.as_ref());
+                data.extend_from_slice(&clearing_price.to_le_bytes());
+                data.extend_from_slice(&clearing_level_fill.to_le_bytes());
+            },
+            Self::BidSettled { mint, bidder, filled_amount, filled_cost, refund_amount } => {
+                data.extend_from_slice(mint.as_ref());
+                data.extend_from_slice(bidder.as_ref());
+                data.extend_from_slice(&filled_amount.to_le_bytes());
+                data.extend_from_slice(&filled_cost.to_le_bytes());
+                data.extend_from_slice(&refund_amount.to_le_bytes());
+            },
+            Self::Migrated { account, account_type, from_version, to_version } => {
+                data.extend_from_slice(account.as_ref());
+                data.extend_from_slice(&[*account_type as u8, *from_version, *to_version]);
+            },
+            Self::Withdrawn { mint, authority, lamports, amount } => {
+                data.extend_from_slice(mint.as_ref());
+                data.extend_from_slice(authority.as_ref());
+                data.extend_from_slice(&lamports.to_le_bytes());
+                data.extend_from_slice(&amount.to_le_bytes());
+            }
+        }
+
+        data
+    }
+
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        let (event_discriminator, data) = data
+            .split_first()
+            .ok_or(ProgramError::InvalidAccountData)?;
+
+        let reader: Reader = data.into();
+
+        Ok(match event_discriminator {
+            0 => {
+                Self::check_expected_payload_len(data.len(), 76)?;
+
+                Self::IdoInitialized {
+                    mint: reader.read_pubkey(0)?,
+                    authority: reader.read_pubkey(32)?,
+                    deposited_amount: reader.read_u64(64)?,
+                    lamports_per_token: reader.read_u32(72)?
+                }
+            },
+            1 => {
+                Self::check_expected_payload_len(data.len(), 80)?;
+
+                Self::Purchased {
+                    mint: reader.read_pubkey(0)?,
+                    buyer: reader.read_pubkey(32)?,
+                    amount: reader.read_u64(64)?,
+                    lamports: reader.read_u64(72)?
+                }
+            },
+            2 => {
+                Self::check_expected_payload_len(data.len(), 104)?;
+
+                Self::Claimed {
+                    mint: reader.read_pubkey(0)?,
+                    buyer: reader.read_pubkey(32)?,
+                    recipient: reader.read_pubkey(64)?,
+                    amount: reader.read_u64(96)?
+                }
+            },
+            3 => {
+                Self::check_expected_payload_len(data.len(), 56)?;
+
+                Self::AuctionInitialized {
+                    mint: reader.read_pubkey(0)?,
+                    supply: reader.read_u64(32)?,
+                    min_price: reader.read_u32(40)?,
+                    tick_size: reader.read_u32(44)?,
+                    bidding_end_ts: reader.read_i64(48)?
+                }
+            },
+            4 => {
+                Self::check_expected_payload_len(data.len(), 76)?;
+
+                Self::BidPlaced {
+                    mint: reader.read_pubkey(0)?,
+                    bidder: reader.read_pubkey(32)?,
+                    quantity: reader.read_u64(64)?,
+                    max_price: reader.read_u32(72)?
+                }
+            },
+            5 => {
+                Self::check_expected_payload_len(data.len(), 44)?;
+
+                Self::AuctionSettled {
+                    mint: reader.read_pubkey(0)?,
+                    clearing_price: reader.read_u32(32)?,
+                    clearing_level_fill: reader.read_u64(36)?
+                }
+            },
+            6 => {
+                Self::check_expected_payload_len(data.len(), 88)?;
+
+                Self::BidSettled {
+                    mint: reader.read_pubkey(0)?,
+                    bidder: reader.read_pubkey(32)?,
+                    filled_amount: reader.read_u64(64)?,
+                    filled_cost: reader.read_u64(72)?,
+                    refund_amount: reader.read_u64(80)?
+                }
+            },
+            7 => {
+                Self::check_expected_payload_len(data.len(), 35)?;
+
+                Self::Migrated {
+                    account: reader.read_pubkey(0)?,
+                    account_type: AccountType::try_from(reader.read_u8(32)?).map_err(|_| ProgramError::InvalidAccountData)?,
+                    from_version: reader.read_u8(33)?,
+                    to_version: reader.read_u8(34)?
+                }
+            },
+            8 => {
+                Self::check_expected_payload_len(data.len(), 80)?;
+
+                Self::Withdrawn {
+                    mint: reader.read_pubkey(0)?,
+                    authority: reader.read_pubkey(32)?,
+                    lamports: reader.read_u64(64)?,
+                    amount: reader.read_u64(72)?
+                }
+            },
+            _ => return Err(ProgramError::InvalidAccountData)
+        })
+    }
+
+    pub fn emit(&self) {
+        #[cfg(target_os = "solana")]
+        sol_log_data(&[&self.pack()]);
+
+        // natively the `sol_log_data` stub prints to stdout, while `sol_log` goes through the installed stubs
+        // (`solana-program-test` records it), so the event ends up in the transaction logs as `Program log: Program data: ...`
+        #[cfg(not(target_os = "solana"))]
+        solana_program::program_stubs::sol_log(&format!("{}{}", PROGRAM_DATA_LOG_PREFIX, BASE64_STANDARD.encode(self.pack())));
+    }
+
+    /// Parses events emitted by this program out of the transaction log messages.
+    ///
+    /// The invocation stack is tracked, so `Program data:` lines logged by other programs
+    /// (including the ones this program invokes) are skipped.
+    pub fn parse_logs<S: AsRef<str>>(logs: &[S]) -> Vec<Self> {
+        let program_id: String = crate::ID.to_string();
+        let mut invocation_stack: Vec<&str> = Vec::new();
+        let mut events: Vec<Self> = Vec::new();
+
+        for log in logs {
+            let log: &str = log.as_ref();
+
+            if let Some(encoded_data) = log.strip_prefix(PROGRAM_DATA_LOG_PREFIX) {
+                if invocation_stack.last() != Some(&program_id.as_str()) {
+                    continue;
+                }
+
+                let maybe_event = BASE64_STANDARD
+                    .decode(encoded_data)
+                    .ok()
+                    .and_then(|data| Self::unpack(&data).ok());
+
+                if let Some(event) = maybe_event {
+                    events.push(event);
+                }
+            } else if let Some(rest) = log.strip_prefix("Program ") {
+                let mut words = rest.split_whitespace();
+
+                match (words.next(), words.next()) {
+                    (Some(invoked_program_id), Some("invoke")) => invocation_stack.push(invoked_program_id),
+                    (Some(_), Some("success")) | (Some(_), Some("failed:")) => {
+                        invocation_stack.pop();
+                    },
+                    _ => {}
+                }
+            }
+        }
+
+        events
+    }
+
+    fn check_expected_payload_len(actual_len: usize, expected_len: usize) -> Result<(), ProgramError> {
+        if actual_len != expected_len {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(())
+    }
+}
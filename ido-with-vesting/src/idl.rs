@@ -0,0 +1,295 @@
+//! Machine-readable description of the program: instructions, their accounts, account layouts & error codes.
+//!
+//! The IDL is generated from the code (instruction builders, layouts & `IDOProgramError`), the checked-in copy
+//! lives in `idl/ido_with_vesting.json` & is regenerated with `UPDATE_IDL=1 cargo test --test program test_idl`.
+use std::mem::{offset_of, size_of};
+use serde_json::{json, Value};
+use solana_program::{
+    pubkey::Pubkey,
+    instruction::Instruction,
+    program_pack::Pack
+};
+use crate::{
+    ID as IDO_PROGRAM_ID,
+    constants::AUCTION_PRICE_LEVELS,
+    error::IDOProgramError,
+    instruction,
+    vesting::LinearVestingStrategy,
+    state::{
+        AccountType,
+        TransferFeePayer,
+        IDOConfigAccount,
+        IDOVestingAccount,
+        IDOAuctionAccount,
+        IDOBidAccount
+    }
+};
+
+
+/// Placeholder addresses passed to the builders, so the accounts with a fixed address (e.g. System Program) can be told apart.
+struct Placeholders([Pubkey; 10]);
+
+impl Placeholders {
+    fn new() -> Self {
+        Self(std::array::from_fn(|index| Pubkey::new_from_array([index as u8 + 1; 32])))
+    }
+
+    fn contains(&self, pubkey: &Pubkey) -> bool {
+        self.0.contains(pubkey)
+    }
+}
+
+struct InstructionIdl {
+    /// `(name, type)` in the order they are packed after the discriminator.
+    args: &'static [(&'static str, &'static str)],
+    /// Names of the accounts in the order the builder passes them.
+    accounts: &'static [&'static str],
+    /// Built by the corresponding builder, so the account flags & the data length are taken from the code.
+    ix: Instruction
+}
+
+pub fn generate() -> Value {
+    json!({
+        "name": "ido_with_vesting",
+        "version": env!("CARGO_PKG_VERSION"),
+        "address": IDO_PROGRAM_ID.to_string(),
+        "instructions": instructions(),
+        "accounts": accounts(),
+        "types": types(),
+        "errors": errors()
+    })
+}
+
+/// Pretty-printed IDL, exactly as it's checked in.
+pub fn generate_json() -> String {
+    let mut idl: String = serde_json::to_string_pretty(&generate()).unwrap_or_default();
+    idl.push('\n');
+    idl
+}
+
+fn instructions() -> Vec<Value> {
+    let [payer, ata, treasury, config, mint, vesting, auction, bid, recipient, token_program] = Placeholders::new().0;
+    let vesting_strategy: LinearVestingStrategy = LinearVestingStrategy { cliff_end_ts: 0, vesting_end_ts: 0, unlock_period: 0 };
+
+    let instructions: [(&str, InstructionIdl); 9] = [
+        ("InitializeWithVesting", InstructionIdl {
+            args: &[("amount", "u64"), ("lamports_per_token", "u32"), ("vesting_strategy", "LinearVestingStrategy"), ("transfer_fee_payer", "TransferFeePayer")],
+            accounts: &["signer", "signer_ata", "treasury", "config", "mint", "token_program", "system_program"],
+            ix: instruction::create_initialize_with_vesting(0, 0, &vesting_strategy, TransferFeePayer::Recipient, &payer, &ata, &treasury, &config, &mint, &token_program)
+        }),
+        ("BuyWithVesting", InstructionIdl {
+            args: &[("amount", "u64")],
+            accounts: &["signer", "vesting", "treasury", "config", "mint", "system_program"],
+            ix: instruction::create_buy_with_vesting(0, &payer, &vesting, &treasury, &config, &mint)
+        }),
+        ("Claim", InstructionIdl {
+            args: &[],
+            accounts: &["signer", "recipient", "recipient_ata", "vesting", "treasury", "config", "mint", "associated_token_program", "token_program", "system_program", "memo_program"],
+            ix: instruction::create_claim(&payer, &recipient, &ata, &vesting, &treasury, &config, &mint, &token_program)
+        }),
+        ("InitializeAuction", InstructionIdl {
+            args: &[("amount", "u64"), ("min_price", "u32"), ("tick_size", "u32"), ("bidding_end_ts", "i64"), ("vesting_strategy", "LinearVestingStrategy"), ("transfer_fee_payer", "TransferFeePayer")],
+            accounts: &["auction", "signer", "signer_ata", "treasury", "config", "mint", "token_program", "system_program"],
+            ix: instruction::create_initialize_auction(0, 0, 0, 0, &vesting_strategy, TransferFeePayer::Recipient, &payer, &ata, &auction, &treasury, &config, &mint, &token_program)
+        }),
+        ("PlaceBid", InstructionIdl {
+            args: &[("quantity", "u64"), ("max_price", "u32"), ("bump", "u8")],
+            accounts: &["signer", "bid", "auction", "treasury", "config", "mint", "system_program"],
+            ix: instruction::create_place_bid(0, 0, &payer, &bid, 0, &auction, &treasury, &config, &mint)
+        }),
+        ("SettleAuction", InstructionIdl {
+            args: &[],
+            accounts: &["auction", "treasury", "config", "mint"],
+            ix: instruction::create_settle_auction(&auction, &treasury, &config, &mint)
+        }),
+        ("SettleBid", InstructionIdl {
+            args: &[],
+            accounts: &["signer", "bidder", "bid", "vesting", "auction", "treasury", "config", "mint", "system_program"],
+            ix: instruction::create_settle_bid(&payer, &recipient, &bid, &vesting, &auction, &treasury, &config, &mint)
+        }),
+        ("Migrate", InstructionIdl {
+            args: &[],
+            accounts: &["signer", "account", "mint", "system_program", "owner"],
+            ix: instruction::create_migrate_vesting(&payer, &vesting, &recipient, &mint)
+        }),
+        ("Withdraw", InstructionIdl {
+            args: &[],
+            accounts: &["authority", "authority_ata", "treasury", "config", "mint", "token_program"],
+            ix: instruction::create_withdraw(&payer, &ata, &treasury, &config, &mint, &token_program)
+        })
+    ];
+
+    let placeholders: Placeholders = Placeholders::new();
+
+    instructions
+        .into_iter()
+        .map(|(name, InstructionIdl { args, accounts, ix })| {
+            assert_eq!(accounts.len(), ix.accounts.len(), "{name}: account names don't match the accounts passed by the builder");
+
+            let accounts: Vec<Value> = ix.accounts
+                .iter()
+                .zip(accounts)
+                .map(|(meta, name)| {
+                    let mut account: Value = json!({
+                        "name": name,
+                        "is_signer": meta.is_signer,
+                        "is_writable": meta.is_writable
+                    });
+
+                    if !placeholders.contains(&meta.pubkey) {
+                        account["address"] = json!(meta.pubkey.to_string());
+                    }
+
+                    account
+                })
+                .collect();
+
+            json!({
+                "name": name,
+                "discriminator": ix.data[0],
+                "data_len": ix.data.len(),
+                "args": args.iter().map(|(name, ty)| json!({ "name": name, "type": ty })).collect::<Vec<Value>>(),
+                "accounts": accounts
+            })
+        })
+        .collect()
+}
+
+/// Every account is prefixed with `[account_type, version]`, Config & Vesting declare the header as their first fields.
+fn accounts() -> Vec<Value> {
+    let header_field = |name: &str, offset: usize| json!({ "name": name, "type": "u8", "offset": offset });
+    let field = |name: &str, ty: Value, offset: usize| json!({ "name": name, "type": ty, "offset": offset });
+    let auction: IDOAuctionAccount = IDOAuctionAccount::new(0, 0, 0, 0, 0);
+    let bid: IDOBidAccount = IDOBidAccount { quantity: 0, max_price: 0, bump: 0, is_initialized: true };
+
+    vec![
+        json!({
+            "name": "IDOConfigAccount",
+            "account_type": AccountType::Config as u8,
+            "size": IDOConfigAccount::LEN,
+            "fields": [
+                header_field("account_type", offset_of!(IDOConfigAccount, account_type)),
+                header_field("version", offset_of!(IDOConfigAccount, version)),
+                field("bump", json!("u8"), offset_of!(IDOConfigAccount, bump)),
+                field("unlocks", json!("u8"), offset_of!(IDOConfigAccount, unlocks)),
+                field("is_initialized", json!("u8"), offset_of!(IDOConfigAccount, is_initialized)),
+                field("transfer_fee_payer", json!("TransferFeePayer"), offset_of!(IDOConfigAccount, transfer_fee_payer)),
+                field("treasury_bump", json!("u8"), offset_of!(IDOConfigAccount, treasury_bump)),
+                field("lamports_per_token", json!("u32"), offset_of!(IDOConfigAccount, lamports_per_token)),
+                field("vesting_strategy", json!("LinearVestingStrategy"), offset_of!(IDOConfigAccount, vesting_strategy)),
+                field("deposited_amount", json!("u64"), offset_of!(IDOConfigAccount, deposited_amount)),
+                field("sold_amount", json!("u64"), offset_of!(IDOConfigAccount, sold_amount)),
+                field("reserved_amount", json!("u64"), offset_of!(IDOConfigAccount, reserved_amount)),
+                field("mint", json!("pubkey"), offset_of!(IDOConfigAccount, mint)),
+                field("treasury", json!("pubkey"), offset_of!(IDOConfigAccount, treasury)),
+                field("authority", json!("pubkey"), offset_of!(IDOConfigAccount, authority))
+            ]
+        }),
+        json!({
+            "name": "IDOVestingAccount",
+            "account_type": AccountType::Vesting as u8,
+            "size": IDOVestingAccount::LEN,
+            "fields": [
+                header_field("account_type", offset_of!(IDOVestingAccount, account_type)),
+                header_field("version", offset_of!(IDOVestingAccount, version)),
+                field("bump", json!("u8"), offset_of!(IDOVestingAccount, bump)),
+                field("is_initialized", json!("u8"), offset_of!(IDOVestingAccount, is_initialized)),
+                field("last_claim_ts", json!("i64"), offset_of!(IDOVestingAccount, last_claim_ts)),
+                field("claimed_amount", json!("u64"), offset_of!(IDOVestingAccount, claimed_amount)),
+                field("bought_amount", json!("u64"), offset_of!(IDOVestingAccount, bought_amount)),
+                field("amount_per_unlock", json!("u64"), offset_of!(IDOVestingAccount, amount_per_unlock)),
+                field("owner", json!("pubkey"), offset_of!(IDOVestingAccount, owner)),
+                field("mint", json!("pubkey"), offset_of!(IDOVestingAccount, mint))
+            ]
+        }),
+        // Auction & Bid are packed without alignment, so their offsets are taken from `Pack`
+        json!({
+            "name": "IDOAuctionAccount",
+            "account_type": AccountType::Auction as u8,
+            "size": IDOAuctionAccount::LEN,
+            "fields": [
+                header_field("account_type", 0),
+                header_field("version", 1),
+                field("bidding_end_ts", json!("i64"), packed_offset(&auction, |auction| auction.bidding_end_ts = !auction.bidding_end_ts)),
+                field("supply", json!("u64"), packed_offset(&auction, |auction| auction.supply = !auction.supply)),
+                field("min_price", json!("u32"), packed_offset(&auction, |auction| auction.min_price = !auction.min_price)),
+                field("tick_size", json!("u32"), packed_offset(&auction, |auction| auction.tick_size = !auction.tick_size)),
+                field("clearing_price", json!("u32"), packed_offset(&auction, |auction| auction.clearing_price = !auction.clearing_price)),
+                field("clearing_level", json!("u8"), packed_offset(&auction, |auction| auction.clearing_level = !auction.clearing_level)),
+                field("bump", json!("u8"), packed_offset(&auction, |auction| auction.bump = !auction.bump)),
+                field("is_settled", json!("bool"), packed_offset(&auction, |auction| auction.is_settled = !auction.is_settled)),
+                field("is_initialized", json!("bool"), packed_offset(&auction, |auction| auction.is_initialized = !auction.is_initialized)),
+                field("clearing_level_fill", json!("u64"), packed_offset(&auction, |auction| auction.clearing_level_fill = !auction.clearing_level_fill)),
+                field("demand", json!({ "array": ["u64", AUCTION_PRICE_LEVELS] }), packed_offset(&auction, |auction| auction.demand[0] = !auction.demand[0]))
+            ]
+        }),
+        json!({
+            "name": "IDOBidAccount",
+            "account_type": AccountType::Bid as u8,
+            "size": IDOBidAccount::LEN,
+            "fields": [
+                header_field("account_type", 0),
+                header_field("version", 1),
+                field("quantity", json!("u64"), packed_offset(&bid, |bid| bid.quantity = !bid.quantity)),
+                field("max_price", json!("u32"), packed_offset(&bid, |bid| bid.max_price = !bid.max_price)),
+                field("bump", json!("u8"), packed_offset(&bid, |bid| bid.bump = !bid.bump)),
+                field("is_initialized", json!("bool"), packed_offset(&bid, |bid| bid.is_initialized = !bid.is_initialized))
+            ]
+        })
+    ]
+}
+
+/// Offset of a field in the packed account, i.e. the first byte that changes once the field (and only it) is changed.
+fn packed_offset<T: Pack + Clone>(account: &T, change_field: impl FnOnce(&mut T)) -> usize {
+    let mut changed_account: T = account.clone();
+    change_field(&mut changed_account);
+
+    let mut data: Vec<u8> = vec![0; T::LEN];
+    let mut changed_data: Vec<u8> = vec![0; T::LEN];
+    account.pack_into_slice(&mut data);
+    changed_account.pack_into_slice(&mut changed_data);
+
+    data
+        .iter()
+        .zip(&changed_data)
+        .position(|(byte, changed_byte)| byte != changed_byte)
+        .expect("changed field isn't packed")
+}
+
+fn types() -> Vec<Value> {
+    let transfer_fee_payer_variants: Vec<Value> = [TransferFeePayer::Recipient, TransferFeePayer::Treasury]
+        .iter()
+        .map(|variant| json!({ "name": format!("{variant:?}"), "value": *variant as u8 }))
+        .collect();
+
+    vec![
+        json!({
+            "name": "LinearVestingStrategy",
+            "kind": "struct",
+            "size": size_of::<LinearVestingStrategy>(),
+            "fields": [
+                { "name": "cliff_end_ts", "type": "i64", "offset": offset_of!(LinearVestingStrategy, cliff_end_ts) },
+                { "name": "vesting_end_ts", "type": "i64", "offset": offset_of!(LinearVestingStrategy, vesting_end_ts) },
+                { "name": "unlock_period", "type": "i64", "offset": offset_of!(LinearVestingStrategy, unlock_period) }
+            ]
+        }),
+        json!({
+            "name": "TransferFeePayer",
+            "kind": "enum",
+            "size": size_of::<TransferFeePayer>(),
+            "variants": transfer_fee_payer_variants
+        })
+    ]
+}
+
+/// Error codes are sequential, so every code is decoded until the first unknown one.
+fn errors() -> Vec<Value> {
+    (0..)
+        .map_while(|code: u32| IDOProgramError::try_from(code).ok())
+        .map(|error| json!({
+            "code": error as u32,
+            "name": format!("{error:?}"),
+            "msg": error.to_string()
+        }))
+        .collect()
+}
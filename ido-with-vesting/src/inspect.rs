@@ -0,0 +1,237 @@
+//! Decodes any account owned by the program & derives the values support needs to reason about it.
+//!
+//! `AccountInspection` is rendered either as a human-readable table (`Display`) or as JSON (`to_json`).
+use serde_json::{json, Value};
+use solana_program::{
+    pubkey::Pubkey,
+    program_error::ProgramError,
+    program_pack::Pack
+};
+use crate::{
+    error::IDOProgramError,
+    vesting::{LinearVestingStrategy, allow_claim_and_define_portion_at},
+    state::{
+        AccountType,
+        IDOConfigAccount,
+        IDOVestingAccount,
+        IDOAuctionAccount,
+        IDOBidAccount
+    }
+};
+
+
+pub enum ProgramAccount {
+    Config(IDOConfigAccount),
+    Vesting(IDOVestingAccount),
+    Auction(Box<IDOAuctionAccount>),
+    Bid(IDOBidAccount)
+}
+
+impl ProgramAccount {
+    /// The type is taken from the header, so v1 accounts are rejected with `UnsupportedAccountVersion` or `InvalidAccountType`.
+    pub fn decode(data: &[u8]) -> Result<Self, ProgramError> {
+        let account_type: AccountType = data
+            .first()
+            .and_then(|account_type| AccountType::try_from(*account_type).ok())
+            .ok_or(IDOProgramError::InvalidAccountType)?;
+
+        Ok(match account_type {
+            AccountType::Config => Self::Config(IDOConfigAccount::unpack(data)?),
+            AccountType::Vesting => Self::Vesting(IDOVestingAccount::unpack(data)?),
+            AccountType::Auction => Self::Auction(Box::new(IDOAuctionAccount::unpack(data)?)),
+            AccountType::Bid => Self::Bid(IDOBidAccount::unpack(data)?)
+        })
+    }
+
+    pub fn account_type(&self) -> AccountType {
+        match self {
+            Self::Config(_) => AccountType::Config,
+            Self::Vesting(_) => AccountType::Vesting,
+            Self::Auction(_) => AccountType::Auction,
+            Self::Bid(_) => AccountType::Bid
+        }
+    }
+}
+
+/// Values derived from the vesting & the strategy of its IDO at `now_ts`, amounts are represented without decimals.
+pub struct VestingProgress {
+    /// Amount the `Claim` instruction would transfer right now.
+    pub claimable_now: u64,
+    /// The first timestamp, when more tokens become claimable (assuming the claimable amount is claimed now),
+    /// `None` once everything is claimable.
+    pub next_unlock_ts: Option<i64>,
+    /// Portions that are still to be released after `claimable_now` is claimed.
+    pub remaining_unlocks: u8
+}
+
+impl VestingProgress {
+    /// Replays `Claim` with the same business logic the program uses at every upcoming unlock,
+    /// so the quirks of the schedule (e.g. the 1st claim releases the 1st portion right after the cliff) are respected.
+    pub fn new(vesting_account: &IDOVestingAccount, config_account: &IDOConfigAccount, now_ts: i64) -> Self {
+        let vesting_strategy: LinearVestingStrategy = config_account.vesting_strategy;
+        let LinearVestingStrategy { cliff_end_ts, vesting_end_ts, unlock_period } = vesting_strategy;
+
+        let mut vesting_account: IDOVestingAccount = *vesting_account;
+        let claimable_now: u64 = claim_at(&vesting_strategy, &mut vesting_account, now_ts);
+
+        // portions are released at the cliff end, every unlock period after it & the remainder at the vesting end
+        let unlock_timestamps = std::iter::once(cliff_end_ts)
+            .chain((1..=config_account.unlocks as i64).map(|unlock| cliff_end_ts.saturating_add(unlock.saturating_mul(unlock_period))))
+            .chain(std::iter::once(vesting_end_ts))
+            .filter(|ts| *ts > now_ts);
+
+        let mut next_unlock_ts: Option<i64> = None;
+        let mut remaining_unlocks: u8 = 0;
+        for ts in unlock_timestamps {
+            if claim_at(&vesting_strategy, &mut vesting_account, ts) > 0 {
+                next_unlock_ts.get_or_insert(ts);
+                remaining_unlocks = remaining_unlocks.saturating_add(1);
+            }
+        }
+
+        Self {
+            claimable_now,
+            next_unlock_ts,
+            remaining_unlocks
+        }
+    }
+}
+
+/// Mutates `vesting_account` the same way the successful claim does, returns 0 if nothing is claimable.
+fn claim_at(vesting_strategy: &LinearVestingStrategy, vesting_account: &mut IDOVestingAccount, ts: i64) -> u64 {
+    allow_claim_and_define_portion_at(ts, vesting_strategy, vesting_account).unwrap_or(0)
+}
+
+pub struct AccountInspection {
+    pub address: Pubkey,
+    pub account: ProgramAccount,
+    /// Defined only for Vesting, since the strategy is stored in the Config.
+    pub progress: Option<VestingProgress>,
+    /// Timestamp the progress is derived at.
+    pub now_ts: i64
+}
+
+impl AccountInspection {
+    /// `config_account` must be the config of the Vesting's mint, it's required only for the Vesting progress.
+    pub fn new(
+        address: Pubkey,
+        data: &[u8],
+        config_account: Option<&IDOConfigAccount>,
+        now_ts: i64
+    ) -> Result<Self, ProgramError> {
+        let account: ProgramAccount = ProgramAccount::decode(data)?;
+        let progress: Option<VestingProgress> = match (&account, config_account) {
+            (ProgramAccount::Vesting(vesting_account), Some(config_account)) => Some(VestingProgress::new(vesting_account, config_account, now_ts)),
+            _ => None
+        };
+
+        Ok(Self { address, account, progress, now_ts })
+    }
+
+    pub fn to_json(&self) -> Value {
+        let mut fields: Value = match &self.account {
+            ProgramAccount::Config(config_account) => json!({
+                "bump": config_account.bump,
+                "treasury_bump": config_account.treasury_bump,
+                "mint": config_account.mint.to_string(),
+                "treasury": config_account.treasury.to_string(),
+                "authority": config_account.authority.to_string(),
+                "lamports_per_token": config_account.lamports_per_token,
+                "deposited_amount": config_account.deposited_amount,
+                "sold_amount": config_account.sold_amount,
+                "reserved_amount": config_account.reserved_amount,
+                "transfer_fee_payer": config_account.transfer_fee_payer().map(|payer| format!("{:?}", payer)).ok(),
+                "unlocks": config_account.unlocks,
+                "cliff_end_ts": config_account.vesting_strategy.cliff_end_ts,
+                "vesting_end_ts": config_account.vesting_strategy.vesting_end_ts,
+                "unlock_period": config_account.vesting_strategy.unlock_period
+            }),
+            ProgramAccount::Vesting(vesting_account) => json!({
+                "bump": vesting_account.bump,
+                "bought_amount": vesting_account.bought_amount,
+                "claimed_amount": vesting_account.claimed_amount,
+                "amount_per_unlock": vesting_account.amount_per_unlock,
+                "last_claim_ts": vesting_account.last_claim_ts,
+                "owner": vesting_account.owner.to_string(),
+                "mint": vesting_account.mint.to_string()
+            }),
+            ProgramAccount::Auction(auction_account) => json!({
+                "bump": auction_account.bump,
+                "bidding_end_ts": auction_account.bidding_end_ts,
+                "supply": auction_account.supply,
+                "min_price": auction_account.min_price,
+                "tick_size": auction_account.tick_size,
+                "is_settled": auction_account.is_settled,
+                "clearing_price": auction_account.clearing_price,
+                "clearing_level": auction_account.clearing_level,
+                "clearing_level_fill": auction_account.clearing_level_fill,
+                "demand": auction_account.demand.to_vec()
+            }),
+            ProgramAccount::Bid(bid_account) => json!({
+                "bump": bid_account.bump,
+                "quantity": bid_account.quantity,
+                "max_price": bid_account.max_price
+            })
+        };
+
+        if let Some(progress) = &self.progress {
+            fields["claimable_now"] = json!(progress.claimable_now);
+            fields["next_unlock_ts"] = json!(progress.next_unlock_ts);
+            fields["remaining_unlocks"] = json!(progress.remaining_unlocks);
+        }
+
+        json!({
+            "address": self.address.to_string(),
+            "type": format!("{:?}", self.account.account_type()),
+            "now_ts": self.now_ts,
+            "fields": fields
+        })
+    }
+}
+
+/// Table of `field | value` rows, timestamps are followed by their UTC date.
+impl std::fmt::Display for AccountInspection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let json: Value = self.to_json();
+        let Some(fields) = json["fields"].as_object() else {
+            return Ok(());
+        };
+
+        let width: usize = fields.keys().map(String::len).max().unwrap_or(0).max("address".len());
+        writeln!(f, "{:<width$} | {}", "address", self.address)?;
+        writeln!(f, "{:<width$} | {:?}", "type", self.account.account_type())?;
+        writeln!(f, "{:-<width$}-+-{:-<44}", "", "")?;
+
+        for (field, value) in fields {
+            let value: String = match value {
+                Value::Null => "-".to_string(),
+                Value::String(value) => value.clone(),
+                Value::Number(ts) if field.ends_with("_ts") && ts.as_i64().is_some_and(|ts| ts > 0) => {
+                    format!("{} ({})", ts, format_utc(ts.as_i64().unwrap_or_default()))
+                },
+                value => value.to_string()
+            };
+            writeln!(f, "{:<width$} | {}", field, value)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// `YYYY-MM-DD hh:mm:ss UTC` without pulling the date crates into the program.
+fn format_utc(ts: i64) -> String {
+    let (days, secs) = (ts.div_euclid(86_400), ts.rem_euclid(86_400));
+
+    // civil from days, see http://howardhinnant.github.io/date_algorithms.html
+    let z: i64 = days + 719_468;
+    let era: i64 = z.div_euclid(146_097);
+    let doe: i64 = z - era * 146_097;
+    let yoe: i64 = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy: i64 = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp: i64 = (5 * doy + 2) / 153;
+    let day: i64 = doy - (153 * mp + 2) / 5 + 1;
+    let month: i64 = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year: i64 = yoe + era * 400 + (month <= 2) as i64;
+
+    format!("{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC", year, month, day, secs / 3_600, secs % 3_600 / 60, secs % 60)
+}
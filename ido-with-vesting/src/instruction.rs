@@ -1,51 +1,147 @@
 use solana_program::{
     program_error::ProgramError,
 };
+#[cfg(feature = "borsh")]
+use borsh::{BorshSerialize, BorshDeserialize};
 use super::{
     utils::{
         Reader,
         ReadBytes,
         read_u64_slice
     },
-    vesting::LinearVestingStrategy
+    vesting::LinearVestingStrategy,
+    state::TransferFeePayer
 };
 
 
+/// Instruction data is the 1-byte discriminator followed by the little-endian payload, which is exactly
+/// the Borsh encoding of this enum, so with `borsh` feature it can be decoded by standard tooling as well.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "borsh", derive(BorshSerialize, BorshDeserialize))]
 pub enum IDOInstruction {
     InitializeWithVesting { 
         amount: u64, 
         lamports_per_token: u32,
-        vesting_strategy: LinearVestingStrategy
+        vesting_strategy: LinearVestingStrategy,
+        transfer_fee_payer: TransferFeePayer
     },
 
     BuyWithVesting {
         amount: u64
     },
 
-    Claim
+    Claim,
+
+    /// Uniform-price batch auction: the whole deposited supply is sold at a single clearing price.
+    InitializeAuction {
+        amount: u64,
+        min_price: u32,
+        tick_size: u32,
+        bidding_end_ts: i64,
+        vesting_strategy: LinearVestingStrategy,
+        transfer_fee_payer: TransferFeePayer
+    },
+
+    /// `bump` of the Bid PDA, which is created by this instruction, the builders pass the canonical one.
+    PlaceBid {
+        quantity: u64,
+        max_price: u32,
+        bump: u8
+    },
+
+    /// Permissionless crank, which defines the clearing price once the bidding window is closed
+    /// & keeps the reserve of the config only for the filled supply.
+    SettleAuction,
+
+    /// Permissionless crank, which turns the winning part of a bid into the vesting position & refunds the rest.
+    SettleBid,
+
+    /// Permissionless, reallocates v1 (headerless) Config or Vesting into the current layout,
+    /// the Config only once its vesting is over, since v1 didn't track the sold supply.
+    Migrate,
+
+    /// Moves the raised LAMPORTS (held by Config PDA) & (once the vesting is over) the unsold supply to the authority.
+    Withdraw
 }
 
 impl IDOInstruction {
+    pub fn discriminator(&self) -> u8 {
+        match self {
+            Self::InitializeWithVesting { .. } => 0,
+            Self::BuyWithVesting { .. } => 1,
+            Self::Claim => 2,
+            Self::InitializeAuction { .. } => 3,
+            Self::PlaceBid { .. } => 4,
+            Self::SettleAuction => 5,
+            Self::SettleBid => 6,
+            Self::Migrate => 7,
+            Self::Withdraw => 8
+        }
+    }
+
+    /// Inverse of `Self::unpack()`, used by the instruction builders.
+    pub fn pack(&self) -> Vec<u8> {
+        let mut data: Vec<u8> = Vec::with_capacity(50);
+        data.push(self.discriminator());
+
+        match self {
+            Self::InitializeWithVesting { amount, lamports_per_token, vesting_strategy, transfer_fee_payer } => {
+                data.extend_from_slice(&amount.to_le_bytes());
+                data.extend_from_slice(&lamports_per_token.to_le_bytes());
+                data.extend_from_slice(vesting_strategy.as_ref());
+                data.push(*transfer_fee_payer as u8);
+            },
+            Self::BuyWithVesting { amount } => {
+                data.extend_from_slice(&amount.to_le_bytes());
+            },
+            Self::InitializeAuction { amount, min_price, tick_size, bidding_end_ts, vesting_strategy, transfer_fee_payer } => {
+                data.extend_from_slice(&amount.to_le_bytes());
+                data.extend_from_slice(&min_price.to_le_bytes());
+                data.extend_from_slice(&tick_size.to_le_bytes());
+                data.extend_from_slice(&bidding_end_ts.to_le_bytes());
+                data.extend_from_slice(vesting_strategy.as_ref());
+                data.push(*transfer_fee_payer as u8);
+            },
+            Self::PlaceBid { quantity, max_price, bump } => {
+                data.extend_from_slice(&quantity.to_le_bytes());
+                data.extend_from_slice(&max_price.to_le_bytes());
+                data.push(*bump);
+            },
+            Self::Claim | Self::SettleAuction | Self::SettleBid | Self::Migrate | Self::Withdraw => {}
+        }
+
+        data
+    }
+
     pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
-        let (instr_discriminator, data) = data.split_at(1);
+        let (instr_discriminator, data) = data
+            .split_first()
+            .ok_or(ProgramError::InvalidInstructionData)?;
         
-        Ok(match instr_discriminator[0] {
+        Ok(match instr_discriminator {
             0 => Self::unpack_initialize_with_vesting(data)?,
             1 => Self::unpack_buy_with_vesting(data)?,
             2 => Self::unpack_claim(data)?,
+            3 => Self::unpack_initialize_auction(data)?,
+            4 => Self::unpack_place_bid(data)?,
+            5 => Self::unpack_settle_auction(data)?,
+            6 => Self::unpack_settle_bid(data)?,
+            7 => Self::unpack_migrate(data)?,
+            8 => Self::unpack_withdraw(data)?,
             _ => return Err(ProgramError::InvalidInstructionData)
         })
     }
 
     fn unpack_initialize_with_vesting(data: &[u8]) -> Result<Self, ProgramError> {
-        Self::check_expected_payload_len(data.len(), 36)?;
+        Self::check_expected_payload_len(data.len(), 37)?;
         
         let reader: Reader = data.into();
 
         Ok(Self::InitializeWithVesting {
             amount: reader.read_u64(0)?,
             lamports_per_token: reader.read_u32(8)?,
-            vesting_strategy: reader.read_linear_vesting_strategy(12)?
+            vesting_strategy: reader.read_linear_vesting_strategy(12)?,
+            transfer_fee_payer: reader.read_u8(36)?.try_into()?
         })
     }
 
@@ -63,6 +159,57 @@ impl IDOInstruction {
         Ok(Self::Claim)
     }
 
+    fn unpack_initialize_auction(data: &[u8]) -> Result<Self, ProgramError> {
+        Self::check_expected_payload_len(data.len(), 49)?;
+
+        let reader: Reader = data.into();
+
+        Ok(Self::InitializeAuction {
+            amount: reader.read_u64(0)?,
+            min_price: reader.read_u32(8)?,
+            tick_size: reader.read_u32(12)?,
+            bidding_end_ts: reader.read_i64(16)?,
+            vesting_strategy: reader.read_linear_vesting_strategy(24)?,
+            transfer_fee_payer: reader.read_u8(48)?.try_into()?
+        })
+    }
+
+    fn unpack_place_bid(data: &[u8]) -> Result<Self, ProgramError> {
+        Self::check_expected_payload_len(data.len(), 13)?;
+
+        let reader: Reader = data.into();
+
+        Ok(Self::PlaceBid {
+            quantity: reader.read_u64(0)?,
+            max_price: reader.read_u32(8)?,
+            bump: reader.read_u8(12)?
+        })
+    }
+
+    fn unpack_settle_auction(data: &[u8]) -> Result<Self, ProgramError> {
+        Self::check_expected_payload_len(data.len(), 0)?;
+
+        Ok(Self::SettleAuction)
+    }
+
+    fn unpack_settle_bid(data: &[u8]) -> Result<Self, ProgramError> {
+        Self::check_expected_payload_len(data.len(), 0)?;
+
+        Ok(Self::SettleBid)
+    }
+
+    fn unpack_migrate(data: &[u8]) -> Result<Self, ProgramError> {
+        Self::check_expected_payload_len(data.len(), 0)?;
+
+        Ok(Self::Migrate)
+    }
+
+    fn unpack_withdraw(data: &[u8]) -> Result<Self, ProgramError> {
+        Self::check_expected_payload_len(data.len(), 0)?;
+
+        Ok(Self::Withdraw)
+    }
+
     /// `expected_len` - ix's payload length without enum variant's discriminator.
     fn check_expected_payload_len(data_len: usize, expected_len: usize) -> Result<(), ProgramError> {
         if data_len != expected_len {
@@ -74,39 +221,71 @@ impl IDOInstruction {
 }
 
 #[cfg(feature = "instruction")]
-pub use builders::{create_initialize_with_vesting, create_buy_with_vesting, create_claim};
+pub use builders::{
+    create_initialize_with_vesting, 
+    create_buy_with_vesting, 
+    create_claim,
+    create_initialize_auction,
+    create_place_bid,
+    create_settle_auction,
+    create_settle_bid,
+    create_migrate,
+    create_migrate_vesting,
+    create_withdraw,
+    create_initialize_with_vesting_for_mint,
+    create_buy_with_vesting_for_mint,
+    create_claim_for_mint,
+    create_initialize_auction_for_mint,
+    create_place_bid_for_mint,
+    create_settle_auction_for_mint,
+    create_settle_bid_for_mint,
+    create_withdraw_for_mint,
+    add_transfer_hook_accounts
+};
 
+/// Builders that involve token CPIs take `token_program_id`, which must be the program that owns the mint:
+/// either SPL Token or SPL Token 2022.
 #[cfg(feature = "instruction")]
 pub mod builders {
     use solana_program::{
-        rent::Rent,
         pubkey::Pubkey,
-        sysvar::SysvarId,
         system_program::ID as SYSTEM_PROGRAM_ID,
         instruction::{Instruction, AccountMeta},
     };
-    use spl_token_2022::ID as SPL_TOKEN_2022_ID;
     use crate::{
         ID as IDO_PROGRAM_ID,
-        external_ids::ATA_PROGRAM_ID,
+        pda,
+        instruction::IDOInstruction,
+        external_ids::{ATA_PROGRAM_ID, MEMO_PROGRAM_ID},
         vesting::LinearVestingStrategy,
+        state::TransferFeePayer,
+    };
+    use spl_token_2022::offchain::{
+        add_extra_account_metas,
+        AccountDataResult,
+        AccountFetchError
     };
+    use std::future::Future;
 
+    #[allow(clippy::too_many_arguments)]
     pub fn create_initialize_with_vesting(
         transfer_amount: u64,
         lamports_per_token: u32,
         vesting_strategy: &LinearVestingStrategy,
+        transfer_fee_payer: TransferFeePayer,
         payer_pkey: &Pubkey,
         ata_pda: &Pubkey, 
         treasury_pda: &Pubkey, 
         config_pda: &Pubkey,
-        mint_pkey: &Pubkey
+        mint_pkey: &Pubkey,
+        token_program_id: &Pubkey
     ) -> Instruction {
-        let mut init_ix_payload: Vec<u8> = Vec::with_capacity(37);         
-        init_ix_payload.push(0);
-        init_ix_payload.extend_from_slice(&transfer_amount.to_le_bytes());
-        init_ix_payload.extend_from_slice(&lamports_per_token.to_le_bytes());
-        init_ix_payload.extend_from_slice(vesting_strategy.as_ref());
+        let init_ix_payload: Vec<u8> = IDOInstruction::InitializeWithVesting {
+            amount: transfer_amount,
+            lamports_per_token,
+            vesting_strategy: *vesting_strategy,
+            transfer_fee_payer
+        }.pack();
 
         Instruction::new_with_bytes(
             IDO_PROGRAM_ID, 
@@ -117,8 +296,7 @@ pub mod builders {
                 AccountMeta::new(*treasury_pda, false),
                 AccountMeta::new(*config_pda, false),
                 AccountMeta::new_readonly(*mint_pkey, false),
-                AccountMeta::new_readonly(Rent::id(), false),
-                AccountMeta::new_readonly(SPL_TOKEN_2022_ID, false),
+                AccountMeta::new_readonly(*token_program_id, false),
                 AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false)
             ]
         )
@@ -132,9 +310,7 @@ pub mod builders {
         config_pda: &Pubkey,
         mint_pkey: &Pubkey
     ) -> Instruction {
-        let mut buy_ix_payload: Vec<u8> = Vec::with_capacity(9);
-        buy_ix_payload.push(1); 
-        buy_ix_payload.extend_from_slice(&buy_amount.to_le_bytes());
+        let buy_ix_payload: Vec<u8> = IDOInstruction::BuyWithVesting { amount: buy_amount }.pack();
 
         Instruction::new_with_bytes(
             IDO_PROGRAM_ID, 
@@ -143,13 +319,14 @@ pub mod builders {
                 AccountMeta::new(*payer_pkey, true),
                 AccountMeta::new(*vesting_account, false),
                 AccountMeta::new(*treasury_pda, false),
-                AccountMeta::new_readonly(*config_pda, false),
+                AccountMeta::new(*config_pda, false),
                 AccountMeta::new_readonly(*mint_pkey, false),
                 AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false)
             ]
         )
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn create_claim(
         payer_pkey: &Pubkey, 
         recipient: &Pubkey,
@@ -157,11 +334,12 @@ pub mod builders {
         vesting_account: &Pubkey, 
         treasury_pda: &Pubkey,
         config_pda: &Pubkey,
-        mint_pkey: &Pubkey
+        mint_pkey: &Pubkey,
+        token_program_id: &Pubkey
     ) -> Instruction {
         Instruction::new_with_bytes(
             IDO_PROGRAM_ID, 
-            &[2], 
+            &IDOInstruction::Claim.pack(), 
             vec![
                 AccountMeta::new(*payer_pkey, true),
                 AccountMeta::new_readonly(*recipient, false),
@@ -171,9 +349,372 @@ pub mod builders {
                 AccountMeta::new_readonly(*config_pda, false),
                 AccountMeta::new_readonly(*mint_pkey, false),
                 AccountMeta::new_readonly(ATA_PROGRAM_ID, false),
-                AccountMeta::new_readonly(SPL_TOKEN_2022_ID, false),
+                AccountMeta::new_readonly(*token_program_id, false),
+                AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+                AccountMeta::new_readonly(MEMO_PROGRAM_ID, false)
+            ]
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_initialize_auction(
+        transfer_amount: u64,
+        min_price: u32,
+        tick_size: u32,
+        bidding_end_ts: i64,
+        vesting_strategy: &LinearVestingStrategy,
+        transfer_fee_payer: TransferFeePayer,
+        payer_pkey: &Pubkey,
+        ata_pda: &Pubkey, 
+        auction_pda: &Pubkey,
+        treasury_pda: &Pubkey, 
+        config_pda: &Pubkey,
+        mint_pkey: &Pubkey,
+        token_program_id: &Pubkey
+    ) -> Instruction {
+        let init_ix_payload: Vec<u8> = IDOInstruction::InitializeAuction {
+            amount: transfer_amount,
+            min_price,
+            tick_size,
+            bidding_end_ts,
+            vesting_strategy: *vesting_strategy,
+            transfer_fee_payer
+        }.pack();
+
+        Instruction::new_with_bytes(
+            IDO_PROGRAM_ID, 
+            &init_ix_payload, 
+            vec![
+                AccountMeta::new(*auction_pda, false),
+                AccountMeta::new(*payer_pkey, true),
+                AccountMeta::new(*ata_pda, false),
+                AccountMeta::new(*treasury_pda, false),
+                AccountMeta::new(*config_pda, false),
+                AccountMeta::new_readonly(*mint_pkey, false),
+                AccountMeta::new_readonly(*token_program_id, false),
                 AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false)
             ]
         )
     }
+
+    /// `bid_bump` must be the bump `bid_pda` was derived with, e.g. `pda::find_bid(payer_pkey, mint_pkey).1`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_place_bid(
+        quantity: u64,
+        max_price: u32,
+        payer_pkey: &Pubkey,
+        bid_pda: &Pubkey,
+        bid_bump: u8,
+        auction_pda: &Pubkey,
+        treasury_pda: &Pubkey,
+        config_pda: &Pubkey,
+        mint_pkey: &Pubkey
+    ) -> Instruction {
+        let bid_ix_payload: Vec<u8> = IDOInstruction::PlaceBid { quantity, max_price, bump: bid_bump }.pack();
+
+        Instruction::new_with_bytes(
+            IDO_PROGRAM_ID, 
+            &bid_ix_payload, 
+            vec![
+                AccountMeta::new(*payer_pkey, true),
+                AccountMeta::new(*bid_pda, false),
+                AccountMeta::new(*auction_pda, false),
+                AccountMeta::new_readonly(*treasury_pda, false),
+                AccountMeta::new(*config_pda, false),
+                AccountMeta::new_readonly(*mint_pkey, false),
+                AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false)
+            ]
+        )
+    }
+
+    pub fn create_settle_auction(
+        auction_pda: &Pubkey,
+        treasury_pda: &Pubkey,
+        config_pda: &Pubkey,
+        mint_pkey: &Pubkey
+    ) -> Instruction {
+        Instruction::new_with_bytes(
+            IDO_PROGRAM_ID, 
+            &IDOInstruction::SettleAuction.pack(), 
+            vec![
+                AccountMeta::new(*auction_pda, false),
+                AccountMeta::new_readonly(*treasury_pda, false),
+                AccountMeta::new(*config_pda, false),
+                AccountMeta::new_readonly(*mint_pkey, false)
+            ]
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_settle_bid(
+        payer_pkey: &Pubkey,
+        bidder_pkey: &Pubkey,
+        bid_pda: &Pubkey,
+        vesting_account: &Pubkey,
+        auction_pda: &Pubkey,
+        treasury_pda: &Pubkey,
+        config_pda: &Pubkey,
+        mint_pkey: &Pubkey
+    ) -> Instruction {
+        Instruction::new_with_bytes(
+            IDO_PROGRAM_ID, 
+            &IDOInstruction::SettleBid.pack(), 
+            vec![
+                AccountMeta::new(*payer_pkey, true),
+                AccountMeta::new(*bidder_pkey, false),
+                AccountMeta::new(*bid_pda, false),
+                AccountMeta::new(*vesting_account, false),
+                AccountMeta::new_readonly(*auction_pda, false),
+                AccountMeta::new(*treasury_pda, false),
+                AccountMeta::new(*config_pda, false),
+                AccountMeta::new_readonly(*mint_pkey, false),
+                AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false)
+            ]
+        )
+    }
+
+    /// `account` is the v1 (headerless) Config, the Vesting requires its owner (see `create_migrate_vesting`),
+    /// `mint_pkey` is the mint of the IDO the account belongs to.
+    pub fn create_migrate(payer_pkey: &Pubkey, account: &Pubkey, mint_pkey: &Pubkey) -> Instruction {
+        Instruction::new_with_bytes(
+            IDO_PROGRAM_ID, 
+            &IDOInstruction::Migrate.pack(), 
+            vec![
+                AccountMeta::new(*payer_pkey, true),
+                AccountMeta::new(*account, false),
+                AccountMeta::new_readonly(*mint_pkey, false),
+                AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false)
+            ]
+        )
+    }
+
+    /// v1 Vesting stores neither its Owner nor Mint, so the owner is appended to the `create_migrate` accounts.
+    pub fn create_migrate_vesting(payer_pkey: &Pubkey, vesting_account: &Pubkey, owner_pkey: &Pubkey, mint_pkey: &Pubkey) -> Instruction {
+        let mut migrate_ix: Instruction = create_migrate(payer_pkey, vesting_account, mint_pkey);
+        migrate_ix.accounts.push(AccountMeta::new_readonly(*owner_pkey, false));
+
+        migrate_ix
+    }
+
+    /// `authority_ata` must already exist, it receives the unsold supply, while the raised LAMPORTS go to the authority itself.
+    pub fn create_withdraw(
+        authority_pkey: &Pubkey,
+        authority_ata: &Pubkey,
+        treasury_pda: &Pubkey,
+        config_pda: &Pubkey,
+        mint_pkey: &Pubkey,
+        token_program_id: &Pubkey
+    ) -> Instruction {
+        Instruction::new_with_bytes(
+            IDO_PROGRAM_ID, 
+            &IDOInstruction::Withdraw.pack(), 
+            vec![
+                AccountMeta::new(*authority_pkey, true),
+                AccountMeta::new(*authority_ata, false),
+                AccountMeta::new(*treasury_pda, false),
+                AccountMeta::new(*config_pda, false),
+                AccountMeta::new_readonly(*mint_pkey, false),
+                AccountMeta::new_readonly(*token_program_id, false)
+            ]
+        )
+    }
+
+    /// Same as `create_initialize_with_vesting`, but the source is the payer's ATA & the PDAs are derived from the mint.
+    pub fn create_initialize_with_vesting_for_mint(
+        transfer_amount: u64,
+        lamports_per_token: u32,
+        vesting_strategy: &LinearVestingStrategy,
+        transfer_fee_payer: TransferFeePayer,
+        payer_pkey: &Pubkey,
+        mint_pkey: &Pubkey,
+        token_program_id: &Pubkey
+    ) -> Instruction {
+        create_initialize_with_vesting(
+            transfer_amount, 
+            lamports_per_token, 
+            vesting_strategy, 
+            transfer_fee_payer, 
+            payer_pkey, 
+            &pda::find_ata(payer_pkey, mint_pkey, token_program_id).0, 
+            &pda::find_treasury(mint_pkey).0, 
+            &pda::find_config(mint_pkey).0, 
+            mint_pkey, 
+            token_program_id
+        )
+    }
+
+    /// Same as `create_buy_with_vesting`, but the PDAs are derived from the payer & the mint.
+    pub fn create_buy_with_vesting_for_mint(
+        buy_amount: u64,
+        payer_pkey: &Pubkey,
+        mint_pkey: &Pubkey
+    ) -> Instruction {
+        create_buy_with_vesting(
+            buy_amount, 
+            payer_pkey, 
+            &pda::find_vesting(payer_pkey, mint_pkey).0, 
+            &pda::find_treasury(mint_pkey).0, 
+            &pda::find_config(mint_pkey).0, 
+            mint_pkey
+        )
+    }
+
+    /// Same as `create_claim`, but the recipient's ATA & the PDAs are derived from the wallets & the mint.
+    pub fn create_claim_for_mint(
+        payer_pkey: &Pubkey, 
+        recipient: &Pubkey,
+        mint_pkey: &Pubkey,
+        token_program_id: &Pubkey
+    ) -> Instruction {
+        create_claim(
+            payer_pkey, 
+            recipient, 
+            &pda::find_ata(recipient, mint_pkey, token_program_id).0, 
+            &pda::find_vesting(payer_pkey, mint_pkey).0, 
+            &pda::find_treasury(mint_pkey).0, 
+            &pda::find_config(mint_pkey).0, 
+            mint_pkey, 
+            token_program_id
+        )
+    }
+
+    /// Same as `create_initialize_auction`, but the source is the payer's ATA & the PDAs are derived from the mint.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_initialize_auction_for_mint(
+        transfer_amount: u64,
+        min_price: u32,
+        tick_size: u32,
+        bidding_end_ts: i64,
+        vesting_strategy: &LinearVestingStrategy,
+        transfer_fee_payer: TransferFeePayer,
+        payer_pkey: &Pubkey,
+        mint_pkey: &Pubkey,
+        token_program_id: &Pubkey
+    ) -> Instruction {
+        create_initialize_auction(
+            transfer_amount, 
+            min_price, 
+            tick_size, 
+            bidding_end_ts, 
+            vesting_strategy, 
+            transfer_fee_payer, 
+            payer_pkey, 
+            &pda::find_ata(payer_pkey, mint_pkey, token_program_id).0, 
+            &pda::find_auction(mint_pkey).0, 
+            &pda::find_treasury(mint_pkey).0, 
+            &pda::find_config(mint_pkey).0, 
+            mint_pkey, 
+            token_program_id
+        )
+    }
+
+    /// Same as `create_place_bid`, but the PDAs are derived from the payer & the mint.
+    pub fn create_place_bid_for_mint(
+        quantity: u64,
+        max_price: u32,
+        payer_pkey: &Pubkey,
+        mint_pkey: &Pubkey
+    ) -> Instruction {
+        let (bid_pda, bid_bump) = pda::find_bid(payer_pkey, mint_pkey);
+
+        create_place_bid(
+            quantity, 
+            max_price, 
+            payer_pkey, 
+            &bid_pda, 
+            bid_bump, 
+            &pda::find_auction(mint_pkey).0, 
+            &pda::find_treasury(mint_pkey).0, 
+            &pda::find_config(mint_pkey).0, 
+            mint_pkey
+        )
+    }
+
+    /// Same as `create_settle_auction`, but the PDAs are derived from the mint.
+    pub fn create_settle_auction_for_mint(mint_pkey: &Pubkey) -> Instruction {
+        create_settle_auction(
+            &pda::find_auction(mint_pkey).0, 
+            &pda::find_treasury(mint_pkey).0, 
+            &pda::find_config(mint_pkey).0, 
+            mint_pkey
+        )
+    }
+
+    /// Same as `create_settle_bid`, but the PDAs are derived from the bidder & the mint.
+    pub fn create_settle_bid_for_mint(
+        payer_pkey: &Pubkey,
+        bidder_pkey: &Pubkey,
+        mint_pkey: &Pubkey
+    ) -> Instruction {
+        create_settle_bid(
+            payer_pkey, 
+            bidder_pkey, 
+            &pda::find_bid(bidder_pkey, mint_pkey).0, 
+            &pda::find_vesting(bidder_pkey, mint_pkey).0, 
+            &pda::find_auction(mint_pkey).0, 
+            &pda::find_treasury(mint_pkey).0, 
+            &pda::find_config(mint_pkey).0, 
+            mint_pkey
+        )
+    }
+
+    /// Same as `create_withdraw`, but the authority's ATA & the PDAs are derived from the authority & the mint.
+    pub fn create_withdraw_for_mint(
+        authority_pkey: &Pubkey,
+        mint_pkey: &Pubkey,
+        token_program_id: &Pubkey
+    ) -> Instruction {
+        create_withdraw(
+            authority_pkey, 
+            &pda::find_ata(authority_pkey, mint_pkey, token_program_id).0, 
+            &pda::find_treasury(mint_pkey).0, 
+            &pda::find_config(mint_pkey).0, 
+            mint_pkey, 
+            token_program_id
+        )
+    }
+
+    /// Resolves the extra accounts required by the mint's Token-2022 transfer hook from the hook's validation PDA
+    /// and appends them as remaining accounts to the `InitializeWithVesting`, `InitializeAuction`, `Claim` or `Withdraw` instruction.
+    /// 
+    /// It's a no-op for mints without TransferHook extension and for instructions that don't transfer tokens.
+    /// `fetch_account_data_fn` must return the account data for the given address, e.g. fetched via `RpcClient` or `BanksClient`.
+    /// 
+    /// Note, claimed & withdrawn amounts are defined on-chain, so extra accounts that depend on the transfer amount
+    /// can't be resolved for `Claim` & `Withdraw`.
+    pub async fn add_transfer_hook_accounts<F, Fut>(
+        ix: &mut Instruction,
+        fetch_account_data_fn: F
+    ) -> Result<(), AccountFetchError>
+    where
+        F: Fn(Pubkey) -> Fut,
+        Fut: Future<Output = AccountDataResult>
+    {
+        // (source, mint, destination, authority) positions & transfer amount
+        let ((source, mint, destination, authority), amount) = match IDOInstruction::unpack(&ix.data) {
+            Ok(IDOInstruction::InitializeWithVesting { amount, .. }) => ((1, 4, 2, 0), amount),
+            Ok(IDOInstruction::InitializeAuction { amount, .. }) => ((2, 5, 3, 1), amount),
+            Ok(IDOInstruction::Claim) => ((4, 6, 2, 4), 0),
+            Ok(IDOInstruction::Withdraw) => ((2, 4, 1, 2), 0),
+            _ => return Ok(())
+        };
+
+        let pubkey_at = |index: usize| ix.accounts[index].pubkey;
+        let (source, mint, destination, authority) = (
+            pubkey_at(source), 
+            pubkey_at(mint), 
+            pubkey_at(destination), 
+            pubkey_at(authority)
+        );
+
+        add_extra_account_metas(
+            ix, 
+            &source, 
+            &mint, 
+            &destination, 
+            &authority, 
+            amount, 
+            fetch_account_data_fn
+        ).await
+    }
 }
\ No newline at end of file
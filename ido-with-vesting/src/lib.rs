@@ -3,14 +3,31 @@ pub mod entrypoint;
 pub mod instruction;
 pub mod processor;
 pub mod vesting;
+pub mod auction;
 pub mod constants;
 pub mod contexts;
 pub mod error;
 pub mod utils;
+pub mod pda;
+pub mod token;
+pub mod events;
+#[cfg(feature = "idl")]
+pub mod idl;
+#[cfg(feature = "client")]
+pub mod client;
+#[cfg(feature = "inspect")]
+pub mod inspect;
+#[cfg(feature = "serde")]
+mod serde_helpers;
 
 use solana_program::{declare_id, pubkey::Pubkey};
 
 
+#[cfg(feature = "borsh")]
+pub use borsh;
+#[cfg(feature = "serde")]
+pub use serde;
+
 declare_id!("BhMF5PU37Ssyjwjp4FmHufc1b1pYZXZrRmNP4kV3fFc5");
 
 /// This module is used for external Program ID's declaration.
@@ -25,4 +42,7 @@ pub mod external_ids {
 
     /// if spl_associated_token_account crate is added => remove this
     pub const ATA_PROGRAM_ID: Pubkey = Pubkey::from_str_const("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+
+    /// SPL Memo v3, if spl_memo crate is added => remove this
+    pub const MEMO_PROGRAM_ID: Pubkey = Pubkey::from_str_const("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
 }
\ No newline at end of file
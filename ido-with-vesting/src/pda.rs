@@ -0,0 +1,54 @@
+//! Addresses of every account the program works with, derived from the IDO mint and the wallets.
+//!
+//! Every fn returns `(address, bump)` of the canonical PDA, so off-chain callers and the builders
+//! don't repeat the seeds.
+use solana_program::pubkey::Pubkey;
+use crate::{
+    external_ids::ATA_PROGRAM_ID,
+    utils::derive_program_pda,
+    constants::{
+        IDO_TREASURY_ACCOUNT_SEED,
+        IDO_CONFIG_ACCOUNT_SEED,
+        IDO_VESTING_ACCOUNT_SEED,
+        IDO_AUCTION_ACCOUNT_SEED,
+        IDO_BID_ACCOUNT_SEED
+    }
+};
+
+
+/// Treasury is the owner of the IDO supply.
+pub fn find_treasury(mint: &Pubkey) -> (Pubkey, u8) {
+    derive_program_pda(&[IDO_TREASURY_ACCOUNT_SEED, mint.as_ref()])
+}
+
+/// Note, Config is seeded by the Treasury, so the Treasury is derived first.
+pub fn find_config(mint: &Pubkey) -> (Pubkey, u8) {
+    let treasury_pda: Pubkey = find_treasury(mint).0;
+    derive_program_pda(&[IDO_CONFIG_ACCOUNT_SEED, treasury_pda.as_ref()])
+}
+
+pub fn find_vesting(owner: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+    derive_program_pda(&[IDO_VESTING_ACCOUNT_SEED, owner.as_ref(), mint.as_ref()])
+}
+
+/// Note, Auction is seeded by the Treasury, so the Treasury is derived first.
+pub fn find_auction(mint: &Pubkey) -> (Pubkey, u8) {
+    let treasury_pda: Pubkey = find_treasury(mint).0;
+    derive_program_pda(&[IDO_AUCTION_ACCOUNT_SEED, treasury_pda.as_ref()])
+}
+
+pub fn find_bid(bidder: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+    derive_program_pda(&[IDO_BID_ACCOUNT_SEED, bidder.as_ref(), mint.as_ref()])
+}
+
+/// Associated Token Account for the mint owned by `token_program_id` (either SPL Token or SPL Token 2022).
+pub fn find_ata(owner: &Pubkey, mint: &Pubkey, token_program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            owner.as_ref(),
+            token_program_id.as_ref(),
+            mint.as_ref()
+        ],
+        &ATA_PROGRAM_ID
+    )
+}
@@ -12,25 +12,51 @@ use solana_program::{
     program_error::ProgramError,
     program::{invoke, invoke_signed},
 };
-use spl_token_2022::state::{Account, Mint};
+use spl_token_2022::{
+    state::{Account, Mint},
+    extension::{
+        ExtensionType,
+        StateWithExtensions,
+        BaseStateWithExtensions,
+        memo_transfer::memo_required
+    }
+};
 use super::{
     constants::*,
+    events::IDOEvent,
+    external_ids::MEMO_PROGRAM_ID,
     error::IDOProgramError,
-    utils::derive_program_pda,
+    utils::{derive_program_pda, create_program_pda},
+    token::{TransferAmount, mint_decimals, to_base_units, reserve_amount},
     instruction::IDOInstruction,
     contexts::{
         IDOInitializeCtx, 
         IDOBuyWithVestingCtx,
-        IDOClaimCtx
+        IDOClaimCtx,
+        IDOInitializeAuctionCtx,
+        IDOPlaceBidCtx,
+        IDOSettleAuctionCtx,
+        IDOSettleBidCtx,
+        IDOMigrateCtx,
+        IDOWithdrawCtx
     },
     vesting::{
         LinearVestingStrategy,
         allow_claim_and_define_portion
     },
-    state::{IDOConfigAccount, IDOVestingAccount}
+    state::{
+        IDOConfigAccount, 
+        IDOVestingAccount,
+        TransferFeePayer,
+        IDOAuctionAccount,
+        IDOBidAccount,
+        AccountType,
+        ACCOUNT_VERSION,
+        ACCOUNT_HEADER_LEN
+    }
 };
 
-use std::cell::Ref;
+use std::cell::{Ref, RefMut};
 
 
 pub struct Processor;
@@ -47,12 +73,32 @@ impl Processor {
             IDOInstruction::InitializeWithVesting { 
                 amount, 
                 lamports_per_token, 
-                vesting_strategy 
-            } => Self::process_initialize_ido_with_vesting_instruction(program_id, accounts, amount, lamports_per_token, vesting_strategy)?,
+                vesting_strategy,
+                transfer_fee_payer
+            } => Self::process_initialize_ido_with_vesting_instruction(program_id, accounts, amount, lamports_per_token, vesting_strategy, transfer_fee_payer)?,
             
             IDOInstruction::BuyWithVesting { amount } => Self::process_buy_with_vesting_instruction(program_id, accounts, amount)?,
             
-            IDOInstruction::Claim => Self::process_claim_instruction(accounts)?
+            IDOInstruction::Claim => Self::process_claim_instruction(accounts)?,
+
+            IDOInstruction::InitializeAuction { 
+                amount, 
+                min_price, 
+                tick_size, 
+                bidding_end_ts, 
+                vesting_strategy,
+                transfer_fee_payer
+            } => Self::process_initialize_auction_instruction(program_id, accounts, amount, min_price, tick_size, bidding_end_ts, vesting_strategy, transfer_fee_payer)?,
+
+            IDOInstruction::PlaceBid { quantity, max_price, bump } => Self::process_place_bid_instruction(program_id, accounts, quantity, max_price, bump)?,
+
+            IDOInstruction::SettleAuction => Self::process_settle_auction_instruction(accounts)?,
+
+            IDOInstruction::SettleBid => Self::process_settle_bid_instruction(program_id, accounts)?,
+
+            IDOInstruction::Migrate => Self::process_migrate_instruction(accounts)?,
+
+            IDOInstruction::Withdraw => Self::process_withdraw_instruction(accounts)?
         };
 
         Ok(())
@@ -63,27 +109,53 @@ impl Processor {
         accounts: &[AccountInfo],
         amount: u64,
         lamports_per_token: u32,
-        vesting_strategy: LinearVestingStrategy 
+        vesting_strategy: LinearVestingStrategy,
+        transfer_fee_payer: TransferFeePayer
     ) -> ProgramResult {
-        // 1. Validate the provided Vesting Strategy.
+        // 1. Validate the provided price & Vesting Strategy, `lamports_per_token == 0` marks the auction mode Config.
+        if lamports_per_token == 0 {
+            return Err(IDOProgramError::LamportsPerTokenMustNotEqualZero.into());
+        }
+
         let clock: Clock = Clock::get()?;
         let vesting_strategy: LinearVestingStrategy = vesting_strategy.reinit_with_checked_cliff(&clock);
         vesting_strategy.is_valid(&clock)?;
 
+        let ctx: IDOInitializeCtx = accounts.try_into()?;
+
+        Self::initialize_treasury_and_config(program_id, &ctx, amount, lamports_per_token, vesting_strategy, transfer_fee_payer)?;
+
+        Ok(())
+    }
+
+    /// Shared by both fixed-price IDO and batch auction initialization:
+    /// creates Treasury & Config PDA and deposits `amount` from `signer_ata` to the Treasury.
+    /// 
+    /// The provided `vesting_strategy` must be already validated.
+    /// 
+    /// Returns the amount the treasury actually received, which is smaller than `amount` if the mint charges transfer fees.
+    fn initialize_treasury_and_config(
+        program_id: &Pubkey, 
+        ctx: &IDOInitializeCtx,
+        amount: u64,
+        lamports_per_token: u32,
+        vesting_strategy: LinearVestingStrategy,
+        transfer_fee_payer: TransferFeePayer
+    ) -> Result<u64, ProgramError> {
         let IDOInitializeCtx { 
             signer_info, 
             signer_ata_info, 
             treasury_info,
             config_info,
             mint_info,
-            rent_info,
             token_program_info,
+            remaining_accounts,
             ..
-        } = accounts.try_into()?;
+        } = *ctx;
 
         let signer_pkey: &Pubkey = signer_info.key;
 
-        // 2. Check that the provided accounts are deterministic PDA
+        // 2. Check that the provided accounts are deterministic PDA (signer & programs are validated by the context)
         let mint_pkey_bytes: &[u8] = mint_info.key.as_ref();
 
         let (expected_treasury_ata, treasury_bump) = derive_program_pda(&[
@@ -92,7 +164,7 @@ impl Processor {
         ]);
 
         if *treasury_info.key != expected_treasury_ata {
-            return Err(ProgramError::InvalidInstructionData);
+            return Err(IDOProgramError::InvalidTreasuryAccount.into());
         }
 
         let treasury_pkey_bytes: &[u8] = expected_treasury_ata.as_ref();
@@ -103,20 +175,35 @@ impl Processor {
         ]);
 
         if *config_info.key != expected_config_pda {
-            return Err(ProgramError::InvalidInstructionData);
+            return Err(IDOProgramError::InvalidConfigAccount.into());
         }
 
-        let mint: Mint = Mint::unpack(*mint_info.data.borrow())?;
+        // Token-2022 mint extensions (e.g. TransferFeeConfig, NonTransferable) may require the extensions on the token account as well,
+        // the treasury is also protected with ImmutableOwner (classic SPL Token has no account extensions at all).
+        let is_token_2022: bool = *token_program_info.key == spl_token_2022::ID;
+        let (mint_decimals, treasury_len) = {
+            let mint_data_ref: Ref<&mut [u8]> = mint_info.data.borrow();
+            let mint: StateWithExtensions<Mint> = StateWithExtensions::<Mint>::unpack(*mint_data_ref)?;
+            let mut treasury_extension_types: Vec<ExtensionType> = ExtensionType::get_required_init_account_extensions(
+                &mint.get_extension_types()?
+            );
+
+            if is_token_2022 && !treasury_extension_types.contains(&ExtensionType::ImmutableOwner) {
+                treasury_extension_types.push(ExtensionType::ImmutableOwner);
+            }
+
+            (mint.base.decimals, ExtensionType::try_calculate_account_len::<Account>(&treasury_extension_types)?)
+        };
 
         // 3. Create accounts with SystemProgram
-        let rent_sysvar: Rent = Rent::from_account_info(rent_info)?;
+        let rent_sysvar: Rent = Rent::get()?;
 
-        let treasury_rent_exempt: u64 = rent_sysvar.minimum_balance(Account::LEN);
+        let treasury_rent_exempt: u64 = rent_sysvar.minimum_balance(treasury_len);
         let create_treasury_ix: Instruction = system_instruction::create_account(
             signer_pkey, 
             treasury_info.key, 
             treasury_rent_exempt, 
-            Account::LEN as u64, 
+            treasury_len as u64, 
             token_program_info.key
         );
         invoke_signed(
@@ -145,8 +232,20 @@ impl Processor {
             &[&[IDO_CONFIG_ACCOUNT_SEED, treasury_pkey_bytes, &[config_bump]]]
         )?;
 
-        // 4. Initialize Treasury Token Account wtih SPL token program
-        let initialize_treasury_ix: Instruction = spl_token_2022::instruction::initialize_account(
+        // 4. Initialize Treasury Token Account wtih SPL token program (either SPL Token or SPL Token 2022 instruction layout is the same),
+        // ImmutableOwner must be initialized before the account itself.
+        if is_token_2022 {
+            let initialize_immutable_owner_ix: Instruction = spl_token_2022::instruction::initialize_immutable_owner(
+                token_program_info.key, 
+                treasury_info.key
+            )?;
+            invoke(
+                &initialize_immutable_owner_ix,
+                std::slice::from_ref(treasury_info)
+            )?;
+        }
+
+        let initialize_treasury_ix: Instruction = spl_token_2022::instruction::initialize_account3(
             token_program_info.key, 
             treasury_info.key, 
             mint_info.key, 
@@ -156,45 +255,50 @@ impl Processor {
             &initialize_treasury_ix,
             &[
                 treasury_info.clone(),
-                mint_info.clone(),
-                treasury_info.clone(),
-                rent_info.clone()
+                mint_info.clone()
             ]
         )?;
 
-        // 5. Initialize Config PDA Account
-        let unlocks: u8 = ((vesting_strategy.vesting_end_ts - vesting_strategy.cliff_end_ts) / vesting_strategy.unlock_period) as u8; 
-        let config_account: IDOConfigAccount = IDOConfigAccount {
-            vesting_strategy,
-            lamports_per_token,
-            unlocks,
-            bump: config_bump,
-            is_initialized: true
-        };
-        config_account.pack_into_slice(*config_info.data.borrow_mut());
-
-        // 6. Transfer provided supply from `signer_ata` to `treasury`
-        let transfer_checked_ix: Instruction = spl_token_2022::instruction::transfer_checked(
+        // 5. Transfer provided supply from `signer_ata` to `treasury`, transfer hook extra accounts (if any) are forwarded.
+        TransferAmount { amount, fee: None }.invoke_transfer_checked(
             token_program_info.key, 
-            signer_ata_info.key, 
-            mint_info.key, 
-            treasury_info.key, 
-            signer_pkey, 
-            &[], 
-            amount, 
-            mint.decimals
-        )?;
-        invoke(
-            &transfer_checked_ix,
-            &[
-                signer_ata_info.clone(),
-                mint_info.clone(),
-                treasury_info.clone(),
-                signer_info.clone()
-            ]
+            signer_ata_info, 
+            mint_info, 
+            treasury_info, 
+            signer_info, 
+            remaining_accounts, 
+            mint_decimals,
+            &[]
         )?;
 
-        Ok(())
+        // 6. Initialize Config PDA Account with the amount the treasury actually received (transfer fee is withheld on the treasury).
+        let deposited_amount: u64 = StateWithExtensions::<Account>::unpack(*treasury_info.data.borrow())?.base.amount;
+
+        let unlocks: u8 = vesting_strategy.unlocks()?;
+        {
+            let mut config_data_ref: RefMut<&mut [u8]> = config_info.data.borrow_mut();
+            let config_account: &mut IDOConfigAccount = IDOConfigAccount::init(&mut config_data_ref)?;
+
+            config_account.vesting_strategy = vesting_strategy;
+            config_account.lamports_per_token = lamports_per_token;
+            config_account.unlocks = unlocks;
+            config_account.bump = config_bump;
+            config_account.treasury_bump = treasury_bump;
+            config_account.transfer_fee_payer = transfer_fee_payer as u8;
+            config_account.deposited_amount = deposited_amount;
+            config_account.mint = *mint_info.key;
+            config_account.treasury = *treasury_info.key;
+            config_account.authority = *signer_pkey;
+        }
+
+        IDOEvent::IdoInitialized {
+            mint: *mint_info.key,
+            authority: *signer_pkey,
+            deposited_amount,
+            lamports_per_token
+        }.emit();
+
+        Ok(deposited_amount)
     }
 
     fn process_buy_with_vesting_instruction(
@@ -209,68 +313,165 @@ impl Processor {
             treasury_info, 
             config_info, 
             mint_info, 
+            config_account,
             ..
         } = accounts.try_into()?;
         
         let signer_pkey: &Pubkey = signer_info.key;
 
-        let (signer_pkey_bytes, mint_pkey_bytes) = (
-            signer_pkey.as_ref(),
-            mint_info.key.as_ref()
-        );
-
-        let (expected_vesting_pda, vesting_bump) = derive_program_pda(&[
-            IDO_VESTING_ACCOUNT_SEED,
-            signer_pkey_bytes,
-            mint_pkey_bytes
-        ]);
+        let vesting_bump: u8 = Self::check_vesting_pda(vesting_info, signer_pkey, mint_info.key)?;
 
-        let (expected_config_pda, _config_bump) = derive_program_pda(&[
+        let expected_config_pda: Pubkey = create_program_pda(&[
             IDO_CONFIG_ACCOUNT_SEED,
-            treasury_info.key.as_ref()
-        ]);
+            treasury_info.key.as_ref(),
+            &[config_account.bump]
+        ])?;
 
-        let treasury_ata: Account = Account::unpack(*treasury_info.data.borrow())?;
+        let treasury_ata: Account = StateWithExtensions::<Account>::unpack(*treasury_info.data.borrow())?.base;
 
-        if expected_vesting_pda != *vesting_info.key
-        || expected_config_pda != *config_info.key
-        || treasury_ata.mint != *mint_info.key
-        || treasury_ata.owner != *treasury_info.key
-        {
-            return Err(ProgramError::InvalidInstructionData);
+        if expected_config_pda != *config_info.key {
+            return Err(IDOProgramError::InvalidConfigAccount.into());
+        }
+
+        if treasury_ata.mint != *mint_info.key {
+            return Err(IDOProgramError::TreasuryMintMismatch.into());
+        }
+
+        if treasury_ata.owner != *treasury_info.key {
+            return Err(IDOProgramError::TreasuryOwnerMismatch.into());
         }
 
         // 3. Get `lamports_per_token` from Config PDA & check whether the vesting is over or not & `calculate lamports_transfer_amount`
         let clock: Clock = Clock::get()?;
-        let config_account: IDOConfigAccount = IDOConfigAccount::unpack(*config_info.data.borrow())?;
         
+        // auction mode configs don't have a fixed price, tokens are distributed via `SettleBid` instead.
+        if config_account.lamports_per_token == 0 {
+            return Err(IDOProgramError::BuyNotAllowedInAuctionMode.into());
+        }
+
         if config_account.vesting_strategy.vesting_end_ts <= clock.unix_timestamp {
             return Err(IDOProgramError::VestingPeriodEnded.into());
         }
 
-        let lamports_transfer_amount: u64 = amount * config_account.lamports_per_token as u64;
+        let lamports_transfer_amount: u64 = amount
+            .checked_mul(config_account.lamports_per_token as u64)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+
+        // tokens are sold only while the deposited supply covers them along with the fees the treasury pays on claim,
+        // `sold_amount` is in whole tokens, `reserved_amount` is in base units
+        let sold_amount: u64 = config_account.sold_amount
+            .checked_add(amount)
+            .ok_or(IDOProgramError::SupplyExceeded)?;
+        let base_amount: u64 = to_base_units(amount, mint_decimals(mint_info)?).map_err(|_| IDOProgramError::SupplyExceeded)?;
+        let reserved_amount: u64 = Self::reserve_sold_amount(mint_info, &config_account, base_amount)?
+            .checked_add(config_account.reserved_amount)
+            .filter(|reserved_amount| *reserved_amount <= config_account.deposited_amount)
+            .ok_or(IDOProgramError::SupplyExceeded)?;
+
+        {
+            let mut config_data_ref: RefMut<&mut [u8]> = config_info.data.borrow_mut();
+            let config_account: &mut IDOConfigAccount = IDOConfigAccount::load_mut(&mut config_data_ref)?;
+
+            config_account.sold_amount = sold_amount;
+            config_account.reserved_amount = reserved_amount;
+        }
         let rent: Rent = Rent::get()?;
 
         // 4. Initialize Vesting PDA if needed OR unpack it and update necessary fields.
-        let maybe_vesting_account  = {
-            // gets dropped at the end of this scope, so we dont have to call `std::mem::drop` manually twice in if else blocks
-            let vesting_data_ref: Ref<&mut [u8]> = vesting_info.data.borrow();
-            IDOVestingAccount::unpack(*vesting_data_ref)
-        };
-        
-        if let Ok(mut vesting_account) = maybe_vesting_account {
+        Self::create_or_top_up_vesting_account(
+            program_id, 
+            signer_info, 
+            vesting_info, 
+            signer_pkey, 
+            mint_info.key, 
+            vesting_bump, 
+            amount, 
+            config_account.unlocks
+        )?;
+
+        // IMPROTANT: this code of block must be located below step 3, because we have to know the updated `signer_info.lamports` balance.
+        // 
+        // 5. Check if `signer` lamports balance is not smaller than the required amount.
+        // SystemProgram owned accounts have data len == 0 bytes.
+        let signers_balance_without_rent: u64 = signer_info.lamports()
+            .checked_sub(rent.minimum_balance(0))
+            .ok_or(ProgramError::InsufficientFunds)?;
+
+        if signers_balance_without_rent < lamports_transfer_amount {
+            return Err(ProgramError::InsufficientFunds);
+        }
+
+        // 6. Transfer `lamports_transfer_amount` to Config PDA, the program owns it, so the authority can `Withdraw` it
+        // regardless of the token program (SPL Token can't release the LAMPORTS of the treasury token account).
+        let transfer_ix: Instruction = system_instruction::transfer(
+            signer_pkey, 
+            config_info.key, 
+            lamports_transfer_amount
+        );
+        invoke(
+            &transfer_ix,
+            &[
+                signer_info.clone(),
+                config_info.clone()
+            ]
+        )?;
+
+        IDOEvent::Purchased {
+            mint: *mint_info.key,
+            buyer: *signer_pkey,
+            amount: base_amount,
+            lamports: lamports_transfer_amount
+        }.emit();
+
+        Ok(())
+    }
+
+    /// Tokens (with decimals) the treasury must keep for `amount` (with decimals) sold: every vesting is claimed
+    /// at most once per unlock & once at the end, so each sold portion is reserved for `unlocks + 1` transfers.
+    fn reserve_sold_amount(
+        mint_info: &AccountInfo,
+        config_account: &IDOConfigAccount,
+        amount: u64
+    ) -> Result<u64, ProgramError> {
+        let mint_data_ref: Ref<&mut [u8]> = mint_info.data.borrow();
+        let mint: StateWithExtensions<Mint> = StateWithExtensions::<Mint>::unpack(*mint_data_ref)?;
+
+        reserve_amount(
+            &mint,
+            amount,
+            config_account.unlocks as u64 + 1,
+            config_account.transfer_fee_payer()?
+        )
+    }
+
+    /// Initializes Vesting PDA (rent is paid by `payer`) if it doesn't exist yet,
+    /// otherwise increases the bought amount and advances `amount_per_unlock`.
+    #[allow(clippy::too_many_arguments)]
+    fn create_or_top_up_vesting_account<'a>(
+        program_id: &Pubkey,
+        payer_info: &AccountInfo<'a>,
+        vesting_info: &AccountInfo<'a>,
+        owner_pkey: &Pubkey,
+        mint_pkey: &Pubkey,
+        vesting_bump: u8,
+        amount: u64,
+        unlocks: u8
+    ) -> ProgramResult {
+        // outdated or foreign account must not be silently treated as the missing one
+        if !vesting_info.data_is_empty() {
+            let mut vesting_data_ref: RefMut<&mut [u8]> = vesting_info.data.borrow_mut();
+            let vesting_account: &mut IDOVestingAccount = IDOVestingAccount::load_mut(&mut vesting_data_ref)?;
+
             let updated_bought_amount: u64 = vesting_account.bought_amount
                 .checked_add(amount)
                 .ok_or(ProgramError::ArithmeticOverflow)?;
             vesting_account.bought_amount = updated_bought_amount;
-            vesting_account.amount_per_unlock = updated_bought_amount / config_account.unlocks as u64;
-
-            vesting_account.pack_into_slice(*vesting_info.data.borrow_mut()); 
+            vesting_account.amount_per_unlock = updated_bought_amount / unlocks as u64;
         } else {
-            let vesting_rent_exempt: u64 = rent.minimum_balance(IDOVestingAccount::LEN);
+            let vesting_rent_exempt: u64 = Rent::get()?.minimum_balance(IDOVestingAccount::LEN);
             let create_vesting_account_ix: Instruction = system_instruction::create_account(
-                signer_pkey, 
-                &expected_vesting_pda, 
+                payer_info.key, 
+                vesting_info.key, 
                 vesting_rent_exempt, 
                 IDOVestingAccount::LEN as u64, 
                 program_id
@@ -278,54 +479,62 @@ impl Processor {
             invoke_signed(
                 &create_vesting_account_ix,
                 &[
-                    signer_info.clone(),
+                    payer_info.clone(),
                     vesting_info.clone()
                 ],
-                &[&[IDO_VESTING_ACCOUNT_SEED, signer_pkey_bytes, mint_pkey_bytes, &[vesting_bump]]]
+                &[&[IDO_VESTING_ACCOUNT_SEED, owner_pkey.as_ref(), mint_pkey.as_ref(), &[vesting_bump]]]
             )?;
 
             let vesting_account: IDOVestingAccount = IDOVestingAccount::new(
+                *owner_pkey,
+                *mint_pkey,
                 amount, 
-                amount / config_account.unlocks as u64, 
+                amount / unlocks as u64, 
                 vesting_bump
             );
 
-            vesting_account.pack_into_slice(*vesting_info.data.borrow_mut());
+            vesting_info.data.borrow_mut().copy_from_slice(bytemuck::bytes_of(&vesting_account));
         };
 
-        // IMPROTANT: this code of block must be located below step 3, because we have to know the updated `signer_info.lamports` balance.
-        // 
-        // 5. Check if `signer` lamports balance is not smaller than the required amount.
-        // SystemProgram owned accounts have data len == 0 bytes.
-        let signers_balance_without_rent: u64 = signer_info.lamports()
-            .checked_sub(rent.minimum_balance(0))
-            .ok_or(ProgramError::InsufficientFunds)?;
+        Ok(())
+    }
 
-        if signers_balance_without_rent < lamports_transfer_amount {
-            return Err(ProgramError::InsufficientFunds);
-        }
+    /// Validates Vesting PDA & returns its bump. The stored bump is used if the account exists,
+    /// otherwise the PDA is derived, since the account is created on the first buy (or bid settlement).
+    fn check_vesting_pda(
+        vesting_info: &AccountInfo,
+        owner_pkey: &Pubkey,
+        mint_pkey: &Pubkey
+    ) -> Result<u8, ProgramError> {
+        let (expected_vesting_pda, vesting_bump) = if vesting_info.data_is_empty() {
+            derive_program_pda(&[
+                IDO_VESTING_ACCOUNT_SEED,
+                owner_pkey.as_ref(),
+                mint_pkey.as_ref()
+            ])
+        } else {
+            let vesting_bump: u8 = IDOVestingAccount::load(&vesting_info.data.borrow())?.bump;
+            let expected_vesting_pda: Pubkey = create_program_pda(&[
+                IDO_VESTING_ACCOUNT_SEED,
+                owner_pkey.as_ref(),
+                mint_pkey.as_ref(),
+                &[vesting_bump]
+            ])?;
 
-        // 6. Transfer `lamports_transfer_amount` to `treasury ATA`.
-        let transfer_ix: Instruction = system_instruction::transfer(
-            signer_pkey, 
-            treasury_info.key, 
-            lamports_transfer_amount
-        );
-        invoke(
-            &transfer_ix,
-            &[
-                signer_info.clone(),
-                treasury_info.clone()
-            ]
-        )?;
+            (expected_vesting_pda, vesting_bump)
+        };
 
-        Ok(())
+        if expected_vesting_pda != *vesting_info.key {
+            return Err(IDOProgramError::InvalidVestingAccount.into());
+        }
+
+        Ok(vesting_bump)
     }
 
     fn process_claim_instruction(
         accounts: &[AccountInfo]
     ) -> ProgramResult {
-        // 1. Check deterministic derivation
+        // 1. Check deterministic derivation (programs & the config's mint are validated by the context)
         let IDOClaimCtx { 
             signer_info,
             recipient_info, 
@@ -336,58 +545,58 @@ impl Processor {
             mint_info, 
             associated_token_program_info,
             token_program_info,
-            system_program_info
+            system_program_info,
+            memo_program_info,
+            remaining_accounts,
+            config_account
         } = accounts.try_into()?;
 
         let signer_pkey: Pubkey = *signer_info.key;
 
         let mint_pkey_bytes: &[u8] = mint_info.key.as_ref();
 
-        let (expected_vesting_pda, _vesting_bump) = derive_program_pda(&[
-            IDO_VESTING_ACCOUNT_SEED,
-            signer_pkey.as_ref(),
-            mint_pkey_bytes
-        ]);
+        Self::check_vesting_pda(vesting_info, &signer_pkey, mint_info.key)?;
 
-        let (expected_config_pda, _config_bump) = derive_program_pda(&[
+        let expected_config_pda: Pubkey = create_program_pda(&[
             IDO_CONFIG_ACCOUNT_SEED,
-            treasury_info.key.as_ref()
-        ]);
+            treasury_info.key.as_ref(),
+            &[config_account.bump]
+        ])?;
 
         // in this case we dont need to unpack treasury ata, but we still have to validate that the correct account is provided.
-        let (expected_treasury_pda, treasury_bump) = derive_program_pda(&[
+        let treasury_bump: u8 = config_account.treasury_bump;
+        let expected_treasury_pda: Pubkey = create_program_pda(&[
             IDO_TREASURY_ACCOUNT_SEED,
-            mint_pkey_bytes
-        ]);
+            mint_pkey_bytes,
+            &[treasury_bump]
+        ])?;
 
-        if expected_vesting_pda != *vesting_info.key
-        || expected_config_pda != *config_info.key
-        || expected_treasury_pda != *treasury_info.key {
-            return Err(ProgramError::InvalidInstructionData);
+        if expected_config_pda != *config_info.key {
+            return Err(IDOProgramError::InvalidConfigAccount.into());
         }
 
-        // 2. Define and Check if the `transfer_amount` can be claimed.        
-        let config_account: IDOConfigAccount = IDOConfigAccount::unpack(*config_info.data.borrow())?;
-        
-        let vesting_account_ref: Ref<&mut [u8]> = vesting_info.data.borrow();
-        
-        let mut vesting_account: IDOVestingAccount = match IDOVestingAccount::unpack(*vesting_account_ref) {
-            Ok(acc) => acc,
-            _ => return Err(IDOProgramError::ClaimBeforeBuy.into())
-        };
+        if expected_treasury_pda != *treasury_info.key {
+            return Err(IDOProgramError::InvalidTreasuryAccount.into());
+        }
 
-        std::mem::drop(vesting_account_ref);
+        // 2. Define and Check if the `transfer_amount` can be claimed.        
+        if vesting_info.data_is_empty() {
+            return Err(IDOProgramError::ClaimBeforeBuy.into());
+        }
 
         let clock: Clock = Clock::get()?;
         let vesting_strategy: LinearVestingStrategy = config_account.vesting_strategy;
         
-        let raw_transfer_amount: u64 = allow_claim_and_define_portion(
-            &clock, 
-            &vesting_strategy, 
-            &mut vesting_account
-        )?;
+        // the vesting account is updated in place, the borrow is released before any CPI
+        let raw_transfer_amount: u64 = {
+            let mut vesting_data_ref: RefMut<&mut [u8]> = vesting_info.data.borrow_mut();
 
-        vesting_account.pack_into_slice(*vesting_info.data.borrow_mut());
+            allow_claim_and_define_portion(
+                &clock, 
+                &vesting_strategy, 
+                IDOVestingAccount::load_mut(&mut vesting_data_ref)?
+            )?
+        };
 
         // 3. If `recipient_ata` is not initialized => create PDA & initialize account.  
         if recipient_ata_info.owner == system_program_info.key {
@@ -417,32 +626,809 @@ impl Processor {
             )?;
         }
 
-        // 4. Transfer `transfer_amount` to `recipient_ata`
-        let mint_account: Mint = Mint::unpack(*mint_info.data.borrow())?;
-        let mint_decimals: u8 = mint_account.decimals;
-        let transfer_amount: u64 = raw_transfer_amount * 10u64.pow(mint_decimals as u32);
+        // 4. Check the recipient's Token-2022 account extensions:
+        // - frozen account (e.g. created under DefaultAccountState::Frozen) can't receive tokens until thawed by the freeze authority.
+        // - MemoTransfer requires the memo instruction right before the transfer.
+        // - CpiGuard restricts only outgoing transfers, so the recipient may keep it enabled.
+        let is_memo_required: bool = {
+            let recipient_ata_data_ref: Ref<&mut [u8]> = recipient_ata_info.data.borrow();
+            let recipient_ata: StateWithExtensions<Account> = StateWithExtensions::<Account>::unpack(*recipient_ata_data_ref)?;
+
+            if recipient_ata.base.is_frozen() {
+                return Err(IDOProgramError::RecipientAccountFrozen.into());
+            }
+
+            memo_required(&recipient_ata)
+        };
+
+        if is_memo_required {
+            let memo_ix: Instruction = Instruction::new_with_bytes(
+                MEMO_PROGRAM_ID, 
+                CLAIM_MEMO, 
+                vec![]
+            );
+            invoke(&memo_ix, std::slice::from_ref(memo_program_info))?;
+        }
+
+        // 5. Transfer `transfer_amount` to `recipient_ata`, Token-2022 transfer fee is absorbed according to the config
+        // & transfer hook extra accounts (if any) are forwarded.
+        let (claimed_amount, transfer_amount, mint_decimals) = {
+            let mint_data_ref: Ref<&mut [u8]> = mint_info.data.borrow();
+            let mint: StateWithExtensions<Mint> = StateWithExtensions::<Mint>::unpack(*mint_data_ref)?;
+            let mint_decimals: u8 = mint.base.decimals;
+            let claimed_amount: u64 = to_base_units(raw_transfer_amount, mint_decimals)?;
+
+            let transfer_amount: TransferAmount = TransferAmount::new(
+                &mint, 
+                clock.epoch, 
+                claimed_amount, 
+                config_account.transfer_fee_payer()?
+            )?;
+
+            (claimed_amount, transfer_amount, mint_decimals)
+        };
 
-        let transfer_checked_ix: Instruction = spl_token_2022::instruction::transfer_checked(
+        transfer_amount.invoke_transfer_checked(
             token_program_info.key, 
-            treasury_info.key, 
-            mint_info.key, 
-            recipient_ata_info.key, 
-            treasury_info.key, 
-            &[], 
-            transfer_amount, 
-            mint_decimals
+            treasury_info, 
+            mint_info, 
+            recipient_ata_info, 
+            treasury_info, 
+            remaining_accounts,
+            mint_decimals,
+            &[&[IDO_TREASURY_ACCOUNT_SEED, mint_pkey_bytes, &[treasury_bump]]]
+        )?;
+
+        IDOEvent::Claimed {
+            mint: *mint_info.key,
+            buyer: signer_pkey,
+            recipient: *recipient_info.key,
+            amount: claimed_amount
+        }.emit();
+
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn process_initialize_auction_instruction(
+        program_id: &Pubkey, 
+        accounts: &[AccountInfo],
+        amount: u64,
+        min_price: u32,
+        tick_size: u32,
+        bidding_end_ts: i64,
+        vesting_strategy: LinearVestingStrategy,
+        transfer_fee_payer: TransferFeePayer
+    ) -> ProgramResult {
+        // 1. Validate the auction params & the provided Vesting Strategy.
+        let clock: Clock = Clock::get()?;
+
+        if tick_size == 0 {
+            return Err(IDOProgramError::TickSizeMustNotEqualZero.into());
+        }
+
+        // the highest price level must fit into u32, so `clearing_price` never overflows
+        tick_size
+            .checked_mul(AUCTION_PRICE_LEVELS as u32 - 1)
+            .and_then(|max_offset| max_offset.checked_add(min_price))
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+
+        if bidding_end_ts <= clock.unix_timestamp {
+            return Err(IDOProgramError::BiddingEndMustBeGreaterThanNow.into());
+        }
+
+        // winners' vesting can't start before they are known, so missing cliff is set to the end of the bidding window.
+        let vesting_strategy: LinearVestingStrategy = LinearVestingStrategy {
+            cliff_end_ts: if vesting_strategy.cliff_end_ts == 0 { bidding_end_ts } else { vesting_strategy.cliff_end_ts },
+            ..vesting_strategy
+        };
+        vesting_strategy.is_valid(&clock)?;
+
+        if vesting_strategy.cliff_end_ts < bidding_end_ts {
+            return Err(IDOProgramError::CliffMustNotPrecedeBiddingEnd.into());
+        }
+
+        let IDOInitializeAuctionCtx { auction_info, ido_ctx } = accounts.try_into()?;
+        let IDOInitializeCtx { signer_info, treasury_info, config_info, mint_info, .. } = ido_ctx;
+
+        // 2. Check that the provided Auction account is deterministic PDA
+        let treasury_pkey_bytes: &[u8] = treasury_info.key.as_ref();
+
+        let (expected_auction_pda, auction_bump) = derive_program_pda(&[
+            IDO_AUCTION_ACCOUNT_SEED,
+            treasury_pkey_bytes
+        ]);
+
+        if *auction_info.key != expected_auction_pda {
+            return Err(IDOProgramError::InvalidAuctionAccount.into());
+        }
+
+        // 3. Create Treasury & Config PDA and deposit the supply. 
+        // `lamports_per_token == 0` marks Config as the auction mode one, so `BuyWithVesting` is rejected.
+        let deposited_amount: u64 = Self::initialize_treasury_and_config(
+            program_id, 
+            &ido_ctx, 
+            amount, 
+            0, 
+            vesting_strategy, 
+            transfer_fee_payer
         )?;
+
+        // 4. Create & Initialize Auction PDA, only the amount the treasury actually received is sold.
+        // The whole supply is reserved (along with the fees the treasury pays on claim) until the auction is settled.
+        let (supply, reserved_amount) = Self::define_auction_supply(mint_info, deposited_amount, &vesting_strategy, transfer_fee_payer)?;
+
+        {
+            let mut config_data_ref: RefMut<&mut [u8]> = config_info.data.borrow_mut();
+            IDOConfigAccount::load_mut(&mut config_data_ref)?.reserved_amount = reserved_amount;
+        }
+
+        let auction_rent_exempt: u64 = Rent::get()?.minimum_balance(IDOAuctionAccount::LEN);
+        let create_auction_ix: Instruction = system_instruction::create_account(
+            signer_info.key, 
+            auction_info.key, 
+            auction_rent_exempt, 
+            IDOAuctionAccount::LEN as u64, 
+            program_id
+        );
         invoke_signed(
-            &transfer_checked_ix, 
+            &create_auction_ix,
             &[
-                treasury_info.clone(),
-                mint_info.clone(),
-                recipient_ata_info.clone(),
-                treasury_info.clone()
-            ], 
-            &[&[IDO_TREASURY_ACCOUNT_SEED, mint_pkey_bytes, &[treasury_bump]]]
+                signer_info.clone(),
+                auction_info.clone()
+            ],
+            &[&[IDO_AUCTION_ACCOUNT_SEED, treasury_pkey_bytes, &[auction_bump]]]
         )?;
 
+        let auction_account: IDOAuctionAccount = IDOAuctionAccount::new(
+            bidding_end_ts, 
+            supply, 
+            min_price, 
+            tick_size, 
+            auction_bump
+        );
+        auction_account.pack_into_slice(*auction_info.data.borrow_mut());
+
+        IDOEvent::AuctionInitialized {
+            mint: *mint_info.key,
+            supply: to_base_units(supply, mint_decimals(mint_info)?)?,
+            min_price,
+            tick_size,
+            bidding_end_ts
+        }.emit();
+
+        Ok(())
+    }
+
+    /// The largest supply (in whole tokens), whose reserve fits into `deposited_amount`, & the reserve itself.
+    /// 
+    /// Every fill is at least one token & its vesting is claimed at most `unlocks + 1` times, so the number of transfers
+    /// is bounded by `supply * (unlocks + 1)`. Without the fee absorbed by the treasury the whole deposited tokens are sold.
+    fn define_auction_supply(
+        mint_info: &AccountInfo,
+        deposited_amount: u64,
+        vesting_strategy: &LinearVestingStrategy,
+        transfer_fee_payer: TransferFeePayer
+    ) -> Result<(u64, u64), ProgramError> {
+        let mint_data_ref: Ref<&mut [u8]> = mint_info.data.borrow();
+        let mint: StateWithExtensions<Mint> = StateWithExtensions::<Mint>::unpack(*mint_data_ref)?;
+        let unlocks: u8 = vesting_strategy.unlocks()?;
+        let token: u64 = to_base_units(1, mint.base.decimals)?;
+
+        let supply_reserve = |supply: u64| Self::reserve_auction_supply(&mint, supply, unlocks, transfer_fee_payer);
+
+        // the reserve grows with the supply, so the largest fitting supply is found by the binary search
+        let (mut supply, mut max_supply) = (0, deposited_amount / token);
+
+        while supply < max_supply {
+            let mid_supply: u64 = max_supply - (max_supply - supply) / 2;
+
+            if supply_reserve(mid_supply)? <= deposited_amount {
+                supply = mid_supply;
+            } else {
+                max_supply = mid_supply - 1;
+            }
+        }
+
+        Ok((supply, supply_reserve(supply)?))
+    }
+
+    /// Tokens (with decimals) the treasury must keep for the auction `supply` (without decimals):
+    /// every token may end up in a separate vesting, so each one is reserved for `unlocks + 1` transfers.
+    fn reserve_auction_supply(
+        mint: &StateWithExtensions<Mint>,
+        supply: u64,
+        unlocks: u8,
+        transfer_fee_payer: TransferFeePayer
+    ) -> Result<u64, ProgramError> {
+        reserve_amount(
+            mint, 
+            to_base_units(supply, mint.base.decimals)?, 
+            supply.saturating_mul(unlocks as u64 + 1), 
+            transfer_fee_payer
+        )
+    }
+
+    fn process_place_bid_instruction(
+        program_id: &Pubkey, 
+        accounts: &[AccountInfo],
+        quantity: u64,
+        max_price: u32,
+        bid_bump: u8
+    ) -> ProgramResult {
+        // 1. Check deterministic derivation
+        let IDOPlaceBidCtx { 
+            signer_info, 
+            bid_info, 
+            auction_info, 
+            treasury_info, 
+            config_info, 
+            mint_info, 
+            config_account,
+            ..
+        } = accounts.try_into()?;
+
+        // Bid PDA doesn't exist yet, so it's validated with the bump provided by the bidder (the builders pass the canonical one),
+        // every other account is validated with the stored bump
+        let mut auction_account: IDOAuctionAccount = IDOAuctionAccount::unpack(*auction_info.data.borrow())?;
+
+        let (signer_pkey_bytes, mint_pkey_bytes) = (
+            signer_info.key.as_ref(),
+            mint_info.key.as_ref()
+        );
+
+        let expected_bid_pda: Pubkey = create_program_pda(&[
+            IDO_BID_ACCOUNT_SEED,
+            signer_pkey_bytes,
+            mint_pkey_bytes,
+            &[bid_bump]
+        ])?;
+
+        let expected_treasury_pda: Pubkey = create_program_pda(&[
+            IDO_TREASURY_ACCOUNT_SEED,
+            mint_pkey_bytes,
+            &[config_account.treasury_bump]
+        ])?;
+
+        let treasury_pkey_bytes: &[u8] = expected_treasury_pda.as_ref();
+
+        let expected_auction_pda: Pubkey = create_program_pda(&[
+            IDO_AUCTION_ACCOUNT_SEED,
+            treasury_pkey_bytes,
+            &[auction_account.bump]
+        ])?;
+
+        let expected_config_pda: Pubkey = create_program_pda(&[
+            IDO_CONFIG_ACCOUNT_SEED,
+            treasury_pkey_bytes,
+            &[config_account.bump]
+        ])?;
+
+        if expected_bid_pda != *bid_info.key {
+            return Err(IDOProgramError::InvalidBidAccount.into());
+        }
+
+        if expected_treasury_pda != *treasury_info.key {
+            return Err(IDOProgramError::InvalidTreasuryAccount.into());
+        }
+
+        if expected_auction_pda != *auction_info.key {
+            return Err(IDOProgramError::InvalidAuctionAccount.into());
+        }
+
+        if expected_config_pda != *config_info.key {
+            return Err(IDOProgramError::InvalidConfigAccount.into());
+        }
+
+        // 2. Check that the bidding window is open & the bid is valid.
+        let clock: Clock = Clock::get()?;
+
+        if !auction_account.is_bidding_active(&clock) {
+            return Err(IDOProgramError::BiddingEnded.into());
+        }
+
+        if quantity == 0 {
+            return Err(IDOProgramError::BidQuantityMustNotEqualZero.into());
+        }
+
+        let price_level: usize = auction_account.price_level(max_price)?;
+
+        if !bid_info.data_is_empty() {
+            return Err(IDOProgramError::BidAlreadyPlaced.into());
+        }
+
+        // 3. Create Bid PDA, which escrows `quantity * max_price` lamports on top of the rent.
+        let escrow_amount: u64 = quantity
+            .checked_mul(max_price as u64)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        let bid_lamports: u64 = Rent::get()?
+            .minimum_balance(IDOBidAccount::LEN)
+            .checked_add(escrow_amount)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+
+        let create_bid_ix: Instruction = system_instruction::create_account(
+            signer_info.key, 
+            bid_info.key, 
+            bid_lamports, 
+            IDOBidAccount::LEN as u64, 
+            program_id
+        );
+        invoke_signed(
+            &create_bid_ix,
+            &[
+                signer_info.clone(),
+                bid_info.clone()
+            ],
+            &[&[IDO_BID_ACCOUNT_SEED, signer_pkey_bytes, mint_pkey_bytes, &[bid_bump]]]
+        )?;
+
+        let bid_account: IDOBidAccount = IDOBidAccount {
+            quantity,
+            max_price,
+            bump: bid_bump,
+            is_initialized: true
+        };
+        bid_account.pack_into_slice(*bid_info.data.borrow_mut());
+
+        // 4. Add the bid to the demand book.
+        auction_account.demand[price_level] = auction_account.demand[price_level]
+            .checked_add(quantity)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        auction_account.pack_into_slice(*auction_info.data.borrow_mut());
+
+        IDOEvent::BidPlaced {
+            mint: *mint_info.key,
+            bidder: *signer_info.key,
+            quantity: to_base_units(quantity, mint_decimals(mint_info)?)?,
+            max_price
+        }.emit();
+
+        Ok(())
+    }
+
+    fn process_settle_auction_instruction(
+        accounts: &[AccountInfo]
+    ) -> ProgramResult {
+        // 1. Check deterministic derivation with the stored bumps
+        let IDOSettleAuctionCtx { 
+            auction_info, 
+            treasury_info, 
+            config_info, 
+            mint_info, 
+            config_account
+        } = accounts.try_into()?;
+
+        let mut auction_account: IDOAuctionAccount = IDOAuctionAccount::unpack(*auction_info.data.borrow())?;
+
+        let expected_treasury_pda: Pubkey = create_program_pda(&[
+            IDO_TREASURY_ACCOUNT_SEED,
+            mint_info.key.as_ref(),
+            &[config_account.treasury_bump]
+        ])?;
+
+        let treasury_pkey_bytes: &[u8] = expected_treasury_pda.as_ref();
+
+        let expected_auction_pda: Pubkey = create_program_pda(&[
+            IDO_AUCTION_ACCOUNT_SEED,
+            treasury_pkey_bytes,
+            &[auction_account.bump]
+        ])?;
+
+        let expected_config_pda: Pubkey = create_program_pda(&[
+            IDO_CONFIG_ACCOUNT_SEED,
+            treasury_pkey_bytes,
+            &[config_account.bump]
+        ])?;
+
+        if expected_treasury_pda != *treasury_info.key {
+            return Err(IDOProgramError::InvalidTreasuryAccount.into());
+        }
+
+        if expected_auction_pda != *auction_info.key {
+            return Err(IDOProgramError::InvalidAuctionAccount.into());
+        }
+
+        if expected_config_pda != *config_info.key {
+            return Err(IDOProgramError::InvalidConfigAccount.into());
+        }
+
+        // 2. Define the clearing price once the bidding window is closed.
+        let clock: Clock = Clock::get()?;
+
+        if auction_account.is_bidding_active(&clock) {
+            return Err(IDOProgramError::BiddingIsActive.into());
+        }
+
+        auction_account.settle()?;
+        auction_account.pack_into_slice(*auction_info.data.borrow_mut());
+
+        // 3. Keep the reserve only for the supply the bids fill, the rest of the deposit is unsold (see `Withdraw`).
+        let reserved_amount: u64 = {
+            let mint_data_ref: Ref<&mut [u8]> = mint_info.data.borrow();
+            let mint: StateWithExtensions<Mint> = StateWithExtensions::<Mint>::unpack(*mint_data_ref)?;
+
+            Self::reserve_auction_supply(
+                &mint, 
+                auction_account.filled_supply(), 
+                config_account.unlocks, 
+                config_account.transfer_fee_payer()?
+            )?
+        };
+
+        {
+            let mut config_data_ref: RefMut<&mut [u8]> = config_info.data.borrow_mut();
+            let config_account: &mut IDOConfigAccount = IDOConfigAccount::load_mut(&mut config_data_ref)?;
+
+            config_account.reserved_amount = config_account.reserved_amount.min(reserved_amount);
+        }
+
+        IDOEvent::AuctionSettled {
+            mint: *mint_info.key,
+            clearing_price: auction_account.clearing_price,
+            clearing_level_fill: to_base_units(auction_account.clearing_level_fill, mint_decimals(mint_info)?)?
+        }.emit();
+
+        Ok(())
+    }
+
+    fn process_settle_bid_instruction(
+        program_id: &Pubkey, 
+        accounts: &[AccountInfo]
+    ) -> ProgramResult {
+        // 1. Check deterministic derivation
+        let IDOSettleBidCtx { 
+            signer_info, 
+            bidder_info, 
+            bid_info, 
+            vesting_info, 
+            auction_info, 
+            treasury_info, 
+            config_info, 
+            mint_info, 
+            config_account,
+            ..
+        } = accounts.try_into()?;
+
+        // every account except the Vesting PDA (which is created on the first fill) is validated with the stored bump
+        let auction_account: IDOAuctionAccount = IDOAuctionAccount::unpack(*auction_info.data.borrow())?;
+        let bid_account: IDOBidAccount = IDOBidAccount::unpack(*bid_info.data.borrow())?;
+
+        let (bidder_pkey_bytes, mint_pkey_bytes) = (
+            bidder_info.key.as_ref(),
+            mint_info.key.as_ref()
+        );
+
+        let expected_bid_pda: Pubkey = create_program_pda(&[
+            IDO_BID_ACCOUNT_SEED,
+            bidder_pkey_bytes,
+            mint_pkey_bytes,
+            &[bid_account.bump]
+        ])?;
+
+        let expected_treasury_pda: Pubkey = create_program_pda(&[
+            IDO_TREASURY_ACCOUNT_SEED,
+            mint_pkey_bytes,
+            &[config_account.treasury_bump]
+        ])?;
+
+        let treasury_pkey_bytes: &[u8] = expected_treasury_pda.as_ref();
+
+        let expected_auction_pda: Pubkey = create_program_pda(&[
+            IDO_AUCTION_ACCOUNT_SEED,
+            treasury_pkey_bytes,
+            &[auction_account.bump]
+        ])?;
+
+        let expected_config_pda: Pubkey = create_program_pda(&[
+            IDO_CONFIG_ACCOUNT_SEED,
+            treasury_pkey_bytes,
+            &[config_account.bump]
+        ])?;
+
+        if expected_bid_pda != *bid_info.key {
+            return Err(IDOProgramError::InvalidBidAccount.into());
+        }
+
+        let vesting_bump: u8 = Self::check_vesting_pda(vesting_info, bidder_info.key, mint_info.key)?;
+
+        if expected_treasury_pda != *treasury_info.key {
+            return Err(IDOProgramError::InvalidTreasuryAccount.into());
+        }
+
+        if expected_auction_pda != *auction_info.key {
+            return Err(IDOProgramError::InvalidAuctionAccount.into());
+        }
+
+        if expected_config_pda != *config_info.key {
+            return Err(IDOProgramError::InvalidConfigAccount.into());
+        }
+
+        // 2. Define the winning part of the bid & its cost at the clearing price.
+        let filled_amount: u64 = auction_account.define_bid_fill(&bid_account)?;
+        let filled_cost: u64 = filled_amount
+            .checked_mul(auction_account.clearing_price as u64)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+
+        // 3. Turn the winning part into the vesting position & account it as sold, `sold_amount` is in whole tokens.
+        if filled_amount > 0 {
+            {
+                let mut config_data_ref: RefMut<&mut [u8]> = config_info.data.borrow_mut();
+                let sold_amount: &mut u64 = &mut IDOConfigAccount::load_mut(&mut config_data_ref)?.sold_amount;
+
+                *sold_amount = sold_amount
+                    .checked_add(filled_amount)
+                    .ok_or(ProgramError::ArithmeticOverflow)?;
+            }
+
+            Self::create_or_top_up_vesting_account(
+                program_id, 
+                signer_info, 
+                vesting_info, 
+                bidder_info.key, 
+                mint_info.key, 
+                vesting_bump, 
+                filled_amount, 
+                config_account.unlocks
+            )?;
+        }
+
+        // 4. Close Bid PDA: pay `filled_cost` to Config PDA (like `BuyWithVesting`) & refund the rest of the escrow (including rent) to the bidder.
+        let refund_amount: u64 = bid_info.lamports()
+            .checked_sub(filled_cost)
+            .ok_or(ProgramError::InsufficientFunds)?;
+
+        **config_info.try_borrow_mut_lamports()? = config_info.lamports()
+            .checked_add(filled_cost)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        **bidder_info.try_borrow_mut_lamports()? = bidder_info.lamports()
+            .checked_add(refund_amount)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        **bid_info.try_borrow_mut_lamports()? = 0;
+        bid_info.data.borrow_mut().fill(0);
+
+        IDOEvent::BidSettled {
+            mint: *mint_info.key,
+            bidder: *bidder_info.key,
+            filled_amount: to_base_units(filled_amount, mint_decimals(mint_info)?)?,
+            filled_cost,
+            refund_amount
+        }.emit();
+
+        Ok(())
+    }
+
+    fn process_migrate_instruction(
+        accounts: &[AccountInfo]
+    ) -> ProgramResult {
+        let IDOMigrateCtx { 
+            signer_info, 
+            account_info, 
+            mint_info,
+            owner_info,
+            .. 
+        } = accounts.try_into()?;
+
+        // 1. v1 layouts have no header, so the account type is defined by the length (v1 & the current lengths never collide).
+        let account_type: AccountType = {
+            let data_ref: Ref<&mut [u8]> = account_info.data.borrow();
+
+            AccountType::from_v1_len(data_ref.len()).ok_or_else(|| {
+                if data_ref.len() >= ACCOUNT_HEADER_LEN && data_ref[1] == ACCOUNT_VERSION {
+                    IDOProgramError::AccountAlreadyMigrated
+                } else {
+                    IDOProgramError::InvalidAccountType
+                }
+            })?
+        };
+
+        // v1 Config has neither Mint nor Treasury address, so they are derived from the provided mint.
+        let (expected_treasury_pda, treasury_bump) = derive_program_pda(&[
+            IDO_TREASURY_ACCOUNT_SEED,
+            mint_info.key.as_ref()
+        ]);
+
+        if account_type == AccountType::Config {
+            let (expected_config_pda, _config_bump) = derive_program_pda(&[
+                IDO_CONFIG_ACCOUNT_SEED,
+                expected_treasury_pda.as_ref()
+            ]);
+
+            if expected_config_pda != *account_info.key {
+                return Err(IDOProgramError::InvalidConfigAccount.into());
+            }
+        }
+
+        let old_data: Vec<u8> = account_info.data.borrow().to_vec();
+
+        // v1 Config tracked neither the deposited nor the sold supply, which can't be rebuilt without every Vesting of the sale,
+        // so the Config is migrated only once nothing can be bought anymore.
+        if account_type == AccountType::Config {
+            let vesting_end_ts: i64 = IDOConfigAccount::from_v1(&old_data)?.vesting_strategy.vesting_end_ts;
+
+            if Clock::get()?.unix_timestamp < vesting_end_ts {
+                return Err(IDOProgramError::SaleIsActive.into());
+            }
+        }
+
+        // v1 Vesting has no Owner, so the provided one must derive the Vesting PDA with the stored bump.
+        let vesting_owner_pkey: Option<Pubkey> = if account_type == AccountType::Vesting {
+            let owner_pkey: Pubkey = *owner_info.ok_or(ProgramError::NotEnoughAccountKeys)?.key;
+            let expected_vesting_pda: Pubkey = create_program_pda(&[
+                IDO_VESTING_ACCOUNT_SEED,
+                owner_pkey.as_ref(),
+                mint_info.key.as_ref(),
+                &[old_data[32]]
+            ])?;
+
+            if expected_vesting_pda != *account_info.key {
+                return Err(IDOProgramError::InvalidVestingAccount.into());
+            }
+
+            Some(owner_pkey)
+        } else {
+            None
+        };
+
+        // 2. Top up the rent for the current layout & reallocate the account.
+        let account_len: usize = account_type.account_len();
+        let rent_top_up: u64 = Rent::get()?
+            .minimum_balance(account_len)
+            .saturating_sub(account_info.lamports());
+
+        if rent_top_up > 0 {
+            let transfer_ix: Instruction = system_instruction::transfer(
+                signer_info.key, 
+                account_info.key, 
+                rent_top_up
+            );
+            invoke(
+                &transfer_ix,
+                &[
+                    signer_info.clone(),
+                    account_info.clone()
+                ]
+            )?;
+        }
+
+        account_info.resize(account_len)?;
+
+        // 3. Config & Vesting fields are realigned into the zero-copy layouts.
+        let mut data_ref: RefMut<&mut [u8]> = account_info.data.borrow_mut();
+
+        match vesting_owner_pkey {
+            Some(owner_pkey) => {
+                let vesting_account: IDOVestingAccount = IDOVestingAccount::from_v1(&old_data, owner_pkey, *mint_info.key)?;
+
+                data_ref.copy_from_slice(bytemuck::bytes_of(&vesting_account));
+            },
+            None => {
+                let mut config_account: IDOConfigAccount = IDOConfigAccount::from_v1(&old_data)?;
+                config_account.mint = *mint_info.key;
+                config_account.treasury = expected_treasury_pda;
+                config_account.treasury_bump = treasury_bump;
+
+                data_ref.copy_from_slice(bytemuck::bytes_of(&config_account));
+            }
+        }
+
+        IDOEvent::Migrated {
+            account: *account_info.key,
+            account_type,
+            from_version: 1,
+            to_version: ACCOUNT_VERSION
+        }.emit();
+
+        Ok(())
+    }
+
+    fn process_withdraw_instruction(
+        accounts: &[AccountInfo]
+    ) -> ProgramResult {
+        // 1. Check deterministic derivation & the authority (programs, the authority's ATA & the config's mint are validated by the context)
+        let IDOWithdrawCtx { 
+            authority_info, 
+            authority_ata_info, 
+            treasury_info, 
+            config_info, 
+            mint_info, 
+            token_program_info, 
+            remaining_accounts, 
+            config_account 
+        } = accounts.try_into()?;
+
+        let mint_pkey_bytes: &[u8] = mint_info.key.as_ref();
+        let treasury_bump: u8 = config_account.treasury_bump;
+
+        let expected_treasury_pda: Pubkey = create_program_pda(&[
+            IDO_TREASURY_ACCOUNT_SEED,
+            mint_pkey_bytes,
+            &[treasury_bump]
+        ])?;
+
+        let expected_config_pda: Pubkey = create_program_pda(&[
+            IDO_CONFIG_ACCOUNT_SEED,
+            treasury_info.key.as_ref(),
+            &[config_account.bump]
+        ])?;
+
+        if expected_treasury_pda != *treasury_info.key {
+            return Err(IDOProgramError::InvalidTreasuryAccount.into());
+        }
+
+        if expected_config_pda != *config_info.key {
+            return Err(IDOProgramError::InvalidConfigAccount.into());
+        }
+
+        // migrated v1 configs have the default authority, which can't sign
+        if config_account.authority == Pubkey::default() || config_account.authority != *authority_info.key {
+            return Err(IDOProgramError::InvalidWithdrawAuthority.into());
+        }
+
+        // 2. Define the unsold supply: sales are closed at the vesting end, the reserved tokens (i.e. the sold ones along with
+        // the fees the treasury pays on claim) stay in the treasury. The auction reserve covers only the filled supply once it's settled.
+        let clock: Clock = Clock::get()?;
+        let treasury_seeds: &[&[u8]] = &[IDO_TREASURY_ACCOUNT_SEED, mint_pkey_bytes, &[treasury_bump]];
+
+        let (withdraw_amount, mint_decimals) = {
+            let mint_data_ref: Ref<&mut [u8]> = mint_info.data.borrow();
+            let mint: StateWithExtensions<Mint> = StateWithExtensions::<Mint>::unpack(*mint_data_ref)?;
+
+            let is_sale_over: bool = config_account.vesting_strategy.vesting_end_ts <= clock.unix_timestamp;
+
+            let unsold_amount: u64 = if is_sale_over {
+                config_account.deposited_amount.saturating_sub(config_account.reserved_amount)
+            } else {
+                0
+            };
+
+            (TransferAmount::new(&mint, clock.epoch, unsold_amount, TransferFeePayer::Recipient)?, mint.base.decimals)
+        };
+
+        // 3. Define the raised LAMPORTS, i.e. everything above the rent of Config PDA, which receives the proceeds.
+        let withdraw_lamports: u64 = config_info.lamports().saturating_sub(Rent::get()?.minimum_balance(config_info.data_len()));
+
+        if withdraw_amount.amount == 0 && withdraw_lamports == 0 {
+            return Err(IDOProgramError::NothingToWithdraw.into());
+        }
+
+        // 4. Transfer the unsold supply to the authority's ATA, transfer hook extra accounts (if any) are forwarded.
+        if withdraw_amount.amount > 0 {
+            {
+                let mut config_data_ref: RefMut<&mut [u8]> = config_info.data.borrow_mut();
+                let config_account: &mut IDOConfigAccount = IDOConfigAccount::load_mut(&mut config_data_ref)?;
+
+                config_account.deposited_amount = config_account.deposited_amount
+                    .checked_sub(withdraw_amount.amount)
+                    .ok_or(ProgramError::ArithmeticOverflow)?;
+            }
+
+            withdraw_amount.invoke_transfer_checked(
+                token_program_info.key, 
+                treasury_info, 
+                mint_info, 
+                authority_ata_info, 
+                treasury_info, 
+                remaining_accounts, 
+                mint_decimals, 
+                &[treasury_seeds]
+            )?;
+        }
+
+        // 5. Release the raised LAMPORTS to the authority, Config PDA is owned by the program, so its LAMPORTS are moved directly.
+        if withdraw_lamports > 0 {
+            **config_info.try_borrow_mut_lamports()? -= withdraw_lamports;
+            **authority_info.try_borrow_mut_lamports()? = authority_info.lamports()
+                .checked_add(withdraw_lamports)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+        }
+
+        IDOEvent::Withdrawn {
+            mint: *mint_info.key,
+            authority: *authority_info.key,
+            lamports: withdraw_lamports,
+            amount: withdraw_amount.amount
+        }.emit();
+
         Ok(())
     }
 }
\ No newline at end of file
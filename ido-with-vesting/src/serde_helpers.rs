@@ -0,0 +1,39 @@
+//! `serde(with = ...)` adapters for the fields, whose default encoding isn't practical for the backends.
+
+/// Pubkey as base58 string instead of the byte array.
+pub(crate) mod pubkey {
+    use std::str::FromStr;
+    use serde::{Deserialize, Deserializer, Serializer};
+    use solana_program::pubkey::Pubkey;
+
+    pub fn serialize<S: Serializer>(pubkey: &Pubkey, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(pubkey)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Pubkey, D::Error> {
+        let pubkey: String = String::deserialize(deserializer)?;
+        Pubkey::from_str(&pubkey).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Arrays longer than 32 items, which serde doesn't support out of the box.
+pub(crate) mod array {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer, T: Serialize, const N: usize>(array: &[T; N], serializer: S) -> Result<S::Ok, S::Error> {
+        array.as_slice().serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D, T, const N: usize>(deserializer: D) -> Result<[T; N], D::Error>
+    where
+        D: Deserializer<'de>,
+        T: Deserialize<'de>
+    {
+        let items: Vec<T> = Vec::deserialize(deserializer)?;
+        let len: usize = items.len();
+
+        items
+            .try_into()
+            .map_err(|_| serde::de::Error::invalid_length(len, &format!("an array of {} items", N).as_str()))
+    }
+}
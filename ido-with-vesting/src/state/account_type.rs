@@ -0,0 +1,119 @@
+use bytemuck::Pod;
+use solana_program::{
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    program_pack::Pack
+};
+use crate::error::IDOProgramError;
+use super::{
+    IDOConfigAccount,
+    IDOVestingAccount,
+    IDOAuctionAccount,
+    IDOBidAccount
+};
+
+
+/// Current version of every account layout, v1 layouts had no header at all.
+pub const ACCOUNT_VERSION: u8 = 2;
+
+/// Every account owned by the program is prefixed with `[account_type, version]`.
+pub const ACCOUNT_HEADER_LEN: usize = 2;
+
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AccountType {
+    Config = 1,
+    Vesting = 2,
+    Auction = 3,
+    Bid = 4
+}
+
+impl TryFrom<u8> for AccountType {
+    type Error = ProgramError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        Ok(match value {
+            1 => Self::Config,
+            2 => Self::Vesting,
+            3 => Self::Auction,
+            4 => Self::Bid,
+            _ => return Err(IDOProgramError::InvalidAccountType.into())
+        })
+    }
+}
+
+impl AccountType {
+    pub fn pack_header(self, dst: &mut [u8]) {
+        dst[0] = self as u8;
+        dst[1] = ACCOUNT_VERSION;
+    }
+
+    /// Rejects accounts of another type & accounts that must be migrated first.
+    pub fn check_header(self, src: &[u8]) -> ProgramResult {
+        let [account_type, version] = *src
+            .first_chunk::<ACCOUNT_HEADER_LEN>()
+            .ok_or(ProgramError::InvalidAccountData)?;
+
+        if account_type != self as u8 {
+            return Err(IDOProgramError::InvalidAccountType.into());
+        }
+
+        if version != ACCOUNT_VERSION {
+            return Err(IDOProgramError::UnsupportedAccountVersion.into());
+        }
+
+        Ok(())
+    }
+
+    /// Zero-copy view of the current layout, `data` must be aligned (which is always true for the account data on-chain).
+    pub fn load<T: Pod>(self, data: &[u8]) -> Result<&T, ProgramError> {
+        self.check_layout::<T>(data)?;
+
+        bytemuck::try_from_bytes(data).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    pub fn load_mut<T: Pod>(self, data: &mut [u8]) -> Result<&mut T, ProgramError> {
+        self.check_layout::<T>(data)?;
+
+        bytemuck::try_from_bytes_mut(data).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    /// Copies the current layout out of `data` regardless of its alignment, e.g. the account data fetched by clients.
+    pub fn read<T: Pod>(self, data: &[u8]) -> Result<T, ProgramError> {
+        self.check_layout::<T>(data)?;
+
+        bytemuck::try_pod_read_unaligned(data).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    /// The header is checked first, so the outdated layouts of another length are reported as such.
+    fn check_layout<T: Pod>(self, data: &[u8]) -> ProgramResult {
+        self.check_header(data)?;
+
+        if data.len() != std::mem::size_of::<T>() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(())
+    }
+
+    /// Length of the current layout.
+    pub fn account_len(self) -> usize {
+        match self {
+            Self::Config => IDOConfigAccount::LEN,
+            Self::Vesting => IDOVestingAccount::LEN,
+            Self::Auction => IDOAuctionAccount::LEN,
+            Self::Bid => IDOBidAccount::LEN
+        }
+    }
+
+    /// v1 layouts had neither type nor version, so the type can be defined only by the account length.
+    ///
+    /// Only the fixed price sale was released in v1, so there are neither Auction nor Bid v1 accounts.
+    pub fn from_v1_len(len: usize) -> Option<Self> {
+        Some(match len {
+            31 => Self::Config,
+            34 => Self::Vesting,
+            _ => return None
+        })
+    }
+}
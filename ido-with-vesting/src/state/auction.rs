@@ -0,0 +1,139 @@
+use solana_program::{
+    program_error::ProgramError,
+    program_pack::{
+        Pack,
+        Sealed,
+        IsInitialized
+    }
+};
+use crate::{
+    constants::AUCTION_PRICE_LEVELS,
+    utils::{
+        Reader, 
+        ReadBytes
+    }
+};
+use super::{AccountType, ACCOUNT_HEADER_LEN};
+#[cfg(feature = "borsh")]
+use borsh::{BorshSerialize, BorshDeserialize};
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+
+
+/// Uniform-price batch auction state.
+/// 
+/// Bids are aggregated into the `demand` book, where each entry is a price level:
+/// `price = min_price + level * tick_size`.
+///
+/// Account data is the `[account_type, version]` header followed by the Borsh encoding of this struct.
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "borsh", derive(BorshSerialize, BorshDeserialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct IDOAuctionAccount {
+    pub bidding_end_ts: i64,
+    /// Amount of tokens (without decimals) that are sold via the auction.
+    pub supply: u64,
+    
+    pub min_price: u32,
+
+    pub tick_size: u32,
+    /// Defined by the settlement; every winning bid pays this LAMPORTS/TOKEN ratio.
+    pub clearing_price: u32,
+    
+    pub clearing_level: u8,
+
+    pub bump: u8,
+
+    pub is_settled: bool,
+
+    pub is_initialized: bool,
+    /// Amount of tokens that's distributed pro-rata between the bids placed exactly at the `clearing_level`.
+    pub clearing_level_fill: u64,
+    /// Total bid quantity per price level.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_helpers::array"))]
+    pub demand: [u64; AUCTION_PRICE_LEVELS]
+}
+
+impl IDOAuctionAccount {
+    pub fn new(
+        bidding_end_ts: i64, 
+        supply: u64, 
+        min_price: u32, 
+        tick_size: u32, 
+        bump: u8
+    ) -> Self {
+        Self {
+            bidding_end_ts,
+            supply,
+            min_price,
+            tick_size,
+            clearing_price: 0,
+            clearing_level: 0,
+            bump,
+            is_settled: false,
+            is_initialized: true,
+            clearing_level_fill: 0,
+            demand: [0; AUCTION_PRICE_LEVELS]
+        }
+    }
+}
+
+impl Sealed for IDOAuctionAccount {}
+
+impl IsInitialized for IDOAuctionAccount {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for IDOAuctionAccount {
+    const LEN: usize = ACCOUNT_HEADER_LEN + 40 + AUCTION_PRICE_LEVELS * 8;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        AccountType::Auction.pack_header(dst);
+        let dst: &mut [u8] = &mut dst[ACCOUNT_HEADER_LEN..];
+
+        dst[..8].copy_from_slice(&self.bidding_end_ts.to_le_bytes());
+        dst[8..16].copy_from_slice(&self.supply.to_le_bytes());
+        dst[16..20].copy_from_slice(&self.min_price.to_le_bytes());
+        dst[20..24].copy_from_slice(&self.tick_size.to_le_bytes());
+        dst[24..28].copy_from_slice(&self.clearing_price.to_le_bytes());
+        dst[28] = self.clearing_level;
+        dst[29] = self.bump;
+        dst[30] = self.is_settled as u8;
+        dst[31] = self.is_initialized as u8;
+        dst[32..40].copy_from_slice(&self.clearing_level_fill.to_le_bytes());
+
+        for (level, demand) in self.demand.iter().enumerate() {
+            let start: usize = 40 + level * 8;
+            dst[start..start + 8].copy_from_slice(&demand.to_le_bytes());
+        }
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        AccountType::Auction.check_header(src)?;
+        let src: &[u8] = src.get(ACCOUNT_HEADER_LEN..).ok_or(ProgramError::InvalidAccountData)?;
+
+        let reader: Reader = src.into();
+
+        let mut demand: [u64; AUCTION_PRICE_LEVELS] = [0; AUCTION_PRICE_LEVELS];
+        for (level, demand) in demand.iter_mut().enumerate() {
+            *demand = reader.read_u64(40 + level * 8)?;
+        }
+
+        Ok(Self {
+            bidding_end_ts: reader.read_i64(0)?,
+            supply: reader.read_u64(8)?,
+            min_price: reader.read_u32(16)?,
+            tick_size: reader.read_u32(20)?,
+            clearing_price: reader.read_u32(24)?,
+            clearing_level: reader.read_u8(28)?,
+            bump: reader.read_u8(29)?,
+            is_settled: reader.read_u8(30)? != 0,
+            is_initialized: reader.read_u8(31)? != 0,
+            clearing_level_fill: reader.read_u64(32)?,
+            demand
+        })
+    }
+}
\ No newline at end of file
@@ -0,0 +1,72 @@
+use solana_program::{
+    program_error::ProgramError,
+    program_pack::{
+        Pack,
+        Sealed,
+        IsInitialized
+    }
+};
+use crate::utils::{
+    Reader, 
+    ReadBytes
+};
+use super::{AccountType, ACCOUNT_HEADER_LEN};
+#[cfg(feature = "borsh")]
+use borsh::{BorshSerialize, BorshDeserialize};
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+
+
+/// Per-bidder PDA, which also escrows `quantity * max_price` lamports until the bid is settled.
+///
+/// Account data is the `[account_type, version]` header followed by the Borsh encoding of this struct.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "borsh", derive(BorshSerialize, BorshDeserialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct IDOBidAccount {
+    /// Amount of tokens (without decimals) the bidder is willing to buy.
+    pub quantity: u64,
+    /// Max LAMPORTS/TOKEN ratio the bidder is willing to pay.
+    pub max_price: u32,
+
+    pub bump: u8,
+
+    pub is_initialized: bool
+}
+
+impl Sealed for IDOBidAccount {}
+
+impl IsInitialized for IDOBidAccount {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for IDOBidAccount {
+    const LEN: usize = 16;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        AccountType::Bid.pack_header(dst);
+        let dst: &mut [u8] = &mut dst[ACCOUNT_HEADER_LEN..];
+
+        dst[..8].copy_from_slice(&self.quantity.to_le_bytes());
+        dst[8..12].copy_from_slice(&self.max_price.to_le_bytes());
+        dst[12] = self.bump;
+        dst[13] = self.is_initialized as u8;
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        AccountType::Bid.check_header(src)?;
+        let src: &[u8] = src.get(ACCOUNT_HEADER_LEN..).ok_or(ProgramError::InvalidAccountData)?;
+
+        let reader: Reader = src.into();
+
+        Ok(Self {
+            quantity: reader.read_u64(0)?,
+            max_price: reader.read_u32(8)?,
+            bump: reader.read_u8(12)?,
+            is_initialized: reader.read_u8(13)? != 0
+        })
+    }
+}
\ No newline at end of file
@@ -1,65 +1,179 @@
+use bytemuck::{Pod, Zeroable};
 use solana_program::{
+    pubkey::Pubkey,
     program_error::ProgramError,
-    program_pack::{
-        Pack,
-        Sealed,
-        IsInitialized
-    }
+    program_pack::IsInitialized
 };
 use crate::{
     vesting::LinearVestingStrategy,
     utils::{
-        read_u32_slice,
-        read_linear_vesting_strategy_slice
+        Reader,
+        ReadBytes
     }
 };
+use super::{AccountType, ACCOUNT_VERSION};
+#[cfg(feature = "borsh")]
+use borsh::{BorshSerialize, BorshDeserialize};
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+
+
+/// Defines who absorbs the fee on claim, if the mint has Token-2022 `TransferFeeConfig` extension.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "borsh", derive(BorshSerialize, BorshDeserialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TransferFeePayer {
+    /// Claimed portion is transferred as is, so the recipient receives the portion minus the fee (net delivery).
+    Recipient,
+    /// Claimed portion is grossed up by the fee, so the recipient receives exactly the claimed portion.
+    /// 
+    /// The fee is paid from the treasury, so the supply must be deposited with a reserve for fees.
+    Treasury
+}
+
+impl TryFrom<u8> for TransferFeePayer {
+    type Error = ProgramError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Recipient),
+            1 => Ok(Self::Treasury),
+            _ => Err(ProgramError::InvalidArgument)
+        }
+    }
+}
 
 
+/// Zero-copy layout, every field is aligned explicitly, so the account data is read & modified in place.
 #[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+#[cfg_attr(feature = "borsh", derive(BorshSerialize, BorshDeserialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct IDOConfigAccount {
-    pub vesting_strategy: LinearVestingStrategy,
+    pub account_type: u8,
+
+    pub version: u8,
+
+    pub bump: u8,
+
+    pub unlocks: u8,  // u8 is fine, because MAX_UNLOCKS: u8
+
+    pub is_initialized: u8,
+    /// `TransferFeePayer` as u8, use `Self::transfer_fee_payer()` to read it.
+    pub transfer_fee_payer: u8,
+    /// Stored, so Treasury PDA is validated & signed for without `find_program_address`.
+    pub treasury_bump: u8,
+
+    #[cfg_attr(feature = "serde", serde(skip))]
+    _padding0: [u8; 1],
     /// This field is basically a LAMPORTS/TOKEN ratio.
     ///
     /// Example: 1000 LAMPORTS == 1 SPL TOKEN.
     pub lamports_per_token: u32,
-    
-    pub bump: u8,
 
-    pub unlocks: u8,  // u8 is fine, because MAX_UNLOCKS: u8
+    #[cfg_attr(feature = "serde", serde(skip))]
+    _padding1: [u8; 4],
 
-    pub is_initialized: bool
-}
+    pub vesting_strategy: LinearVestingStrategy,
+    /// Amount of tokens (with decimals) the treasury actually received on initialization, i.e. without transfer fee.
+    pub deposited_amount: u64,
+    /// Amount of tokens (without decimals) sold via `BuyWithVesting`, the buy that would exceed `deposited_amount` is rejected.
+    pub sold_amount: u64,
+    /// Amount of tokens (with decimals) reserved for the sold supply, including the transfer fees the treasury pays on claim
+    /// (see `token::reserve_amount`), the rest of `deposited_amount` is unsold.
+    pub reserved_amount: u64,
+    /// Mint & Treasury the config was initialized for, so the accounts provided along with the config can be cross-checked.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_helpers::pubkey"))]
+    pub mint: Pubkey,
 
-impl Sealed for IDOConfigAccount {}
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_helpers::pubkey"))]
+    pub treasury: Pubkey,
+    /// Signer of the initialization, the only one who can `Withdraw`. Default (i.e. nobody) for the migrated v1 configs.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_helpers::pubkey"))]
+    pub authority: Pubkey
+}
 
 impl IsInitialized for IDOConfigAccount {
     fn is_initialized(&self) -> bool {
-        self.is_initialized
+        self.is_initialized != 0
     }
 }
 
-impl Pack for IDOConfigAccount {
-    const LEN: usize = 31;
+impl IDOConfigAccount {
+    pub const LEN: usize = std::mem::size_of::<Self>();
+
+    /// Writes the header & marks the zeroed account data as initialized, the caller sets the rest of the fields in place.
+    pub fn init(data: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        let config_account: &mut Self = bytemuck::try_from_bytes_mut(data).map_err(|_| ProgramError::InvalidAccountData)?;
+
+        if config_account.is_initialized() {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+
+        *config_account = Self {
+            account_type: AccountType::Config as u8,
+            version: ACCOUNT_VERSION,
+            is_initialized: true as u8,
+            ..Self::zeroed()
+        };
+
+        Ok(config_account)
+    }
+
+    pub fn load(data: &[u8]) -> Result<&Self, ProgramError> {
+        let config_account: &Self = AccountType::Config.load(data)?;
+        config_account.check_initialized()?;
+
+        Ok(config_account)
+    }
+
+    pub fn load_mut(data: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        let config_account: &mut Self = AccountType::Config.load_mut(data)?;
+        config_account.check_initialized()?;
 
-    fn pack_into_slice(&self, dst: &mut [u8]) {
-        dst[..24].copy_from_slice(self.vesting_strategy.as_ref());
-        dst[24..28].copy_from_slice(&self.lamports_per_token.to_le_bytes());
-        dst[28] = self.bump;
-        dst[29] = self.unlocks;
-        dst[30] = self.is_initialized as u8;
+        Ok(config_account)
     }
 
-    /// Calling .unwrap() is safe, because LEN is validated in .unpack() or .unpack_unchecked() methods.
-    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
-        let vesting_strategy: LinearVestingStrategy = read_linear_vesting_strategy_slice(src, 0)?;
-        let lamports_per_token: u32 = read_u32_slice(src, 24)?;
+    /// Copies the account out of `data` regardless of its alignment, e.g. the account data fetched by clients.
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        let config_account: Self = AccountType::Config.read(data)?;
+        config_account.check_initialized()?;
+
+        Ok(config_account)
+    }
+
+    pub fn transfer_fee_payer(&self) -> Result<TransferFeePayer, ProgramError> {
+        self.transfer_fee_payer.try_into()
+    }
+
+    /// v1 layout (31 bytes) had no header & was packed without alignment, Mint, Treasury & its bump must be set by the caller.
+    /// 
+    /// v1 tracked neither the deposited nor the sold supply, so they are zeroed (i.e. no supply is left for sale),
+    /// `transfer_fee_payer` didn't exist either (i.e. `TransferFeePayer::Recipient`) & the authority is left unset.
+    pub fn from_v1(src: &[u8]) -> Result<Self, ProgramError> {
+        let reader: Reader = src.into();
 
         Ok(Self {
-            vesting_strategy,
-            lamports_per_token,
-            bump: src[28],
-            unlocks: src[29],
-            is_initialized: src[30] != 0
+            account_type: AccountType::Config as u8,
+            version: ACCOUNT_VERSION,
+            vesting_strategy: reader.read_linear_vesting_strategy(0)?,
+            lamports_per_token: reader.read_u32(24)?,
+            bump: reader.read_u8(28)?,
+            unlocks: reader.read_u8(29)?,
+            is_initialized: reader.read_u8(30)?,
+            ..Self::zeroed()
         })
     }
-}
\ No newline at end of file
+
+    fn check_initialized(&self) -> Result<(), ProgramError> {
+        if !self.is_initialized() {
+            return Err(ProgramError::UninitializedAccount);
+        }
+
+        // invalid `TransferFeePayer` is rejected on load, so it's never observed by the processor
+        self.transfer_fee_payer()?;
+
+        Ok(())
+    }
+}
@@ -1,5 +1,11 @@
 mod config;
 mod vesting;
+mod auction;
+mod bid;
+mod account_type;
 
-pub use config::IDOConfigAccount;
-pub use vesting::IDOVestingAccount;
\ No newline at end of file
+pub use config::{IDOConfigAccount, TransferFeePayer};
+pub use vesting::IDOVestingAccount;
+pub use auction::IDOAuctionAccount;
+pub use bid::IDOBidAccount;
+pub use account_type::{AccountType, ACCOUNT_VERSION, ACCOUNT_HEADER_LEN};
\ No newline at end of file
@@ -1,20 +1,40 @@
+use bytemuck::{Pod, Zeroable};
 use solana_program::{
+    pubkey::Pubkey,
     program_error::ProgramError,
-    program_pack::{
-        Pack,
-        Sealed,
-        IsInitialized
-    }
+    program_pack::IsInitialized
 };
-use crate::utils::{
-    Reader, 
-    ReadBytes
+use crate::{
+    vesting::allow_claim_and_define_portion_at,
+    utils::{
+        Reader, 
+        ReadBytes
+    }
 };
+use super::{AccountType, IDOConfigAccount, ACCOUNT_VERSION};
+#[cfg(feature = "borsh")]
+use borsh::{BorshSerialize, BorshDeserialize};
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
 
 
+/// Zero-copy layout, every field is aligned explicitly, so the account data is read & modified in place.
 #[repr(C)]
-#[derive(Default)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+#[cfg_attr(feature = "borsh", derive(BorshSerialize, BorshDeserialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct IDOVestingAccount {
+    pub account_type: u8,
+
+    pub version: u8,
+
+    pub bump: u8,
+
+    pub is_initialized: u8,
+
+    #[cfg_attr(feature = "serde", serde(skip))]
+    _padding: [u8; 4],
+
     pub last_claim_ts: i64,
 
     pub claimed_amount: u64, 
@@ -22,55 +42,91 @@ pub struct IDOVestingAccount {
     pub bought_amount: u64,
     /// this field must be advanced based on the updated bought tokens amount
     pub amount_per_unlock: u64,
-    
-    pub bump: u8,
+    /// Owner & Mint the vesting was created for, stored at fixed offsets, so `getProgramAccounts` can filter vestings by them.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_helpers::pubkey"))]
+    pub owner: Pubkey,
+
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_helpers::pubkey"))]
+    pub mint: Pubkey,
+    /// Reserved for future fields, it also keeps the length distinct from v1 layouts, which are recognized only by the length.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    _reserved: [u8; 8]
+}
 
-    pub is_initialized: bool    
+impl IsInitialized for IDOVestingAccount {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized != 0
+    }
 }
 
 impl IDOVestingAccount {
-    /// ### Use this builder method instead of Self::default()
-    pub fn new(bought_amount: u64, amount_per_unlock: u64, bump: u8) -> Self {
+    pub const LEN: usize = std::mem::size_of::<Self>();
+    pub const OWNER_OFFSET: usize = std::mem::offset_of!(Self, owner);
+    pub const MINT_OFFSET: usize = std::mem::offset_of!(Self, mint);
+
+    /// ### Use this builder method instead of Self::zeroed()
+    pub fn new(owner: Pubkey, mint: Pubkey, bought_amount: u64, amount_per_unlock: u64, bump: u8) -> Self {
         Self { 
+            account_type: AccountType::Vesting as u8,
+            version: ACCOUNT_VERSION,
+            owner,
+            mint,
             bought_amount, 
             amount_per_unlock, 
             bump, 
-            is_initialized: true,
-            ..Self::default() 
+            is_initialized: true as u8,
+            ..Self::zeroed() 
         }
     } 
-}
 
-impl Sealed for IDOVestingAccount {}
+    pub fn load(data: &[u8]) -> Result<&Self, ProgramError> {
+        let vesting_account: &Self = AccountType::Vesting.load(data)?;
+        vesting_account.check_initialized()?;
 
-impl IsInitialized for IDOVestingAccount {
-    fn is_initialized(&self) -> bool {
-        self.is_initialized
+        Ok(vesting_account)
+    }
+
+    pub fn load_mut(data: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        let vesting_account: &mut Self = AccountType::Vesting.load_mut(data)?;
+        vesting_account.check_initialized()?;
+
+        Ok(vesting_account)
     }
-}
 
-impl Pack for IDOVestingAccount {
-    const LEN: usize = 34;
+    /// Copies the account out of `data` regardless of its alignment, e.g. the account data fetched by clients.
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        let vesting_account: Self = AccountType::Vesting.read(data)?;
+        vesting_account.check_initialized()?;
 
-    fn pack_into_slice(&self, dst: &mut [u8]) -> () {
-        dst[..8].copy_from_slice(&self.last_claim_ts.to_le_bytes());
-        dst[8..16].copy_from_slice(&self.claimed_amount.to_le_bytes());
-        dst[16..24].copy_from_slice(&self.bought_amount.to_le_bytes());
-        dst[24..32].copy_from_slice(&self.amount_per_unlock.to_le_bytes());
-        dst[32] = self.bump;
-        dst[33] = self.is_initialized as u8;
+        Ok(vesting_account)
     }
 
-    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+    /// Amount `Claim` would transfer at `ts` (0 if nothing is claimable), the account itself isn't modified.
+    ///
+    /// `config_account` must be the config of the Vesting's mint.
+    pub fn claimable_at(&self, config_account: &IDOConfigAccount, ts: i64) -> u64 {
+        let mut vesting_account: Self = *self;
+        allow_claim_and_define_portion_at(ts, &config_account.vesting_strategy, &mut vesting_account).unwrap_or(0)
+    }
+
+    /// v1 layout had no header & was packed without alignment, it had neither Owner nor Mint,
+    /// so they must be provided by the caller.
+    pub fn from_v1(src: &[u8], owner: Pubkey, mint: Pubkey) -> Result<Self, ProgramError> {
         let reader: Reader = src.into();
 
         Ok(Self {
             last_claim_ts: reader.read_i64(0)?,
             claimed_amount: reader.read_u64(8)?,
-            bought_amount: reader.read_u64(16)?,
-            amount_per_unlock: reader.read_u64(24)?,
-            bump: src[32],
-            is_initialized: src[33] != 0 
-        })        
+            is_initialized: reader.read_u8(33)?,
+            ..Self::new(owner, mint, reader.read_u64(16)?, reader.read_u64(24)?, reader.read_u8(32)?)
+        })
     }
-}
\ No newline at end of file
+
+    fn check_initialized(&self) -> Result<(), ProgramError> {
+        if !self.is_initialized() {
+            return Err(ProgramError::UninitializedAccount);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,225 @@
+use std::cell::Ref;
+use solana_program::{
+    pubkey::Pubkey,
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    instruction::Instruction,
+    program::invoke_signed,
+    program_error::ProgramError
+};
+use spl_token_2022::{
+    check_spl_token_program_account,
+    state::Mint,
+    extension::{
+        StateWithExtensions,
+        BaseStateWithExtensions,
+        transfer_fee::{
+            TransferFee,
+            TransferFeeConfig,
+            MAX_FEE_BASIS_POINTS,
+            instruction::transfer_checked_with_fee
+        },
+        transfer_hook
+    }
+};
+use spl_transfer_hook_interface::onchain::add_extra_accounts_for_execute_cpi;
+use super::state::TransferFeePayer;
+
+
+/// Both SPL Token and SPL Token 2022 mints are supported, 
+/// however every CPI must be built for the program that owns the mint.
+pub fn check_token_program(token_program_info: &AccountInfo, mint_info: &AccountInfo) -> ProgramResult {
+    check_spl_token_program_account(token_program_info.key)?;
+
+    if mint_info.owner != token_program_info.key {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    Ok(())
+}
+
+/// Decimals of SPL Token or SPL Token 2022 mint.
+pub fn mint_decimals(mint_info: &AccountInfo) -> Result<u8, ProgramError> {
+    let mint_data_ref: Ref<&mut [u8]> = mint_info.data.borrow();
+
+    Ok(StateWithExtensions::<Mint>::unpack(*mint_data_ref)?.base.decimals)
+}
+
+/// Converts whole tokens (the units of instruction payloads & vesting accounts) into base units of the mint.
+pub fn to_base_units(amount: u64, decimals: u8) -> Result<u64, ProgramError> {
+    10u64
+        .checked_pow(decimals as u32)
+        .and_then(|multiplier| amount.checked_mul(multiplier))
+        .ok_or(ProgramError::ArithmeticOverflow)
+}
+
+/// Upper bound of the tokens (with decimals) the treasury pays out to deliver `amount` in at most `transfers` claims.
+///
+/// If the treasury absorbs the fee, every claim is grossed up separately, i.e. each one rounds the fee up & is capped by
+/// the maximum fee: `min(ceil(amount / (1 - rate)) + transfers, amount + transfers * maximum_fee)` for the higher of the epoch fees.
+pub fn reserve_amount(
+    mint: &StateWithExtensions<Mint>,
+    amount: u64,
+    transfers: u64,
+    fee_payer: TransferFeePayer
+) -> Result<u64, ProgramError> {
+    let transfer_fee_config: &TransferFeeConfig = match mint.get_extension::<TransferFeeConfig>() {
+        Ok(transfer_fee_config) if fee_payer == TransferFeePayer::Treasury && amount > 0 => transfer_fee_config,
+        _ => return Ok(amount)
+    };
+
+    [&transfer_fee_config.older_transfer_fee, &transfer_fee_config.newer_transfer_fee]
+        .into_iter()
+        .try_fold(amount, |reserve_amount, transfer_fee| {
+            let basis_points: u128 = u16::from(transfer_fee.transfer_fee_basis_points) as u128;
+            let max_basis_points: u128 = MAX_FEE_BASIS_POINTS as u128;
+            let max_fee_reserve: u128 = amount as u128 + transfers as u128 * u64::from(transfer_fee.maximum_fee) as u128;
+
+            let fee_reserve: u128 = match basis_points {
+                0 => amount as u128,
+                basis_points if basis_points >= max_basis_points => max_fee_reserve,
+                basis_points => (amount as u128 * max_basis_points)
+                    .div_ceil(max_basis_points - basis_points)
+                    .saturating_add(transfers as u128)
+                    .min(max_fee_reserve)
+            };
+
+            u64::try_from(fee_reserve)
+                .map(|fee_reserve| reserve_amount.max(fee_reserve))
+                .map_err(|_| ProgramError::ArithmeticOverflow)
+        })
+}
+
+/// Amount of tokens that must be transferred, so the recipient receives the expected amount
+/// according to `TransferFeePayer`.
+///
+/// `fee` is `Some` only if the mint has Token-2022 `TransferFeeConfig` extension.
+pub struct TransferAmount {
+    pub amount: u64,
+    pub fee: Option<u64>
+}
+
+impl TransferAmount {
+    pub fn new(
+        mint: &StateWithExtensions<Mint>,
+        epoch: u64,
+        amount: u64,
+        fee_payer: TransferFeePayer
+    ) -> Result<Self, ProgramError> {
+        let Ok(transfer_fee_config) = mint.get_extension::<TransferFeeConfig>() else {
+            return Ok(Self { amount, fee: None });
+        };
+
+        Ok(match fee_payer {
+            TransferFeePayer::Recipient => Self {
+                amount,
+                fee: Some(transfer_fee_config
+                    .calculate_epoch_fee(epoch, amount)
+                    .ok_or(ProgramError::ArithmeticOverflow)?
+                )
+            },
+            TransferFeePayer::Treasury => {
+                let transfer_fee: &TransferFee = transfer_fee_config.get_epoch_fee(epoch);
+                let gross_amount: u64 = transfer_fee
+                    .calculate_pre_fee_amount(amount)
+                    .ok_or(ProgramError::ArithmeticOverflow)?;
+
+                Self {
+                    amount: gross_amount,
+                    fee: Some(transfer_fee
+                        .calculate_fee(gross_amount)
+                        .ok_or(ProgramError::ArithmeticOverflow)?
+                    )
+                }
+            }
+        })
+    }
+
+    /// Crafts `TransferCheckedWithFee` if the mint charges transfer fees, otherwise plain `TransferChecked`.
+    pub fn create_transfer_checked_ix(
+        &self,
+        token_program_id: &Pubkey,
+        source: &Pubkey,
+        mint: &Pubkey,
+        destination: &Pubkey,
+        authority: &Pubkey,
+        decimals: u8
+    ) -> Result<Instruction, ProgramError> {
+        match self.fee {
+            Some(fee) => transfer_checked_with_fee(
+                token_program_id,
+                source,
+                mint,
+                destination,
+                authority,
+                &[],
+                self.amount,
+                decimals,
+                fee
+            ),
+            None => spl_token_2022::instruction::transfer_checked(
+                token_program_id,
+                source,
+                mint,
+                destination,
+                authority,
+                &[],
+                self.amount,
+                decimals
+            )
+        }
+    }
+
+    /// Invokes the transfer crafted by `create_transfer_checked_ix`.
+    ///
+    /// If the mint has Token-2022 `TransferHook` extension, the extra accounts required by the hook are picked
+    /// from `additional_accounts` according to the hook's validation PDA and appended to the CPI.
+    #[allow(clippy::too_many_arguments)]
+    pub fn invoke_transfer_checked<'a>(
+        &self,
+        token_program_id: &Pubkey,
+        source_info: &AccountInfo<'a>,
+        mint_info: &AccountInfo<'a>,
+        destination_info: &AccountInfo<'a>,
+        authority_info: &AccountInfo<'a>,
+        additional_accounts: &[AccountInfo<'a>],
+        decimals: u8,
+        signers_seeds: &[&[&[u8]]]
+    ) -> ProgramResult {
+        let mut transfer_checked_ix: Instruction = self.create_transfer_checked_ix(
+            token_program_id,
+            source_info.key,
+            mint_info.key,
+            destination_info.key,
+            authority_info.key,
+            decimals
+        )?;
+        let mut transfer_checked_account_infos: Vec<AccountInfo<'a>> = vec![
+            source_info.clone(),
+            mint_info.clone(),
+            destination_info.clone(),
+            authority_info.clone()
+        ];
+
+        let transfer_hook_program_id: Option<Pubkey> = {
+            let mint_data_ref: Ref<&mut [u8]> = mint_info.data.borrow();
+            transfer_hook::get_program_id(&StateWithExtensions::<Mint>::unpack(*mint_data_ref)?)
+        };
+
+        if let Some(transfer_hook_program_id) = transfer_hook_program_id {
+            add_extra_accounts_for_execute_cpi(
+                &mut transfer_checked_ix,
+                &mut transfer_checked_account_infos,
+                &transfer_hook_program_id,
+                source_info.clone(),
+                mint_info.clone(),
+                destination_info.clone(),
+                authority_info.clone(),
+                self.amount,
+                additional_accounts
+            )?;
+        }
+
+        invoke_signed(&transfer_checked_ix, &transfer_checked_account_infos, signers_seeds)
+    }
+}
@@ -5,27 +5,21 @@ use solana_program::{
 use super::vesting::LinearVestingStrategy;
 
 
+/// Every reader is bounds-checked: out of bounds range (including the overflowing one) is rejected with
+/// `ProgramError::InvalidInstructionData` instead of panicking, so arbitrary bytes can be decoded safely.
 pub trait ReadBytes {
     type Error;
 
-    /// # Safety
-    /// The caller is responsible for ensuring that the range `[start..start + 8]`
-    /// is within bounds of the `data` slice.
+    fn read_u8(&self, start: usize) -> Result<u8, Self::Error>;
+
     fn read_u64(&self, start: usize) -> Result<u64, Self::Error>;
 
-    /// # Safety
-    /// The caller is responsible for ensuring that the range `[start..start + 8]`
-    /// is within bounds of the `data` slice.
     fn read_i64(&self, start: usize) -> Result<i64, Self::Error>;
 
-    /// # Safety
-    /// The caller is responsible for ensuring that the range `[start..start + 4]`
-    /// is within bounds of the `data` slice.
     fn read_u32(&self, start: usize) -> Result<u32, Self::Error>;
 
-    /// # Safety
-    /// The caller is responsible for ensuring that the range `[start..start + 24]`
-    /// is within bounds of the `data` slice.
+    fn read_pubkey(&self, start: usize) -> Result<Pubkey, Self::Error>;
+
     fn read_linear_vesting_strategy(&self, start: usize) -> Result<LinearVestingStrategy, Self::Error>; 
 }
 
@@ -42,6 +36,10 @@ impl<'a> From<&'a [u8]> for Reader<'a> {
 impl ReadBytes for Reader<'_> {
     type Error = ProgramError;
 
+    fn read_u8(&self, start: usize) -> Result<u8, Self::Error> {
+        read_u8_slice(self.bytes, start)
+    }
+
     fn read_u64(&self, start: usize) -> Result<u64, Self::Error> {
         read_u64_slice(self.bytes, start)
     }
@@ -54,55 +52,54 @@ impl ReadBytes for Reader<'_> {
         read_u32_slice(self.bytes, start)
     }
 
+    fn read_pubkey(&self, start: usize) -> Result<Pubkey, Self::Error> {
+        read_pubkey_slice(self.bytes, start)
+    }
+
     fn read_linear_vesting_strategy(&self, start: usize) -> Result<LinearVestingStrategy, Self::Error> {
         read_linear_vesting_strategy_slice(self.bytes, start)
     }
 }
 
-/// # Safety
-/// The caller is responsible for ensuring that the range `[start..start + 8]`
-/// is within bounds of the `data` slice.
+/// Copies `[start..start + N]` out of `data`, `ProgramError::InvalidInstructionData` if the range is out of bounds.
+pub fn read_array<const N: usize>(data: &[u8], start: usize) -> Result<[u8; N], ProgramError> {
+    start
+        .checked_add(N)
+        .and_then(|end| data.get(start..end))
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or(ProgramError::InvalidInstructionData)
+}
+
+pub fn read_u8_slice(data: &[u8], start: usize) -> Result<u8, ProgramError> {
+    data.get(start).copied().ok_or(ProgramError::InvalidInstructionData)
+}
+
 pub fn read_u64_slice(data: &[u8], start: usize) -> Result<u64, ProgramError> {
-    Ok(
-        u64::from_le_bytes(data[start..start + 8]
-            .try_into()
-            .map_err(|_| ProgramError::InvalidInstructionData)?
-        )
-    )
+    Ok(u64::from_le_bytes(read_array(data, start)?))
 }
 
-/// # Safety
-/// The caller is responsible for ensuring that the range `[start..start + 8]`
-/// is within bounds of the `data` slice.
 pub fn read_i64_slice(data: &[u8], start: usize) -> Result<i64, ProgramError> {
-    Ok(
-        i64::from_le_bytes(data[start..start + 8]
-            .try_into()
-            .map_err(|_| ProgramError::InvalidInstructionData)?
-        )
-    )
+    Ok(i64::from_le_bytes(read_array(data, start)?))
 }
 
-/// # Safety
-/// The caller is responsible for ensuring that the range `[start..start + 4]`
-/// is within bounds of the `data` slice.
 pub fn read_u32_slice(data: &[u8], start: usize) -> Result<u32, ProgramError> {
-    Ok(
-        u32::from_le_bytes(data[start..start + 4]
-            .try_into()
-            .map_err(|_| ProgramError::InvalidInstructionData)?
-        )
-    )
+    Ok(u32::from_le_bytes(read_array(data, start)?))
+}
+
+pub fn read_pubkey_slice(data: &[u8], start: usize) -> Result<Pubkey, ProgramError> {
+    Ok(Pubkey::new_from_array(read_array(data, start)?))
 }
 
-/// # Safety
-/// The caller is responsible for ensuring that the range `[start..start + 24]`
-/// is within bounds of the `data` slice.
 pub fn read_linear_vesting_strategy_slice(data: &[u8], start: usize) -> Result<LinearVestingStrategy, ProgramError> {
+    let (vesting_end_ts_start, unlock_period_start) = (
+        start.checked_add(8).ok_or(ProgramError::InvalidInstructionData)?,
+        start.checked_add(16).ok_or(ProgramError::InvalidInstructionData)?
+    );
+
     Ok(LinearVestingStrategy {
         cliff_end_ts: read_i64_slice(data, start)?,
-        vesting_end_ts: read_i64_slice(data, start + 8)?,
-        unlock_period: read_i64_slice(data, start + 16)?
+        vesting_end_ts: read_i64_slice(data, vesting_end_ts_start)?,
+        unlock_period: read_i64_slice(data, unlock_period_start)?
     })
 }
 
@@ -113,4 +110,13 @@ pub fn derive_program_pda(seeds: &[&[u8]]) -> (Pubkey, u8) {
         seeds, 
         &crate::ID
     )
-}
\ No newline at end of file
+}
+
+/// Re-derives PDA owned by the current program with the stored bump, which is much cheaper than `derive_program_pda`.
+/// ### `program_id = ido_with_vesting::ID`
+pub fn create_program_pda(seeds: &[&[u8]]) -> Result<Pubkey, ProgramError> {
+    Pubkey::create_program_address(
+        seeds, 
+        &crate::ID
+    ).map_err(|_| ProgramError::InvalidSeeds)
+}
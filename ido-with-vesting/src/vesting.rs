@@ -1,12 +1,24 @@
-use solana_program::{sysvar::clock::Clock, entrypoint::ProgramResult};
+use bytemuck::{Pod, Zeroable};
+#[cfg(feature = "borsh")]
+use borsh::{BorshSerialize, BorshDeserialize};
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+use solana_program::{
+    sysvar::clock::Clock,
+    entrypoint::ProgramResult,
+    program_error::ProgramError
+};
 use super::{
     state::IDOVestingAccount,
     error::IDOProgramError,
-    constants::MAX_UNLOCKS
+    constants::{MAX_UNLOCKS, MAX_BPS}
 };
 
 
 #[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+#[cfg_attr(feature = "borsh", derive(BorshSerialize, BorshDeserialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct LinearVestingStrategy {
     pub cliff_end_ts: i64,      // timestamp in secs   
     pub vesting_end_ts: i64,    // timestamp in secs
@@ -15,19 +27,14 @@ pub struct LinearVestingStrategy {
 
 impl AsRef<[u8]> for LinearVestingStrategy {
     fn as_ref(&self) -> &[u8] {
-        unsafe { 
-            std::slice::from_raw_parts(
-                self as *const Self as *const u8, 
-                std::mem::size_of::<Self>()
-            )
-        }
+        bytemuck::bytes_of(self)
     }
 }
 
 impl LinearVestingStrategy {
     pub fn is_valid(&self, clock: &Clock) -> ProgramResult {
         let now_ts: i64 = clock.unix_timestamp;
-        let LinearVestingStrategy { cliff_end_ts, vesting_end_ts, unlock_period } = *self;
+        let LinearVestingStrategy { cliff_end_ts, vesting_end_ts, .. } = *self;
         
         if cliff_end_ts < now_ts {
             return Err(IDOProgramError::CliffPeriodMustBeGreaterThanNow.into());
@@ -41,19 +48,67 @@ impl LinearVestingStrategy {
             return Err(IDOProgramError::VestingPeriodMustBeGreaterThanCliff.into());
         }
 
-        let unlocks: i64 = (vesting_end_ts - cliff_end_ts) / unlock_period;
+        let unlocks: u8 = self.unlocks()?;
         
         if unlocks == 0 {
             return Err(IDOProgramError::UnlocksMustNotEqualZero.into());
         }
-
-        if unlocks > MAX_UNLOCKS as i64 {
-            return Err(IDOProgramError::MaxUnlocksOverflow.into());
-        }
         
         Ok(())
     }
 
+    /// Number of unlocks between the cliff and the end of the vesting.
+    /// 
+    /// Must be called only for the strategy, whose cliff precedes the end of the vesting.
+    pub fn unlocks(&self) -> Result<u8, ProgramError> {
+        if self.unlock_period <= 0 {
+            return Err(IDOProgramError::UnlockPeriodMustBeGreaterThanZero.into());
+        }
+
+        let unlocks: i64 = self.vesting_end_ts
+            .checked_sub(self.cliff_end_ts)
+            .ok_or(ProgramError::ArithmeticOverflow)?
+            / self.unlock_period;
+
+        match u8::try_from(unlocks) {
+            Ok(unlocks) if unlocks <= MAX_UNLOCKS => Ok(unlocks),
+            _ => Err(IDOProgramError::MaxUnlocksOverflow.into())
+        }
+    }
+
+    /// Steps of `(timestamp, cumulative_bps)`: the share of the bought amount (in basis points),
+    /// which is claimable in total starting from the timestamp by the vesting, that hasn't been claimed before.
+    ///
+    /// The 1st unlock is released right at the end of the cliff, the k-th one after k unlock periods & the remainder
+    /// at the end of the vesting. Portions are rounded down the same way `amount_per_unlock` is,
+    /// so the bps are exactly the amounts the vesting of `MAX_BPS` tokens releases.
+    ///
+    /// Note, the last step is always `(vesting_end_ts, MAX_BPS)`, even if the unlocks already reached `MAX_BPS`:
+    /// the bought amount is released in whole only at the end of the vesting, before it the k-th step releases `k * amount_per_unlock`.
+    pub fn unlock_schedule(&self) -> Result<impl Iterator<Item = (i64, u16)>, ProgramError> {
+        let unlocks: u8 = self.unlocks()?;
+
+        if unlocks == 0 {
+            return Err(IDOProgramError::UnlocksMustNotEqualZero.into());
+        }
+
+        let Self { cliff_end_ts, vesting_end_ts, unlock_period } = *self;
+        let bps_per_unlock: u16 = MAX_BPS / unlocks as u16;
+
+        Ok((1..=unlocks)
+            .map(move |unlock| {
+                let unlock_ts: i64 = if unlock == 1 {
+                    cliff_end_ts
+                } else {
+                    cliff_end_ts.saturating_add(unlock_period.saturating_mul(unlock as i64))
+                };
+
+                (unlock_ts, bps_per_unlock * unlock as u16)
+            })
+            .take_while(move |(unlock_ts, _)| *unlock_ts < vesting_end_ts)
+            .chain(std::iter::once((vesting_end_ts, MAX_BPS))))
+    }
+
     /// If the cliff equals to 0 => which basically means there is no cliff, program will use the current timestamp as the end of the cliff
     /// so the vesting period starts.
     /// 
@@ -98,47 +153,67 @@ impl LinearVestingStrategy {
     }
 }
 
-/// TODO: i'd like to refactor this fn later 
+// TODO: i'd like to refactor this fn later 
 
 /// This function handles most of the business logic.
 /// However i didn't decide to delegate transfering tokens to this fn, so it must be implemented externally.
 /// 
 /// This fn has the following flow. Checks for:
 /// 1. Cliff Period is active => returns `IDOProgramError::CliffIsActive`.
-/// 2. Vesting Period is over => returns the `left_transfer_portion`.
-/// 3. First OR next Unlock is reached => returns the `transfer_portion` that's calculated based on `unlocked_times`.
+/// 2. Vesting Period is over => returns the `left_transfer_portion` or `IDOProgramError::AlreadyClaimed` if nothing is left.
+/// 3. First OR next Unlock is reached => returns the `transfer_portion`, i.e. the amount unlocked since the cliff minus the claimed amount.
 /// 
 /// Otherwise Vesting is still considered active and the appropriated error is returned.
+/// 
+/// All accounting is checked, inconsistent vesting account is rejected with `ProgramError::ArithmeticOverflow`.
 pub fn allow_claim_and_define_portion(
     clock: &Clock,
     vesting_strategy: &LinearVestingStrategy,
     vesting_account: &mut IDOVestingAccount
-) -> Result<u64, IDOProgramError> {
-    let now_ts: i64 = clock.unix_timestamp;
+) -> Result<u64, ProgramError> {
+    allow_claim_and_define_portion_at(clock.unix_timestamp, vesting_strategy, vesting_account)
+}
+
+/// Same as `allow_claim_and_define_portion`, but at the arbitrary timestamp, so it's used off-chain as well.
+pub fn allow_claim_and_define_portion_at(
+    now_ts: i64,
+    vesting_strategy: &LinearVestingStrategy,
+    vesting_account: &mut IDOVestingAccount
+) -> Result<u64, ProgramError> {
     let LinearVestingStrategy { cliff_end_ts, vesting_end_ts, unlock_period } = *vesting_strategy;
 
     if now_ts < cliff_end_ts {
-        return Err(IDOProgramError::CliffIsActive);
+        return Err(IDOProgramError::CliffIsActive.into());
     }
 
     let bought_amount: u64 = vesting_account.bought_amount;
+    let claimed_amount: u64 = vesting_account.claimed_amount;
 
     // vesting period is over
     if now_ts >= vesting_end_ts {
-        let left_transfer_portion: u64 = bought_amount - vesting_account.claimed_amount;
-        vesting_account.claimed_amount += left_transfer_portion;
+        let left_transfer_portion: u64 = bought_amount
+            .checked_sub(claimed_amount)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+
+        if left_transfer_portion == 0 {
+            return Err(IDOProgramError::AlreadyClaimed.into());
+        }
+
+        vesting_account.claimed_amount = bought_amount;
         return Ok(left_transfer_portion);
     }
     
     let last_claim_ts: i64 = vesting_account.last_claim_ts;
     let never_claimed: bool = last_claim_ts == 0;
+    // the schedule always ends at `vesting_end_ts`, so a claim right before it never delays the final unlock by a whole period
+    let next_claim_ts: i64 = last_claim_ts.saturating_add(unlock_period).min(vesting_end_ts);
 
     // first claim OR new portion is available to be claimed
     if never_claimed
-    || now_ts >= last_claim_ts + unlock_period {
-        let vesting_ends_in: i64 = vesting_end_ts - now_ts;
-        let vesting_duration: i64 = vesting_end_ts - cliff_end_ts;
-        let time_passed: i64 = vesting_duration - vesting_ends_in;
+    || now_ts >= next_claim_ts {
+        let time_passed: i64 = now_ts
+            .checked_sub(cliff_end_ts)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
         let unlocked_times: i64 = if never_claimed {
             // if cliff has ended and user tries to immediately claim the tokens and next unlock period is not reached yet,
             // omitting max(1) will cause multiplying by 0 bug
@@ -146,12 +221,23 @@ pub fn allow_claim_and_define_portion(
         } else {
             time_passed / unlock_period
         };
-        let transfer_portion: u64 = vesting_account.amount_per_unlock * unlocked_times as u64;
+
+        // `amount_per_unlock` is rounded down, so the remainder is released only once the vesting is over
+        let unlocked_amount: u64 = (vesting_account.amount_per_unlock as u128 * unlocked_times as u128)
+            .min(bought_amount as u128) as u64;
+        let transfer_portion: u64 = unlocked_amount
+            .checked_sub(claimed_amount)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+
+        // the portion of the current unlock has been already claimed
+        if transfer_portion == 0 {
+            return Err(IDOProgramError::VestingIsActive.into());
+        }
 
         vesting_account.last_claim_ts = now_ts;
-        vesting_account.claimed_amount += transfer_portion;
+        vesting_account.claimed_amount = unlocked_amount;
         return Ok(transfer_portion);
     }
 
-    Err(IDOProgramError::VestingIsActive)    
-}
\ No newline at end of file
+    Err(IDOProgramError::VestingIsActive.into())    
+}
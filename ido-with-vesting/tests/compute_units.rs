@@ -0,0 +1,359 @@
+use mint_fixture::{
+    MintFixture,
+    MintFixtureClient,
+    MintFixtureError,
+};
+use ido_with_vesting::{
+    ID as IDO_PROGRAM_ID,
+    entrypoint,
+    instruction,
+    pda,
+    vesting::LinearVestingStrategy,
+    state::{IDOVestingAccount, TransferFeePayer}
+};
+use spl_token_2022::ID as SPL_TOKEN_2022_ID;
+use solana_program_test::{
+    ProgramTest,
+    ProgramTestContext,
+    BanksClientError,
+    processor
+};
+use solana_program::{
+    rent::Rent,
+    hash::Hash,
+    clock::Clock,
+    pubkey::Pubkey,
+    instruction::Instruction
+};
+use solana_sdk::{
+    account::Account as SolanaAccount,
+    message::Message,
+    transaction::Transaction,
+    signer::{keypair::Keypair, Signer}
+};
+
+
+/// Max compute units per instruction, kept next to this file so every change of the budget is reviewed.
+const COMPUTE_BUDGETS: &str = include_str!("compute_budgets.txt");
+
+#[derive(Clone, Copy)]
+enum Runtime {
+    /// `cargo test`, the program is run as a builtin, so only syscalls are charged.
+    Native,
+    /// `cargo test-sbf`, the deployed program is run by the VM.
+    Sbf
+}
+
+impl Runtime {
+    /// `solana-program-test` prefers the compiled program, whenever `cargo test-sbf` has set the output dir.
+    fn detect() -> Self {
+        if std::env::var_os("SBF_OUT_DIR").is_some() || std::env::var_os("BPF_OUT_DIR").is_some() {
+            Self::Sbf
+        } else {
+            Self::Native
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Native => "native",
+            Self::Sbf => "sbf"
+        }
+    }
+}
+
+/// Budget file consists of `<instruction> <native budget> <sbf budget>` lines, `#` starts a comment.
+///
+/// `-` means the budget is not recorded yet, such instruction fails the suite under that runtime.
+fn compute_budget(instruction_name: &str, runtime: Runtime) -> Option<u64> {
+    let budget: &str = budget_lines()
+        .filter_map(|(name, native_budget, sbf_budget)| (name == instruction_name).then_some(match runtime {
+            Runtime::Native => native_budget,
+            Runtime::Sbf => sbf_budget
+        }))
+        .next()
+        .unwrap_or_else(|| panic!("no compute budget for `{instruction_name}` in tests/compute_budgets.txt"));
+
+    if budget == "-" {
+        return None;
+    }
+
+    Some(budget
+        .parse()
+        .unwrap_or_else(|_| panic!("invalid compute budget for `{instruction_name}` in tests/compute_budgets.txt")))
+}
+
+/// `(instruction, native budget, sbf budget)` columns of every budget line.
+fn budget_lines() -> impl Iterator<Item = (&'static str, &'static str, &'static str)> {
+    COMPUTE_BUDGETS
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or_default())
+        .filter_map(|line| {
+            let mut columns = line.split_whitespace();
+
+            Some((columns.next()?, columns.next()?, columns.next()?))
+        })
+}
+
+/// Records the compute units consumed by every instruction & fails once any of them exceeds the stored budget.
+///
+/// Run `cargo test --test compute_units -- --nocapture` (or `cargo test-sbf -- --nocapture compute_units` for the real numbers)
+/// to print the table.
+#[tokio::test]
+async fn test_compute_units() -> Result<(), MintFixtureError> {
+    let program: ProgramTest = ProgramTest::new(
+        "ido_with_vesting",
+        IDO_PROGRAM_ID,
+        processor!(entrypoint::process_instruction)
+    );
+
+    let mut context: ProgramTestContext = program.start_with_context().await;
+    let payer: Keypair = context.payer.insecure_clone();
+    let payer_pkey: Pubkey = payer.pubkey();
+    let rent: Rent = context.banks_client.get_sysvar::<Rent>().await?;
+    let latest_blockhash: Hash = context.last_blockhash;
+
+    // 0. Create two Mints (fixed-price & auction); Create & Initialize ATAs; Mint tokens to ATAs
+    let mint_decimals: u8 = 9;
+    let mint_amount: u64 = 1_000_000 * 10u64.pow(mint_decimals as u32);
+    let mut mints: Vec<(Pubkey, Pubkey)> = Vec::with_capacity(2);
+
+    for _ in 0..2 {
+        let mint_fixture: MintFixture = MintFixture::new(
+            MintFixtureClient::Banks(&context.banks_client),
+            &payer,
+            &payer_pkey,
+            &rent
+        );
+
+        let mint_pkey: Pubkey = mint_fixture.create_and_intiialize_mint(mint_decimals, &latest_blockhash).await?;
+        let ata_pda: Pubkey = mint_fixture.create_and_intiialize_ata(&mint_pkey, &latest_blockhash).await?;
+        mint_fixture.mint_to_ata(&mint_pkey, &ata_pda, mint_amount, &latest_blockhash).await?;
+
+        mints.push((mint_pkey, ata_pda));
+    }
+
+    let mut units: Vec<(&str, u64)> = Vec::new();
+
+    // 1. Fixed-price IDO: Initialize, first Buy (creates the Vesting PDA), top-up Buy, Claims with and without ATA creation
+    // & Withdraw of the unsold supply along with the raised LAMPORTS
+    let (mint_pkey, ata_pda) = mints[0];
+    let treasury_pda: Pubkey = pda::find_treasury(&mint_pkey).0;
+    let config_pda: Pubkey = pda::find_config(&mint_pkey).0;
+    let vesting_pda: Pubkey = pda::find_vesting(&payer_pkey, &mint_pkey).0;
+
+    let now_ts: i64 = context.banks_client.get_sysvar::<Clock>().await?.unix_timestamp;
+    let vesting_strategy: LinearVestingStrategy = LinearVestingStrategy {
+        cliff_end_ts: 0,
+        vesting_end_ts: now_ts + 60 * 5,
+        unlock_period: 60
+    };
+
+    let initialize_ido_ix: Instruction = instruction::create_initialize_with_vesting(
+        mint_amount,
+        1_000,
+        &vesting_strategy,
+        TransferFeePayer::Recipient,
+        &payer_pkey,
+        &ata_pda,
+        &treasury_pda,
+        &config_pda,
+        &mint_pkey,
+        &SPL_TOKEN_2022_ID
+    );
+    units.push(("InitializeWithVesting", process_ix_with_units(&mut context, initialize_ido_ix, &payer).await?));
+
+    let buy_ix: Instruction = instruction::create_buy_with_vesting(1_000, &payer_pkey, &vesting_pda, &treasury_pda, &config_pda, &mint_pkey);
+    units.push(("BuyWithVesting:first", process_ix_with_units(&mut context, buy_ix.clone(), &payer).await?));
+    units.push(("BuyWithVesting:top-up", process_ix_with_units(&mut context, buy_ix, &payer).await?));
+
+    let (recipient, recipient_ata) = recipient_with_canonical_ata(&mint_pkey);
+    let claim_ix: Instruction = instruction::create_claim(
+        &payer_pkey,
+        &recipient,
+        &recipient_ata,
+        &vesting_pda,
+        &treasury_pda,
+        &config_pda,
+        &mint_pkey,
+        &SPL_TOKEN_2022_ID
+    );
+    units.push(("Claim:create-ata", process_ix_with_units(&mut context, claim_ix.clone(), &payer).await?));
+
+    // the second unlock since the cliff
+    warp_to_timestamp(&mut context, now_ts + 60 * 2).await?;
+    units.push(("Claim", process_ix_with_units(&mut context, claim_ix, &payer).await?));
+
+    warp_to_timestamp(&mut context, vesting_strategy.vesting_end_ts).await?;
+
+    let withdraw_ix: Instruction = instruction::create_withdraw(&payer_pkey, &ata_pda, &treasury_pda, &config_pda, &mint_pkey, &SPL_TOKEN_2022_ID);
+    units.push(("Withdraw", process_ix_with_units(&mut context, withdraw_ix, &payer).await?));
+    let fixed_price_vesting_pda: Pubkey = vesting_pda;
+
+    // 2. Auction: Initialize, PlaceBid, SettleAuction & SettleBid (creates the Vesting PDA)
+    let (mint_pkey, ata_pda) = mints[1];
+    let treasury_pda: Pubkey = pda::find_treasury(&mint_pkey).0;
+    let config_pda: Pubkey = pda::find_config(&mint_pkey).0;
+    let auction_pda: Pubkey = pda::find_auction(&mint_pkey).0;
+    let (bid_pda, bid_bump) = pda::find_bid(&payer_pkey, &mint_pkey);
+    let vesting_pda: Pubkey = pda::find_vesting(&payer_pkey, &mint_pkey).0;
+
+    let now_ts: i64 = context.banks_client.get_sysvar::<Clock>().await?.unix_timestamp;
+    let bidding_end_ts: i64 = now_ts + 60;
+    let vesting_strategy: LinearVestingStrategy = LinearVestingStrategy {
+        cliff_end_ts: 0,
+        vesting_end_ts: bidding_end_ts + 60 * 5,
+        unlock_period: 60
+    };
+
+    let initialize_auction_ix: Instruction = instruction::create_initialize_auction(
+        mint_amount,
+        1_000,
+        100,
+        bidding_end_ts,
+        &vesting_strategy,
+        TransferFeePayer::Recipient,
+        &payer_pkey,
+        &ata_pda,
+        &auction_pda,
+        &treasury_pda,
+        &config_pda,
+        &mint_pkey,
+        &SPL_TOKEN_2022_ID
+    );
+    units.push(("InitializeAuction", process_ix_with_units(&mut context, initialize_auction_ix, &payer).await?));
+
+    let place_bid_ix: Instruction = instruction::create_place_bid(100, 1_200, &payer_pkey, &bid_pda, bid_bump, &auction_pda, &treasury_pda, &config_pda, &mint_pkey);
+    units.push(("PlaceBid", process_ix_with_units(&mut context, place_bid_ix, &payer).await?));
+
+    warp_to_timestamp(&mut context, bidding_end_ts).await?;
+
+    let settle_auction_ix: Instruction = instruction::create_settle_auction(&auction_pda, &treasury_pda, &config_pda, &mint_pkey);
+    units.push(("SettleAuction", process_ix_with_units(&mut context, settle_auction_ix, &payer).await?));
+
+    let settle_bid_ix: Instruction = instruction::create_settle_bid(
+        &payer_pkey,
+        &payer_pkey,
+        &bid_pda,
+        &vesting_pda,
+        &auction_pda,
+        &treasury_pda,
+        &config_pda,
+        &mint_pkey
+    );
+    units.push(("SettleBid", process_ix_with_units(&mut context, settle_bid_ix, &payer).await?));
+
+    // 3. Migrate of the fixed-price Vesting downgraded to the v1 layout: the owner derives the PDA, the rent is topped up by CPI
+    let vesting_account: IDOVestingAccount = IDOVestingAccount::unpack(
+        &context.banks_client.get_account(fixed_price_vesting_pda).await?.unwrap().data
+    ).unwrap();
+    let vesting_v1_data: Vec<u8> = [
+        &vesting_account.last_claim_ts.to_le_bytes()[..],
+        &vesting_account.claimed_amount.to_le_bytes(),
+        &vesting_account.bought_amount.to_le_bytes(),
+        &vesting_account.amount_per_unlock.to_le_bytes(),
+        &[vesting_account.bump, vesting_account.is_initialized]
+    ].concat();
+
+    context.set_account(&fixed_price_vesting_pda, &SolanaAccount {
+        lamports: rent.minimum_balance(vesting_v1_data.len()),
+        data: vesting_v1_data,
+        owner: IDO_PROGRAM_ID,
+        executable: false,
+        rent_epoch: 0
+    }.into());
+
+    let migrate_ix: Instruction = instruction::create_migrate_vesting(&payer_pkey, &fixed_price_vesting_pda, &payer_pkey, &mints[0].0);
+    units.push(("Migrate", process_ix_with_units(&mut context, migrate_ix, &payer).await?));
+
+    // 4. Print the whole table first, so every regression & every missing budget is visible at once
+    let runtime: Runtime = Runtime::detect();
+    let mut exceeded: Vec<&str> = Vec::new();
+    let mut missing: Vec<&str> = Vec::new();
+
+    println!("compute units ({} runtime)", runtime.name());
+    println!("{:<24} | {:>8} | {:>8}", "instruction", "units", "budget");
+    println!("{:-<24}-|-{:->8}-|-{:->8}", "", "", "");
+
+    for (name, units_consumed) in units {
+        let budget: Option<u64> = compute_budget(name, runtime);
+        let budget_column: String = budget.map_or_else(|| "-".to_string(), |budget| budget.to_string());
+        println!("{name:<24} | {units_consumed:>8} | {budget_column:>8}");
+
+        match budget {
+            Some(budget) if units_consumed > budget => exceeded.push(name),
+            Some(_) => {},
+            None => missing.push(name)
+        }
+    }
+
+    assert!(exceeded.is_empty(), "compute budget exceeded by: {exceeded:?}");
+    assert!(
+        missing.is_empty(),
+        "no {} compute budget for {missing:?}, record the printed units + ~10% in tests/compute_budgets.txt",
+        runtime.name()
+    );
+
+    Ok(())
+}
+
+/// Simulates the transaction to get the consumed compute units, then processes it.
+async fn process_ix_with_units(
+    context: &mut ProgramTestContext,
+    ix: Instruction,
+    payer: &Keypair
+) -> Result<u64, BanksClientError> {
+    let latest_blockhash: Hash = context.get_new_latest_blockhash().await?;
+    let message: Message = Message::new(&[ix], Some(&payer.pubkey()));
+    let mut tx: Transaction = Transaction::new_unsigned(message);
+
+    tx.sign(&[payer], latest_blockhash);
+
+    let simulation = context.banks_client.simulate_transaction(tx.clone()).await?;
+
+    if let Some(Err(err)) = simulation.result {
+        return Err(BanksClientError::TransactionError(err));
+    }
+
+    let units_consumed: u64 = simulation.simulation_details.map_or(0, |details| details.units_consumed);
+    context.banks_client.process_transaction_with_metadata(tx).await?.result.map_err(BanksClientError::TransactionError)?;
+
+    Ok(units_consumed)
+}
+
+/// The Associated Token Program searches for the bump & every extra iteration costs 1_500 units,
+/// so the recipient, whose ATA has the first bump (255), makes `Claim:create-ata` deterministic.
+fn recipient_with_canonical_ata(mint_pkey: &Pubkey) -> (Pubkey, Pubkey) {
+    loop {
+        let recipient: Pubkey = Pubkey::new_unique();
+        let (recipient_ata, bump) = pda::find_ata(&recipient, mint_pkey, &SPL_TOKEN_2022_ID);
+
+        if bump == u8::MAX {
+            return (recipient, recipient_ata);
+        }
+    }
+}
+
+async fn warp_to_timestamp(context: &mut ProgramTestContext, unix_timestamp: i64) -> Result<(), BanksClientError> {
+    let mut clock: Clock = context.banks_client.get_sysvar::<Clock>().await?;
+    clock.unix_timestamp = unix_timestamp;
+    context.set_sysvar(&clock);
+
+    Ok(())
+}
+
+/// `cargo test` runs the program as a builtin, so the SBF budgets are checked only by `cargo test-sbf` (the `sbf` CI job),
+/// including the ones of the instructions `test_compute_units` doesn't exercise.
+#[test]
+fn test_sbf_compute_budgets_recorded() {
+    if let Runtime::Native = Runtime::detect() {
+        println!("sbf compute budgets are checked by `cargo test-sbf`");
+        return;
+    }
+
+    let missing: Vec<&str> = budget_lines()
+        .filter_map(|(name, _, sbf_budget)| (sbf_budget == "-").then_some(name))
+        .collect();
+
+    assert!(missing.is_empty(), "no sbf compute budget for {missing:?} in tests/compute_budgets.txt");
+}
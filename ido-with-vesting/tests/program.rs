@@ -5,37 +5,89 @@ use mint_fixture::{
 };
 use ido_with_vesting::{
     ID as IDO_PROGRAM_ID,
-    external_ids::ATA_PROGRAM_ID,
     entrypoint,
-    instruction,
-    utils::derive_program_pda,
-    vesting::LinearVestingStrategy,
-    constants::{
-        IDO_CONFIG_ACCOUNT_SEED, 
-        IDO_TREASURY_ACCOUNT_SEED,
-        IDO_VESTING_ACCOUNT_SEED
-    }
+    instruction::{self, IDOInstruction},
+    client::{self, ProgramClient, ProgramClientError, ProgramClientTransport},
+    inspect::{AccountInspection, ProgramAccount, VestingProgress},
+    borsh,
+    idl,
+    events::IDOEvent,
+    error::IDOProgramError,
+    pda,
+    utils::{read_u64_slice, read_linear_vesting_strategy_slice},
+    vesting::{LinearVestingStrategy, allow_claim_and_define_portion},
+    token::to_base_units,
+    state::{
+        IDOAuctionAccount,
+        IDOBidAccount,
+        IDOConfigAccount,
+        IDOVestingAccount,
+        TransferFeePayer,
+        AccountType,
+        ACCOUNT_VERSION,
+        ACCOUNT_HEADER_LEN
+    },
+    constants::{MAX_UNLOCKS, MAX_BPS, AUCTION_PRICE_LEVELS}
 };
+use proptest::prelude::*;
 
-use spl_token_2022::ID as SPL_TOKEN_2022_ID;
+use spl_token_2022::{
+    state::{Account, AccountState, Mint},
+    extension::{
+        ExtensionType,
+        StateWithExtensions,
+        BaseStateWithExtensions,
+        transfer_fee::TransferFeeConfig,
+        memo_transfer::instruction::enable_required_transfer_memos,
+        cpi_guard::instruction::enable_cpi_guard,
+        default_account_state::instruction::update_default_account_state
+    },
+    ID as SPL_TOKEN_2022_ID
+};
+use spl_token::ID as SPL_TOKEN_ID;
+use base64::{engine::general_purpose::STANDARD as BASE64_STANDARD, Engine};
+use spl_token_2022::offchain::AccountFetchError;
+use spl_transfer_hook_interface::{
+    get_extra_account_metas_address,
+    instruction::ExecuteInstruction
+};
+use spl_tlv_account_resolution::{
+    account::ExtraAccountMeta,
+    state::ExtraAccountMetaList
+};
+use solana_client::rpc_filter::RpcFilterType;
 use solana_program_test::{
     ProgramTest,
+    ProgramTestContext,
+    BanksClient,
+    BanksClientError,
+    BanksTransactionResultWithMetadata,
     processor
 };
 use solana_program::{
     rent::Rent,
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    hash::Hash,
+    clock::Clock,
     pubkey::Pubkey,
-    instruction::Instruction,
+    program_pack::Pack,
+    instruction::{Instruction, AccountMeta, InstructionError},
+    native_token::LAMPORTS_PER_SOL
 };
+#[allow(deprecated)]
 use solana_sdk::{
+    account::{Account as SolanaAccount, AccountSharedData},
     message::Message,
-    transaction::Transaction,
-    signer::Signer
+    system_transaction,
+    transaction::{Transaction, TransactionError},
+    signer::{keypair::Keypair, Signer}
 };
 
 
 #[tokio::test]
-async fn test_all_instructions() -> Result<(), MintFixtureError> {
+async fn test_all_instructions() -> Result<(), Box<dyn std::error::Error>> {
     // spl token 2022 is preloaded automatically, so there is no need to explicitly add_program with spl-token-2022 binary
     let program: ProgramTest = ProgramTest::new(
         "ido_with_vesting", 
@@ -43,7 +95,7 @@ async fn test_all_instructions() -> Result<(), MintFixtureError> {
         processor!(entrypoint::process_instruction)
     );
 
-    let (banks_client, payer, latest_blockhash) = program.start().await;
+    let (mut banks_client, payer, latest_blockhash) = program.start().await;
     let payer_pkey: Pubkey = payer.pubkey();
     let rent: Rent = banks_client.get_sysvar::<Rent>().await?;
 
@@ -62,121 +114,2398 @@ async fn test_all_instructions() -> Result<(), MintFixtureError> {
     let ata_pda: Pubkey = mint_fixture.create_and_intiialize_ata(&mint_pkey, &latest_blockhash).await?;
     mint_fixture.mint_to_ata(&mint_pkey, &ata_pda, mint_amount, &latest_blockhash).await?;
 
-    // // 1. Craft InitializeIDOWithVesting instruction
+    // 1. Initialize IDO with vesting, the client derives all PDA from the mint
+    let mut program_client: ProgramClient = ProgramClient::new(
+        ProgramClientTransport::Banks(&mut banks_client),
+        &payer,
+        mint_pkey,
+        SPL_TOKEN_2022_ID
+    );
+
     let transfer_amount: u64 = mint_amount;  // so we transfer the whole supply to the IDO
     let lamports_per_token: u32 = 1_000;
     let vesting_strategy: LinearVestingStrategy = LinearVestingStrategy::new_without_cliff(
         60 * 5,  // 5 minutes vesting
         60          // 1 minute every new unlock
     );
+    program_client.initialize(transfer_amount, lamports_per_token, &vesting_strategy, TransferFeePayer::Recipient).await?;
+
+    let config_account: IDOConfigAccount = program_client.fetch_config().await?;
+    assert_eq!(config_account.deposited_amount, transfer_amount);
+    assert_eq!(config_account.lamports_per_token, lamports_per_token);
+    assert_eq!(config_account.mint, mint_pkey);
+    assert_eq!(config_account.treasury, program_client.treasury_pda());
+    assert_eq!(config_account.bump, pda::find_config(&mint_pkey).1);
+    assert_eq!(config_account.treasury_bump, pda::find_treasury(&mint_pkey).1);
+
+    // 2. Buy with vesting
+    let buy_amount: u64 = 17_000_000;
+    program_client.buy(buy_amount).await?;
+
+    let vesting_account: IDOVestingAccount = program_client.fetch_vesting(&payer_pkey).await?;
+    assert_eq!(vesting_account.bought_amount, buy_amount);
+    assert_eq!(vesting_account.claimed_amount, 0);
+
+    // 3. Claim.
+    // I decided not to force the instruction to always interpriate `signer` as the `recipient`,
+    // so the caller can pass any valid `recipient` and `recipient_ata` beside `signer` and `signer_ata`.
+    let new_wallet: Pubkey = Pubkey::new_unique();
+    program_client.claim(&new_wallet).await?;
+
+    let vesting_account: IDOVestingAccount = program_client.fetch_vesting(&payer_pkey).await?;
+    assert_eq!(vesting_account.claimed_amount, vesting_account.amount_per_unlock);
+
+    // 4. Next unlock isn't reached yet, so the program error is decoded by the client
+    let result = program_client.claim(&new_wallet).await;
+    assert!(matches!(result, Err(ProgramClientError::Program(IDOProgramError::VestingIsActive))));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_program_client_over_context() -> Result<(), Box<dyn std::error::Error>> {
+    let program: ProgramTest = ProgramTest::new(
+        "ido_with_vesting", 
+        IDO_PROGRAM_ID,
+        processor!(entrypoint::process_instruction)
+    );
+
+    let mut context: ProgramTestContext = program.start_with_context().await;
+    let payer: Keypair = context.payer.insecure_clone();
+    let payer_pkey: Pubkey = payer.pubkey();
+    let rent: Rent = context.banks_client.get_sysvar::<Rent>().await?;
+    let latest_blockhash: Hash = context.last_blockhash;
+
+    let mint_fixture: MintFixture = MintFixture::new(
+        MintFixtureClient::Banks(&context.banks_client),
+        &payer,
+        &payer_pkey,
+        &rent
+    );
+    let mint_decimals: u8 = 6;
+    let mint_amount: u64 = to_base_units(1_000, mint_decimals)?;
+
+    let mint_pkey: Pubkey = mint_fixture.create_and_intiialize_mint(mint_decimals, &latest_blockhash).await?;
+    let ata_pda: Pubkey = mint_fixture.create_and_intiialize_ata(&mint_pkey, &latest_blockhash).await?;
+    mint_fixture.mint_to_ata(&mint_pkey, &ata_pda, mint_amount, &latest_blockhash).await?;
+
+    let mut program_client: ProgramClient = ProgramClient::new(
+        ProgramClientTransport::ProgramTest(&mut context),
+        &payer,
+        mint_pkey,
+        SPL_TOKEN_2022_ID
+    );
+
+    // 0. Nothing is initialized yet
+    let result = program_client.fetch_config().await;
+    assert!(matches!(result, Err(ProgramClientError::AccountNotFound(address)) if address == program_client.config_pda()));
+
+    let unlock_period: i64 = 60;
+    let vesting_strategy: LinearVestingStrategy = LinearVestingStrategy::new_without_cliff(unlock_period * 4, unlock_period);
+
+    // Insufficient funds of the token program share the code with `UnlocksMustNotEqualZero`, but aren't the program error
+    let result = program_client.initialize(mint_amount + 1, 1_000, &vesting_strategy, TransferFeePayer::Recipient).await;
+    assert_eq!(spl_token_2022::error::TokenError::InsufficientFunds as u32, IDOProgramError::UnlocksMustNotEqualZero as u32);
+    assert!(matches!(
+        result,
+        Err(ProgramClientError::Transaction(TransactionError::InstructionError(0, InstructionError::Custom(code))))
+            if code == spl_token_2022::error::TokenError::InsufficientFunds as u32
+    ));
+
+    program_client.initialize(mint_amount, 1_000, &vesting_strategy, TransferFeePayer::Recipient).await?;
+
+    // 1. Claim before buy is decoded as the program error
+    let result = program_client.claim(&payer_pkey).await;
+    assert!(matches!(result, Err(ProgramClientError::Program(IDOProgramError::ClaimBeforeBuy))));
+
+    program_client.buy(100).await?;
+
+    // Tokens are sold only within the deposited supply
+    let result = program_client.buy(901).await;
+    assert!(matches!(result, Err(ProgramClientError::Program(IDOProgramError::SupplyExceeded))));
+    assert_eq!(program_client.fetch_config().await?.sold_amount, 100);
+
+    program_client.claim(&payer_pkey).await?;
+
+    // 2. Warp through the transport & claim again.
+    // The immediate 1st claim has already released the 1st unlock, so the 2nd one is reached after 2 unlock periods.
+    let ProgramClientTransport::ProgramTest(context) = program_client.transport() else {
+        unreachable!();
+    };
+    let mut clock: Clock = context.banks_client.get_sysvar::<Clock>().await?;
+    clock.unix_timestamp += unlock_period * 2;
+    context.set_sysvar(&clock);
+
+    program_client.claim(&payer_pkey).await?;
+
+    let vesting_account: IDOVestingAccount = program_client.fetch_vesting(&payer_pkey).await?;
+    assert_eq!(vesting_account.claimed_amount, vesting_account.amount_per_unlock * 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_withdraw() -> Result<(), Box<dyn std::error::Error>> {
+    let program: ProgramTest = ProgramTest::new(
+        "ido_with_vesting", 
+        IDO_PROGRAM_ID,
+        processor!(entrypoint::process_instruction)
+    );
+
+    let mut context: ProgramTestContext = program.start_with_context().await;
+    let payer: Keypair = context.payer.insecure_clone();
+    let payer_pkey: Pubkey = payer.pubkey();
+    let rent: Rent = context.banks_client.get_sysvar::<Rent>().await?;
+    let latest_blockhash: Hash = context.last_blockhash;
+
+    let buyer: Keypair = Keypair::new();
+    let fund_tx: Transaction = system_transaction::transfer(&payer, &buyer.pubkey(), LAMPORTS_PER_SOL, latest_blockhash);
+    process_tx(&context.banks_client, fund_tx).await?;
+
+    // 0. Create Mint; Create & Initialize ATA; Mint tokens to ATA; Initialize the IDO by the payer (authority)
+    let mint_decimals: u8 = 6;
+    let mint_amount: u64 = to_base_units(1_000, mint_decimals)?;
+
+    let mint_fixture: MintFixture = MintFixture::new(
+        MintFixtureClient::Banks(&context.banks_client),
+        &payer,
+        &payer_pkey,
+        &rent
+    );
+    let mint_pkey: Pubkey = mint_fixture.create_and_intiialize_mint(mint_decimals, &latest_blockhash).await?;
+    let ata_pda: Pubkey = mint_fixture.create_and_intiialize_ata(&mint_pkey, &latest_blockhash).await?;
+    mint_fixture.mint_to_ata(&mint_pkey, &ata_pda, mint_amount, &latest_blockhash).await?;
+
+    let treasury_pda: Pubkey = pda::find_treasury(&mint_pkey).0;
+    let config_pda: Pubkey = pda::find_config(&mint_pkey).0;
+    let unlock_period: i64 = 60;
+    let vesting_strategy: LinearVestingStrategy = LinearVestingStrategy::new_without_cliff(unlock_period * 4, unlock_period);
+
+    let initialize_ido_ix: Instruction = instruction::create_initialize_with_vesting_for_mint(
+        mint_amount, 
+        1_000, 
+        &vesting_strategy, 
+        TransferFeePayer::Recipient, 
+        &payer_pkey, 
+        &mint_pkey, 
+        &SPL_TOKEN_2022_ID
+    );
+    process_ixs(&mut context, &[initialize_ido_ix], &payer).await?;
+
+    let config_account: IDOConfigAccount = IDOConfigAccount::unpack(&context.banks_client.get_account(config_pda).await?.unwrap().data).unwrap();
+    assert_eq!(config_account.authority, payer_pkey);
+
+    // 1. Nothing is raised yet
+    let withdraw_ix: Instruction = instruction::create_withdraw_for_mint(&payer_pkey, &mint_pkey, &SPL_TOKEN_2022_ID);
+    let err = process_ixs(&mut context, std::slice::from_ref(&withdraw_ix), &payer).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(IDOProgramError::NothingToWithdraw as u32));
+
+    // 2. The buyer buys & claims (which creates its ATA), but can't withdraw
+    let buy_amount: u64 = 100;
+    let buy_ix: Instruction = instruction::create_buy_with_vesting_for_mint(buy_amount, &buyer.pubkey(), &mint_pkey);
+    let claim_ix: Instruction = instruction::create_claim_for_mint(&buyer.pubkey(), &buyer.pubkey(), &mint_pkey, &SPL_TOKEN_2022_ID);
+    let logs: Vec<String> = process_ixs_with_logs(&mut context, &[buy_ix, claim_ix], &buyer).await?;
+
+    // event amounts are in base units, like the token accounts
+    assert_eq!(IDOEvent::parse_logs(&logs), vec![
+        IDOEvent::Purchased { mint: mint_pkey, buyer: buyer.pubkey(), amount: to_base_units(buy_amount, mint_decimals)?, lamports: buy_amount * 1_000 },
+        IDOEvent::Claimed { 
+            mint: mint_pkey, 
+            buyer: buyer.pubkey(), 
+            recipient: buyer.pubkey(), 
+            amount: to_base_units(buy_amount / config_account.unlocks as u64, mint_decimals)? 
+        }
+    ]);
+
+    let buyer_withdraw_ix: Instruction = instruction::create_withdraw_for_mint(&buyer.pubkey(), &mint_pkey, &SPL_TOKEN_2022_ID);
+    let err = process_ixs(&mut context, &[buyer_withdraw_ix], &buyer).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(IDOProgramError::InvalidWithdrawAuthority as u32));
+
+    // 3. The raised LAMPORTS (held by the config) are withdrawn right away, the supply is kept while the sale is active
+    let config_rent_exempt: u64 = rent.minimum_balance(IDOConfigAccount::LEN);
+    let raised_lamports: u64 = buy_amount * 1_000;
+    assert_eq!(context.banks_client.get_balance(config_pda).await?, config_rent_exempt + raised_lamports);
+
+    let logs: Vec<String> = process_ixs_with_logs(&mut context, std::slice::from_ref(&withdraw_ix), &payer).await?;
+    assert_eq!(IDOEvent::parse_logs(&logs), vec![
+        IDOEvent::Withdrawn { mint: mint_pkey, authority: payer_pkey, lamports: raised_lamports, amount: 0 }
+    ]);
+    assert_eq!(context.banks_client.get_balance(config_pda).await?, config_rent_exempt);
+
+    let err = process_ixs(&mut context, std::slice::from_ref(&withdraw_ix), &payer).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(IDOProgramError::NothingToWithdraw as u32));
+
+    // 4. The unsold supply is withdrawn once the vesting is over, the sold one stays for the buyer
+    let mut clock: Clock = context.banks_client.get_sysvar::<Clock>().await?;
+    clock.unix_timestamp = vesting_strategy.vesting_end_ts;
+    context.set_sysvar(&clock);
+
+    let unsold_amount: u64 = mint_amount - to_base_units(buy_amount, mint_decimals)?;
+    let logs: Vec<String> = process_ixs_with_logs(&mut context, std::slice::from_ref(&withdraw_ix), &payer).await?;
+    assert_eq!(IDOEvent::parse_logs(&logs), vec![
+        IDOEvent::Withdrawn { mint: mint_pkey, authority: payer_pkey, lamports: 0, amount: unsold_amount }
+    ]);
+
+    let token_amount = |data: &[u8]| StateWithExtensions::<Account>::unpack(data).unwrap().base.amount;
+    assert_eq!(token_amount(&context.banks_client.get_account(ata_pda).await?.unwrap().data), unsold_amount);
+
+    let config_account: IDOConfigAccount = IDOConfigAccount::unpack(&context.banks_client.get_account(config_pda).await?.unwrap().data).unwrap();
+    assert_eq!(config_account.deposited_amount, to_base_units(buy_amount, mint_decimals)?);
+
+    let err = process_ixs(&mut context, std::slice::from_ref(&withdraw_ix), &payer).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(IDOProgramError::NothingToWithdraw as u32));
+
+    let claim_ix: Instruction = instruction::create_claim_for_mint(&buyer.pubkey(), &buyer.pubkey(), &mint_pkey, &SPL_TOKEN_2022_ID);
+    process_ixs(&mut context, &[claim_ix], &buyer).await?;
+
+    let buyer_ata: Pubkey = pda::find_ata(&buyer.pubkey(), &mint_pkey, &SPL_TOKEN_2022_ID).0;
+    assert_eq!(token_amount(&context.banks_client.get_account(buyer_ata).await?.unwrap().data), to_base_units(buy_amount, mint_decimals)?);
+    assert_eq!(token_amount(&context.banks_client.get_account(treasury_pda).await?.unwrap().data), 0);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_inspect() -> Result<(), Box<dyn std::error::Error>> {
+    let program: ProgramTest = ProgramTest::new(
+        "ido_with_vesting", 
+        IDO_PROGRAM_ID,
+        processor!(entrypoint::process_instruction)
+    );
+
+    let mut context: ProgramTestContext = program.start_with_context().await;
+    let payer: Keypair = context.payer.insecure_clone();
+    let payer_pkey: Pubkey = payer.pubkey();
+    let rent: Rent = context.banks_client.get_sysvar::<Rent>().await?;
+    let latest_blockhash: Hash = context.last_blockhash;
+    let now_ts: i64 = context.banks_client.get_sysvar::<Clock>().await?.unix_timestamp;
+
+    let mint_fixture: MintFixture = MintFixture::new(
+        MintFixtureClient::Banks(&context.banks_client),
+        &payer,
+        &payer_pkey,
+        &rent
+    );
+    let mint_pkey: Pubkey = mint_fixture.create_and_intiialize_mint(0, &latest_blockhash).await?;
+    let ata_pda: Pubkey = mint_fixture.create_and_intiialize_ata(&mint_pkey, &latest_blockhash).await?;
+    mint_fixture.mint_to_ata(&mint_pkey, &ata_pda, 1_000, &latest_blockhash).await?;
+
+    let mut program_client: ProgramClient = ProgramClient::new(
+        ProgramClientTransport::ProgramTest(&mut context),
+        &payer,
+        mint_pkey,
+        SPL_TOKEN_2022_ID
+    );
+
+    // 4 unlocks of 25 tokens, the cliff ends at the initialization
+    let unlock_period: i64 = 60;
+    let vesting_strategy: LinearVestingStrategy = LinearVestingStrategy {
+        cliff_end_ts: 0,
+        vesting_end_ts: now_ts + unlock_period * 4,
+        unlock_period
+    };
+    program_client.initialize(1_000, 1_000, &vesting_strategy, TransferFeePayer::Recipient).await?;
+    program_client.buy(100).await?;
+
+    // 0. Config is detected by the header
+    let inspection: AccountInspection = program_client.inspect(&program_client.config_pda()).await?;
+    assert!(matches!(inspection.account, ProgramAccount::Config(_)));
+    assert!(inspection.progress.is_none());
+    assert_eq!(inspection.to_json()["fields"]["mint"], mint_pkey.to_string());
+
+    // 1. The 1st portion is claimable right after the cliff
+    let vesting_pda: Pubkey = program_client.vesting_pda(&payer_pkey);
+    let inspection: AccountInspection = program_client.inspect(&vesting_pda).await?;
+    let progress: &VestingProgress = inspection.progress.as_ref().unwrap();
+    assert!(matches!(inspection.account, ProgramAccount::Vesting(_)));
+    assert_eq!(progress.claimable_now, 25);
+    assert_eq!(progress.next_unlock_ts, Some(now_ts + unlock_period * 2));
+    assert_eq!(progress.remaining_unlocks, 3);
+
+    // 2. Nothing is claimable until the 2nd unlock once the 1st portion is claimed
+    program_client.claim(&payer_pkey).await?;
+
+    let inspection: AccountInspection = program_client.inspect(&vesting_pda).await?;
+    let json: serde_json::Value = inspection.to_json();
+    assert_eq!(json["type"], "Vesting");
+    assert_eq!(json["fields"]["claimed_amount"], 25);
+    assert_eq!(json["fields"]["claimable_now"], 0);
+    assert_eq!(json["fields"]["next_unlock_ts"], now_ts + unlock_period * 2);
+    assert_eq!(json["fields"]["remaining_unlocks"], 3);
+
+    let table: String = inspection.to_string();
+    assert!(table.contains(&vesting_pda.to_string()));
+    assert!(table.lines().any(|line| line.starts_with("claimable_now") && line.ends_with("| 0")));
+
+    // 3. The rest is claimable once the vesting is over
+    let ProgramClientTransport::ProgramTest(context) = program_client.transport() else {
+        unreachable!();
+    };
+    let mut clock: Clock = context.banks_client.get_sysvar::<Clock>().await?;
+    clock.unix_timestamp = now_ts + unlock_period * 4;
+    context.set_sysvar(&clock);
+
+    let inspection: AccountInspection = program_client.inspect(&vesting_pda).await?;
+    let progress: &VestingProgress = inspection.progress.as_ref().unwrap();
+    assert_eq!(progress.claimable_now, 75);
+    assert_eq!(progress.next_unlock_ts, None);
+    assert_eq!(progress.remaining_unlocks, 0);
+
+    // 4. Accounts of other programs are rejected
+    let result = program_client.inspect(&mint_pkey).await;
+    assert!(matches!(result, Err(ProgramClientError::InvalidAccountData { error: ProgramError::IncorrectProgramId, .. })));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_list_program_accounts() -> Result<(), Box<dyn std::error::Error>> {
+    let program: ProgramTest = ProgramTest::new(
+        "ido_with_vesting", 
+        IDO_PROGRAM_ID,
+        processor!(entrypoint::process_instruction)
+    );
+
+    let mut context: ProgramTestContext = program.start_with_context().await;
+    let payer: Keypair = context.payer.insecure_clone();
+    let payer_pkey: Pubkey = payer.pubkey();
+    let rent: Rent = context.banks_client.get_sysvar::<Rent>().await?;
+    let latest_blockhash: Hash = context.last_blockhash;
+
+    let buyer: Keypair = Keypair::new();
+    let fund_tx: Transaction = system_transaction::transfer(&payer, &buyer.pubkey(), LAMPORTS_PER_SOL, latest_blockhash);
+    process_tx(&context.banks_client, fund_tx).await?;
+
+    // 0. 2 IDOs: the payer buys from both, the buyer only from the 1st one
+    let mint_decimals: u8 = 6;
+    let mint_amount: u64 = to_base_units(1_000, mint_decimals)?;
+    let mut mints: Vec<Pubkey> = Vec::new();
+
+    for _ in 0..2 {
+        let mint_fixture: MintFixture = MintFixture::new(
+            MintFixtureClient::Banks(&context.banks_client),
+            &payer,
+            &payer_pkey,
+            &rent
+        );
+        let mint_pkey: Pubkey = mint_fixture.create_and_intiialize_mint(mint_decimals, &latest_blockhash).await?;
+        let ata_pda: Pubkey = mint_fixture.create_and_intiialize_ata(&mint_pkey, &latest_blockhash).await?;
+        mint_fixture.mint_to_ata(&mint_pkey, &ata_pda, mint_amount, &latest_blockhash).await?;
+
+        let vesting_strategy: LinearVestingStrategy = LinearVestingStrategy::new_without_cliff(240, 60);
+        let mut program_client: ProgramClient = ProgramClient::new(ProgramClientTransport::ProgramTest(&mut context), &payer, mint_pkey, SPL_TOKEN_2022_ID);
+        program_client.initialize(mint_amount, 1_000, &vesting_strategy, TransferFeePayer::Recipient).await?;
+        program_client.buy(100).await?;
+
+        mints.push(mint_pkey);
+    }
+
+    let mut program_client: ProgramClient = ProgramClient::new(ProgramClientTransport::ProgramTest(&mut context), &buyer, mints[0], SPL_TOKEN_2022_ID);
+    program_client.buy(50).await?;
+
+    // 1. Owner & Mint are stored at the fixed offsets
+    let vesting_account: IDOVestingAccount = program_client.fetch_vesting(&buyer.pubkey()).await?;
+    assert_eq!((vesting_account.owner, vesting_account.mint), (buyer.pubkey(), mints[0]));
+
+    let vesting_data: Vec<u8> = program_client.fetch_account_data(&program_client.vesting_pda(&buyer.pubkey())).await?;
+    assert_eq!(&vesting_data[IDOVestingAccount::OWNER_OFFSET..IDOVestingAccount::OWNER_OFFSET + 32], buyer.pubkey().as_ref());
+    assert_eq!(&vesting_data[IDOVestingAccount::MINT_OFFSET..IDOVestingAccount::MINT_OFFSET + 32], mints[0].as_ref());
+
+    // 2. getProgramAccounts isn't provided by BanksClient
+    let result = program_client.list_idos().await;
+    assert!(matches!(result, Err(ProgramClientError::UnsupportedMethod("getProgramAccounts"))));
+
+    // 3. The filters are applied the same way the RPC node does to every account of the test
+    let mut addresses: Vec<Pubkey> = Vec::new();
+    for mint_pkey in &mints {
+        addresses.extend([
+            pda::find_config(mint_pkey).0,
+            pda::find_treasury(mint_pkey).0,
+            pda::find_vesting(&payer_pkey, mint_pkey).0,
+            pda::find_vesting(&buyer.pubkey(), mint_pkey).0,
+            *mint_pkey
+        ]);
+    }
+
+    let ProgramClientTransport::ProgramTest(context) = program_client.transport() else {
+        unreachable!();
+    };
+    let mut accounts: Vec<(Pubkey, AccountSharedData)> = Vec::new();
+    for address in addresses {
+        if let Some(account) = context.banks_client.get_account(address).await? {
+            accounts.push((address, account.into()));
+        }
+    }
+
+    // `allows` is deprecated in favour of `solana_rpc::filter::filter_allows`, which isn't worth pulling the RPC node for
+    #[allow(deprecated)]
+    let filter_accounts = |filters: Vec<RpcFilterType>| -> Vec<Pubkey> {
+        accounts
+            .iter()
+            .filter(|(_, account)| filters.iter().all(|filter| filter.allows(account)))
+            .map(|(address, _)| *address)
+            .collect()
+    };
+
+    assert_eq!(filter_accounts(client::ido_filters()), [pda::find_config(&mints[0]).0, pda::find_config(&mints[1]).0]);
+    assert_eq!(
+        filter_accounts(client::vesting_filters_for_mint(&mints[0])),
+        [pda::find_vesting(&payer_pkey, &mints[0]).0, pda::find_vesting(&buyer.pubkey(), &mints[0]).0]
+    );
+    assert_eq!(filter_accounts(client::vesting_filters_for_mint(&mints[1])), [pda::find_vesting(&payer_pkey, &mints[1]).0]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_batch_auction() -> Result<(), MintFixtureError> {
+    let program: ProgramTest = ProgramTest::new(
+        "ido_with_vesting", 
+        IDO_PROGRAM_ID,
+        processor!(entrypoint::process_instruction)
+    );
+
+    let mut context: ProgramTestContext = program.start_with_context().await;
+    let payer: Keypair = context.payer.insecure_clone();
+    let payer_pkey: Pubkey = payer.pubkey();
+    let rent: Rent = context.banks_client.get_sysvar::<Rent>().await?;
+    let latest_blockhash: Hash = context.last_blockhash;
+
+    // 0. Create & Initialize Mint Account; Create & Initialize ATA; Mint tokens to ATA
+    let mint_fixture: MintFixture = MintFixture::new(
+        MintFixtureClient::Banks(&context.banks_client),
+        &payer,
+        &payer_pkey,
+        &rent
+    );
+    let mint_decimals: u8 = 9;
+    let supply: u64 = 1_000;
+    let mint_amount: u64 = supply * 10u64.pow(mint_decimals as u32);
+
+    let mint_pkey: Pubkey = mint_fixture.create_and_intiialize_mint(mint_decimals, &latest_blockhash).await?;
+    let ata_pda: Pubkey = mint_fixture.create_and_intiialize_ata(&mint_pkey, &latest_blockhash).await?;
+    mint_fixture.mint_to_ata(&mint_pkey, &ata_pda, mint_amount, &latest_blockhash).await?;
+
+    // 1. Initialize the auction, which sells the whole supply
+    let treasury_pda: Pubkey = pda::find_treasury(&mint_pkey).0;
+    let config_pda: Pubkey = pda::find_config(&mint_pkey).0;
+    let auction_pda: Pubkey = pda::find_auction(&mint_pkey).0;
 
-    let treasury_pda: Pubkey = derive_program_pda(&[
-        IDO_TREASURY_ACCOUNT_SEED,
-        mint_pkey.as_ref()
-    ]).0;
-    let config_pda: Pubkey = derive_program_pda(&[
-        IDO_CONFIG_ACCOUNT_SEED,
-        treasury_pda.as_ref()
-    ]).0;
+    let now_ts: i64 = context.banks_client.get_sysvar::<Clock>().await?.unix_timestamp;
+    let bidding_end_ts: i64 = now_ts + 60;
+    let (min_price, tick_size) = (1_000u32, 100u32);
+    let vesting_strategy: LinearVestingStrategy = LinearVestingStrategy {
+        cliff_end_ts: 0,  // vesting starts right after the bidding window is closed
+        vesting_end_ts: bidding_end_ts + 60 * 5,
+        unlock_period: 60
+    };
 
+    // Zero price is the auction mode marker, so the fixed-price sale can't be initialized with it
     let initialize_ido_ix: Instruction = instruction::create_initialize_with_vesting(
-        transfer_amount, 
-        lamports_per_token, 
+        mint_amount, 
+        0, 
         &vesting_strategy, 
+        TransferFeePayer::Recipient, 
         &payer_pkey, 
         &ata_pda, 
         &treasury_pda, 
         &config_pda, 
+        &mint_pkey, 
+        &SPL_TOKEN_2022_ID
+    );
+    let err = process_ixs(&mut context, &[initialize_ido_ix], &payer).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(IDOProgramError::LamportsPerTokenMustNotEqualZero as u32));
+
+    let initialize_auction_ix: Instruction = instruction::create_initialize_auction(
+        mint_amount, 
+        min_price, 
+        tick_size, 
+        bidding_end_ts, 
+        &vesting_strategy, 
+        TransferFeePayer::Recipient,
+        &payer_pkey, 
+        &ata_pda, 
+        &auction_pda, 
+        &treasury_pda, 
+        &config_pda, 
+        &mint_pkey,
+        &SPL_TOKEN_2022_ID
+    );
+    process_ixs(&mut context, &[initialize_auction_ix], &payer).await?;
+
+    // 2. Fixed-price buy must be rejected in auction mode
+    let payer_vesting_pda: Pubkey = pda::find_vesting(&payer_pkey, &mint_pkey).0;
+    let buy_ix: Instruction = instruction::create_buy_with_vesting(
+        1, 
+        &payer_pkey, 
+        &payer_vesting_pda, 
+        &treasury_pda, 
+        &config_pda, 
         &mint_pkey
     );
+    let err = process_ixs(&mut context, &[buy_ix], &payer).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(IDOProgramError::BuyNotAllowedInAuctionMode as u32));
+
+    // 3. Place bids. Demand book: 1_300 => 600, 1_200 => 900, 1_100 => 200.
+    // Cumulative demand covers the supply at 1_200, so the bids at 1_200 share the rest 400 tokens pro-rata.
+    let bids: [(u64, u32); 4] = [(600, 1_300), (500, 1_200), (400, 1_200), (200, 1_100)];
+    let mut bidders: Vec<Keypair> = Vec::with_capacity(bids.len());
+
+    for (quantity, max_price) in bids {
+        let bidder: Keypair = Keypair::new();
+        let fund_tx: Transaction = system_transaction::transfer(&payer, &bidder.pubkey(), LAMPORTS_PER_SOL, context.last_blockhash);
+        process_tx(&context.banks_client, fund_tx).await?;
+
+        let (bid_pda, bid_bump) = pda::find_bid(&bidder.pubkey(), &mint_pkey);
+        let place_bid_ix: Instruction = instruction::create_place_bid(
+            quantity, 
+            max_price, 
+            &bidder.pubkey(), 
+            &bid_pda, 
+            bid_bump, 
+            &auction_pda, 
+            &treasury_pda, 
+            &config_pda, 
+            &mint_pkey
+        );
+        process_ixs(&mut context, &[place_bid_ix], &bidder).await?;
 
-    // 2. Craft InitializeIDOWithVesting transaction
-    let message: Message = Message::new(&[initialize_ido_ix], Some(&payer_pkey));
-    let mut initialize_ido_tx: Transaction = Transaction::new_unsigned(message);
+        bidders.push(bidder);
+    }
+
+    // off-grid price must be rejected
+    let (bid_pda, bid_bump) = pda::find_bid(&payer_pkey, &mint_pkey);
+    let place_bid_ix: Instruction = instruction::create_place_bid(1, 1_150, &payer_pkey, &bid_pda, bid_bump, &auction_pda, &treasury_pda, &config_pda, &mint_pkey);
+    let err = process_ixs(&mut context, &[place_bid_ix], &payer).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(IDOProgramError::InvalidBidPrice as u32));
+
+    // Bid PDA must be derived with the provided bump
+    let place_bid_ix: Instruction = instruction::create_place_bid(1, 1_200, &payer_pkey, &bid_pda, bid_bump - 1, &auction_pda, &treasury_pda, &config_pda, &mint_pkey);
+    assert!(process_ixs(&mut context, &[place_bid_ix], &payer).await.is_err());
+
+    // 4. Settling is possible only after the bidding window is closed
+    let settle_auction_ix: Instruction = instruction::create_settle_auction(&auction_pda, &treasury_pda, &config_pda, &mint_pkey);
+    let err = process_ixs(&mut context, std::slice::from_ref(&settle_auction_ix), &payer).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(IDOProgramError::BiddingIsActive as u32));
+
+    let mut clock: Clock = context.banks_client.get_sysvar::<Clock>().await?;
+    clock.unix_timestamp = bidding_end_ts;
+    context.set_sysvar(&clock);
+
+    process_ixs(&mut context, &[settle_auction_ix], &payer).await?;
+
+    let auction_account: IDOAuctionAccount = IDOAuctionAccount::unpack(
+        &context.banks_client.get_account(auction_pda).await?.unwrap().data
+    ).unwrap();
+    assert_eq!(auction_account.clearing_price, 1_200);
+    assert_eq!(auction_account.clearing_level_fill, 400);
+
+    // 5. Settle every bid: winners get vesting positions at 1_200, the rest of the escrow is refunded.
+    let expected_fills: [u64; 4] = [600, 500 * 400 / 900, 400 * 400 / 900, 0];
+    let clearing_price: u64 = 1_200;
+    let config_balance_before: u64 = context.banks_client.get_balance(config_pda).await?;
+    let mut total_cost: u64 = 0;
+
+    for ((bidder, (quantity, max_price)), expected_fill) in bidders.iter().zip(bids).zip(expected_fills) {
+        let bidder_pkey: Pubkey = bidder.pubkey();
+        let bid_pda: Pubkey = pda::find_bid(&bidder_pkey, &mint_pkey).0;
+        let vesting_pda: Pubkey = pda::find_vesting(&bidder_pkey, &mint_pkey).0;
+
+        let bidder_balance_before: u64 = context.banks_client.get_balance(bidder_pkey).await?;
+        let bid_rent: u64 = rent.minimum_balance(IDOBidAccount::LEN);
+
+        let settle_bid_ix: Instruction = instruction::create_settle_bid(
+            &payer_pkey, 
+            &bidder_pkey, 
+            &bid_pda, 
+            &vesting_pda, 
+            &auction_pda, 
+            &treasury_pda, 
+            &config_pda, 
+            &mint_pkey
+        );
+        process_ixs(&mut context, &[settle_bid_ix], &payer).await?;
+
+        let cost: u64 = expected_fill * clearing_price;
+        let escrow: u64 = quantity * max_price as u64;
+        total_cost += cost;
+
+        assert_eq!(context.banks_client.get_balance(bidder_pkey).await?, bidder_balance_before + bid_rent + escrow - cost);
+        assert!(context.banks_client.get_account(bid_pda).await?.is_none());
+
+        match context.banks_client.get_account(vesting_pda).await? {
+            Some(vesting_account) => {
+                let vesting_account: IDOVestingAccount = IDOVestingAccount::unpack(&vesting_account.data).unwrap();
+                assert_eq!(vesting_account.bought_amount, expected_fill);
+            },
+            None => assert_eq!(expected_fill, 0)
+        }
+    }
+
+    assert_eq!(context.banks_client.get_balance(config_pda).await?, config_balance_before + total_cost);
 
-    // 3. Sign InitializeIDOWithVesting tx and send it
-    initialize_ido_tx.sign(&[&payer], latest_blockhash);
-    banks_client.process_transaction(initialize_ido_tx).await?;
+    // every fill is accounted as sold, the oversubscribed auction keeps the reserve of the whole supply
+    let config_account: IDOConfigAccount = IDOConfigAccount::unpack(&context.banks_client.get_account(config_pda).await?.unwrap().data).unwrap();
+    assert_eq!(config_account.sold_amount, expected_fills.iter().sum::<u64>());
+    assert_eq!(config_account.reserved_amount, mint_amount);
 
-    // 4. Derive VestingAccount PDA
-    let (vesting_account, _vesting_bump) = Pubkey::find_program_address(
-        &[
-            IDO_VESTING_ACCOUNT_SEED,
-            payer_pkey.as_ref(),
-            mint_pkey.as_ref()
-        ], 
-        &IDO_PROGRAM_ID
+    // 6. The authority withdraws the paid costs, nothing is unsold
+    let mut clock: Clock = context.banks_client.get_sysvar::<Clock>().await?;
+    clock.unix_timestamp = vesting_strategy.vesting_end_ts;
+    context.set_sysvar(&clock);
+
+    let withdraw_ix: Instruction = instruction::create_withdraw(&payer_pkey, &ata_pda, &treasury_pda, &config_pda, &mint_pkey, &SPL_TOKEN_2022_ID);
+    let logs: Vec<String> = process_ixs_with_logs(&mut context, &[withdraw_ix], &payer).await?;
+    assert_eq!(IDOEvent::parse_logs(&logs), vec![
+        IDOEvent::Withdrawn { mint: mint_pkey, authority: payer_pkey, lamports: total_cost, amount: 0 }
+    ]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_undersubscribed_auction_withdraw() -> Result<(), Box<dyn std::error::Error>> {
+    let program: ProgramTest = ProgramTest::new(
+        "ido_with_vesting", 
+        IDO_PROGRAM_ID,
+        processor!(entrypoint::process_instruction)
     );
 
-    // 5. Craft BuyWithVesting instruction
-    let buy_amount: u64 = 17_000_000;
+    let mut context: ProgramTestContext = program.start_with_context().await;
+    let payer: Keypair = context.payer.insecure_clone();
+    let payer_pkey: Pubkey = payer.pubkey();
+    let rent: Rent = context.banks_client.get_sysvar::<Rent>().await?;
+    let latest_blockhash: Hash = context.last_blockhash;
 
-    let buy_ix: Instruction = instruction::create_buy_with_vesting(
-        buy_amount, 
+    // 0. Create & Initialize Mint Account; Create & Initialize ATA; Mint tokens to ATA
+    let mint_fixture: MintFixture = MintFixture::new(
+        MintFixtureClient::Banks(&context.banks_client),
+        &payer,
+        &payer_pkey,
+        &rent
+    );
+    let mint_decimals: u8 = 6;
+    let mint_amount: u64 = to_base_units(1_000, mint_decimals)?;
+
+    let mint_pkey: Pubkey = mint_fixture.create_and_intiialize_mint(mint_decimals, &latest_blockhash).await?;
+    let ata_pda: Pubkey = mint_fixture.create_and_intiialize_ata(&mint_pkey, &latest_blockhash).await?;
+    mint_fixture.mint_to_ata(&mint_pkey, &ata_pda, mint_amount, &latest_blockhash).await?;
+
+    // 1. Initialize the auction of 1_000 tokens, bid for 300 of them only
+    let treasury_pda: Pubkey = pda::find_treasury(&mint_pkey).0;
+    let config_pda: Pubkey = pda::find_config(&mint_pkey).0;
+    let auction_pda: Pubkey = pda::find_auction(&mint_pkey).0;
+    let (bid_pda, bid_bump) = pda::find_bid(&payer_pkey, &mint_pkey);
+    let vesting_pda: Pubkey = pda::find_vesting(&payer_pkey, &mint_pkey).0;
+
+    let now_ts: i64 = context.banks_client.get_sysvar::<Clock>().await?.unix_timestamp;
+    let bidding_end_ts: i64 = now_ts + 60;
+    let vesting_strategy: LinearVestingStrategy = LinearVestingStrategy {
+        cliff_end_ts: 0,
+        vesting_end_ts: bidding_end_ts + 60 * 5,
+        unlock_period: 60
+    };
+
+    let initialize_auction_ix: Instruction = instruction::create_initialize_auction(
+        mint_amount, 
+        1_000, 
+        100, 
+        bidding_end_ts, 
+        &vesting_strategy, 
+        TransferFeePayer::Recipient,
+        &payer_pkey, 
+        &ata_pda, 
+        &auction_pda, 
+        &treasury_pda, 
+        &config_pda, 
+        &mint_pkey,
+        &SPL_TOKEN_2022_ID
+    );
+    let place_bid_ix: Instruction = instruction::create_place_bid(
+        300, 
+        1_000, 
         &payer_pkey, 
-        &vesting_account, 
+        &bid_pda, 
+        bid_bump, 
+        &auction_pda, 
         &treasury_pda, 
         &config_pda, 
         &mint_pkey
     );
+    process_ixs(&mut context, &[initialize_auction_ix, place_bid_ix], &payer).await?;
 
-    // 6. Craft BuyWithVesting transaction
-    let message: Message = Message::new(&[buy_ix], Some(&payer_pkey));
-    let mut buy_tx: Transaction = Transaction::new_unsigned(message);
+    // 2. Settling the auction keeps the reserve only for the filled supply
+    let mut clock: Clock = context.banks_client.get_sysvar::<Clock>().await?;
+    clock.unix_timestamp = bidding_end_ts;
+    context.set_sysvar(&clock);
 
-    // 7. Sign BuyWithVesting tx and send it
-    buy_tx.sign(&[&payer], latest_blockhash);
-    banks_client.process_transaction(buy_tx).await?;
-    
-    // 8. Derive recipient ATA and Craft Claim instruction.
-    // I decided not to force the instruction to always interpriate `signer` as the `recipient`,
-    // so the caller can pass any valid `recipient` and `recipient_ata` beside `signer` and `signer_ata`.
-    // 8.1 Recipient is a new wallet
-    let new_wallet: Pubkey = Pubkey::new_unique();
-    let recipient_ata: Pubkey = Pubkey::find_program_address(
-        &[
-            new_wallet.as_ref(),
-            SPL_TOKEN_2022_ID.as_ref(),
-            mint_pkey.as_ref()
-        ], 
-        &ATA_PROGRAM_ID
-    ).0;
-    let claim_ix: Instruction = instruction::create_claim(
+    let settle_auction_ix: Instruction = instruction::create_settle_auction(&auction_pda, &treasury_pda, &config_pda, &mint_pkey);
+    let settle_bid_ix: Instruction = instruction::create_settle_bid(
         &payer_pkey, 
-        &new_wallet, 
-        &recipient_ata, 
-        &vesting_account, 
+        &payer_pkey, 
+        &bid_pda, 
+        &vesting_pda, 
+        &auction_pda, 
         &treasury_pda, 
         &config_pda, 
         &mint_pkey
     );
+    process_ixs(&mut context, &[settle_auction_ix, settle_bid_ix], &payer).await?;
+
+    let config_account: IDOConfigAccount = IDOConfigAccount::unpack(&context.banks_client.get_account(config_pda).await?.unwrap().data).unwrap();
+    assert_eq!(config_account.sold_amount, 300);
+    assert_eq!(config_account.reserved_amount, to_base_units(300, mint_decimals)?);
+
+    // 3. Once the vesting is over, the authority withdraws the cost of the fill & the supply nobody bid for
+    clock.unix_timestamp = vesting_strategy.vesting_end_ts;
+    context.set_sysvar(&clock);
+
+    let withdraw_ix: Instruction = instruction::create_withdraw(&payer_pkey, &ata_pda, &treasury_pda, &config_pda, &mint_pkey, &SPL_TOKEN_2022_ID);
+    let logs: Vec<String> = process_ixs_with_logs(&mut context, &[withdraw_ix], &payer).await?;
+    assert_eq!(IDOEvent::parse_logs(&logs), vec![
+        IDOEvent::Withdrawn { mint: mint_pkey, authority: payer_pkey, lamports: 300 * 1_000, amount: to_base_units(700, mint_decimals)? }
+    ]);
 
-    // 8.2 Recipient is a signer
-    // let recipient_ata: Pubkey = Pubkey::find_program_address(
-    //     &[
-    //         payer_pkey.as_ref(),
-    //         SPL_TOKEN_2022_ID.as_ref(),
-    //         mint_pkey.as_ref()
-    //     ], 
-    //     &ATA_PROGRAM_ID
-    // ).0;
-    // let claim_ix: Instruction = instruction::create_claim(
-    //     &payer_pkey, 
-    //     &payer_pkey, 
-    //     &recipient_ata, 
-    //     &vesting_account, 
-    //     &treasury_pda, 
-    //     &config_pda, 
-    //     &mint_pkey
-    // );
-
-    // 9. Craft Claim transaction
-    let message: Message = Message::new(&[claim_ix], Some(&payer_pkey));
-    let mut claim_tx: Transaction = Transaction::new_unsigned(message);
-
-    // 10. Sign Claim tx and send it 
-    claim_tx.sign(&[&payer], latest_blockhash);
-    banks_client.process_transaction(claim_tx).await?;
+    // 4. The filled supply is still claimable
+    let claim_ix: Instruction = instruction::create_claim_for_mint(&payer_pkey, &payer_pkey, &mint_pkey, &SPL_TOKEN_2022_ID);
+    process_ixs(&mut context, &[claim_ix], &payer).await?;
+
+    let token_amount = |data: &[u8]| StateWithExtensions::<Account>::unpack(data).unwrap().base.amount;
+    assert_eq!(token_amount(&context.banks_client.get_account(ata_pda).await?.unwrap().data), mint_amount);
+    assert_eq!(token_amount(&context.banks_client.get_account(treasury_pda).await?.unwrap().data), 0);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_transfer_fee_mint() -> Result<(), MintFixtureError> {
+    for transfer_fee_payer in [TransferFeePayer::Recipient, TransferFeePayer::Treasury] {
+        assert_claim_with_transfer_fee(transfer_fee_payer).await?;
+    }
 
     Ok(())
-}
\ No newline at end of file
+}
+
+async fn assert_claim_with_transfer_fee(transfer_fee_payer: TransferFeePayer) -> Result<(), MintFixtureError> {
+    let program: ProgramTest = ProgramTest::new(
+        "ido_with_vesting", 
+        IDO_PROGRAM_ID,
+        processor!(entrypoint::process_instruction)
+    );
+
+    let mut context: ProgramTestContext = program.start_with_context().await;
+    let payer: Keypair = context.payer.insecure_clone();
+    let payer_pkey: Pubkey = payer.pubkey();
+    let rent: Rent = context.banks_client.get_sysvar::<Rent>().await?;
+    let latest_blockhash: Hash = context.last_blockhash;
+
+    // 0. Create Mint with 1% transfer fee; Create & Initialize ATA; Mint tokens to ATA
+    let mint_fixture: MintFixture = MintFixture::new(
+        MintFixtureClient::Banks(&context.banks_client),
+        &payer,
+        &payer_pkey,
+        &rent
+    );
+    let mint_decimals: u8 = 9;
+    let mint_amount: u64 = 1_000_000 * 10u64.pow(mint_decimals as u32);
+
+    let mint_pkey: Pubkey = mint_fixture.create_and_initialize_mint_with_transfer_fee(mint_decimals, 100, u64::MAX, &latest_blockhash).await?;
+    let ata_pda: Pubkey = mint_fixture.create_and_intiialize_ata(&mint_pkey, &latest_blockhash).await?;
+    mint_fixture.mint_to_ata(&mint_pkey, &ata_pda, mint_amount, &latest_blockhash).await?;
+
+    let treasury_pda: Pubkey = pda::find_treasury(&mint_pkey).0;
+    let config_pda: Pubkey = pda::find_config(&mint_pkey).0;
+    let vesting_pda: Pubkey = pda::find_vesting(&payer_pkey, &mint_pkey).0;
+
+    // 1. The treasury must record the deposit without the withheld fee
+    let vesting_strategy: LinearVestingStrategy = LinearVestingStrategy::new_without_cliff(60 * 5, 60);
+    let initialize_ido_ix: Instruction = instruction::create_initialize_with_vesting(
+        mint_amount, 
+        1_000, 
+        &vesting_strategy, 
+        transfer_fee_payer,
+        &payer_pkey, 
+        &ata_pda, 
+        &treasury_pda, 
+        &config_pda, 
+        &mint_pkey,
+        &SPL_TOKEN_2022_ID
+    );
+    process_ixs(&mut context, &[initialize_ido_ix], &payer).await?;
+
+    let epoch: u64 = context.banks_client.get_sysvar::<Clock>().await?.epoch;
+    let mint_account = context.banks_client.get_account(mint_pkey).await?.unwrap();
+    let transfer_fee_config: TransferFeeConfig = *StateWithExtensions::<Mint>::unpack(&mint_account.data)
+        .unwrap()
+        .get_extension::<TransferFeeConfig>()
+        .unwrap();
+
+    let config_account: IDOConfigAccount = IDOConfigAccount::unpack(
+        &context.banks_client.get_account(config_pda).await?.unwrap().data
+    ).unwrap();
+    let deposit_fee: u64 = transfer_fee_config.calculate_epoch_fee(epoch, mint_amount).unwrap();
+    assert_eq!(config_account.deposited_amount, mint_amount - deposit_fee);
+    assert_eq!(config_account.transfer_fee_payer(), Ok(transfer_fee_payer));
+
+    // the treasury is sized for the account extensions required by the mint & protected with ImmutableOwner
+    let treasury_account = context.banks_client.get_account(treasury_pda).await?.unwrap();
+    let treasury_extension_types: Vec<ExtensionType> = StateWithExtensions::<Account>::unpack(&treasury_account.data)
+        .unwrap()
+        .get_extension_types()
+        .unwrap();
+    assert!(treasury_extension_types.contains(&ExtensionType::TransferFeeAmount));
+    assert!(treasury_extension_types.contains(&ExtensionType::ImmutableOwner));
+
+    // 2. Buy & claim the 1st unlock
+    let buy_amount: u64 = 1_000;
+    let buy_ix: Instruction = instruction::create_buy_with_vesting(buy_amount, &payer_pkey, &vesting_pda, &treasury_pda, &config_pda, &mint_pkey);
+    process_ixs(&mut context, &[buy_ix], &payer).await?;
+
+    let recipient: Pubkey = Pubkey::new_unique();
+    let recipient_ata: Pubkey = pda::find_ata(&recipient, &mint_pkey, &SPL_TOKEN_2022_ID).0;
+    let claim_ix: Instruction = instruction::create_claim(
+        &payer_pkey, 
+        &recipient, 
+        &recipient_ata, 
+        &vesting_pda, 
+        &treasury_pda, 
+        &config_pda, 
+        &mint_pkey,
+        &SPL_TOKEN_2022_ID
+    );
+    process_ixs(&mut context, &[claim_ix], &payer).await?;
+
+    // 3. Recipient receives either the net or the exact claimed portion
+    let claimed_amount: u64 = buy_amount / config_account.unlocks as u64 * 10u64.pow(mint_decimals as u32);
+    let recipient_ata_account = context.banks_client.get_account(recipient_ata).await?.unwrap();
+    let received_amount: u64 = StateWithExtensions::<Account>::unpack(&recipient_ata_account.data).unwrap().base.amount;
+
+    let expected_amount: u64 = match transfer_fee_payer {
+        TransferFeePayer::Recipient => claimed_amount - transfer_fee_config.calculate_epoch_fee(epoch, claimed_amount).unwrap(),
+        TransferFeePayer::Treasury => claimed_amount
+    };
+    assert_eq!(received_amount, expected_amount);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_transfer_fee_treasury_payer_sell_out() -> Result<(), Box<dyn std::error::Error>> {
+    let program: ProgramTest = ProgramTest::new(
+        "ido_with_vesting", 
+        IDO_PROGRAM_ID,
+        processor!(entrypoint::process_instruction)
+    );
+
+    let mut context: ProgramTestContext = program.start_with_context().await;
+    let payer: Keypair = context.payer.insecure_clone();
+    let payer_pkey: Pubkey = payer.pubkey();
+    let rent: Rent = context.banks_client.get_sysvar::<Rent>().await?;
+    let latest_blockhash: Hash = context.last_blockhash;
+
+    // 0. Mint without decimals, so the fee of every claim is rounded up by the whole token
+    let mint_fixture: MintFixture = MintFixture::new(
+        MintFixtureClient::Banks(&context.banks_client),
+        &payer,
+        &payer_pkey,
+        &rent
+    );
+    let mint_amount: u64 = 1_000_000;
+
+    let mint_pkey: Pubkey = mint_fixture.create_and_initialize_mint_with_transfer_fee(0, 100, u64::MAX, &latest_blockhash).await?;
+    let ata_pda: Pubkey = mint_fixture.create_and_intiialize_ata(&mint_pkey, &latest_blockhash).await?;
+    mint_fixture.mint_to_ata(&mint_pkey, &ata_pda, mint_amount, &latest_blockhash).await?;
+
+    let config_pda: Pubkey = pda::find_config(&mint_pkey).0;
+
+    let now_ts: i64 = context.banks_client.get_sysvar::<Clock>().await?.unix_timestamp;
+    let vesting_strategy: LinearVestingStrategy = LinearVestingStrategy {
+        cliff_end_ts: now_ts,
+        vesting_end_ts: now_ts + 60 * 5,
+        unlock_period: 60
+    };
+    let initialize_ido_ix: Instruction = instruction::create_initialize_with_vesting_for_mint(
+        mint_amount, 
+        1, 
+        &vesting_strategy, 
+        TransferFeePayer::Treasury, 
+        &payer_pkey, 
+        &mint_pkey, 
+        &SPL_TOKEN_2022_ID
+    );
+    process_ixs(&mut context, &[initialize_ido_ix], &payer).await?;
+
+    let deposited_amount: u64 = IDOConfigAccount::unpack(&context.banks_client.get_account(config_pda).await?.unwrap().data)?.deposited_amount;
+
+    // 1. Sell out the supply: the buyers take turns buying the largest chunk that still fits, until not even a single token fits
+    let buyers: Vec<Keypair> = (0..3).map(|_| Keypair::new()).collect();
+    let mut bought_amounts: Vec<u64> = vec![0; buyers.len()];
+
+    for buyer in &buyers {
+        let fund_tx: Transaction = system_transaction::transfer(&payer, &buyer.pubkey(), LAMPORTS_PER_SOL, context.last_blockhash);
+        process_tx(&context.banks_client, fund_tx).await?;
+    }
+
+    let (mut chunk, mut buys) = (deposited_amount / 2, 0);
+
+    while chunk > 0 {
+        let buyer: &Keypair = &buyers[buys % buyers.len()];
+        let buy_ix: Instruction = instruction::create_buy_with_vesting_for_mint(chunk, &buyer.pubkey(), &mint_pkey);
+
+        match process_ixs(&mut context, &[buy_ix], buyer).await {
+            Ok(()) => {
+                bought_amounts[buys % buyers.len()] += chunk;
+                buys += 1;
+            },
+            Err(err) => {
+                assert_eq!(custom_error_code(&err), Some(IDOProgramError::SupplyExceeded as u32));
+                chunk /= 2;
+            }
+        }
+    }
+
+    // the supply is sold out up to the reserve for the fees the treasury pays on every claim
+    let config_account: IDOConfigAccount = IDOConfigAccount::unpack(&context.banks_client.get_account(config_pda).await?.unwrap().data)?;
+    assert_eq!(config_account.sold_amount, bought_amounts.iter().sum::<u64>());
+    assert!(config_account.reserved_amount <= deposited_amount);
+    assert!(config_account.sold_amount > deposited_amount / 100 * 98);
+
+    // 2. Claim every unlock, so every claim is grossed up (& rounded up) separately
+    let mut clock: Clock = context.banks_client.get_sysvar::<Clock>().await?;
+
+    let unlock_timestamps: Vec<i64> = vesting_strategy.unlock_schedule()?
+        .map(|(unlock_ts, _)| unlock_ts)
+        .filter(|unlock_ts| *unlock_ts < vesting_strategy.vesting_end_ts)
+        .collect();
+
+    for unlock_ts in unlock_timestamps {
+        clock.unix_timestamp = unlock_ts;
+        context.set_sysvar(&clock);
+
+        for buyer in &buyers {
+            let claim_ix: Instruction = instruction::create_claim_for_mint(&buyer.pubkey(), &buyer.pubkey(), &mint_pkey, &SPL_TOKEN_2022_ID);
+            process_ixs(&mut context, &[claim_ix], buyer).await?;
+        }
+    }
+
+    // 3. Withdraw the unsold supply at the end of the vesting, the rest still covers the final claims
+    clock.unix_timestamp = vesting_strategy.vesting_end_ts;
+    context.set_sysvar(&clock);
+
+    let withdraw_ix: Instruction = instruction::create_withdraw_for_mint(&payer_pkey, &mint_pkey, &SPL_TOKEN_2022_ID);
+    process_ixs(&mut context, &[withdraw_ix], &payer).await?;
+
+    // the authority receives the unsold supply minus the 1% fee (rounded up)
+    let unsold_amount: u64 = deposited_amount - config_account.reserved_amount;
+    let token_amount = |data: &[u8]| StateWithExtensions::<Account>::unpack(data).unwrap().base.amount;
+    assert_eq!(
+        token_amount(&context.banks_client.get_account(ata_pda).await?.unwrap().data), 
+        unsold_amount - unsold_amount.div_ceil(100)
+    );
+
+    for (buyer, bought_amount) in buyers.iter().zip(bought_amounts) {
+        let claim_ix: Instruction = instruction::create_claim_for_mint(&buyer.pubkey(), &buyer.pubkey(), &mint_pkey, &SPL_TOKEN_2022_ID);
+        process_ixs(&mut context, &[claim_ix], buyer).await?;
+
+        let buyer_ata: Pubkey = pda::find_ata(&buyer.pubkey(), &mint_pkey, &SPL_TOKEN_2022_ID).0;
+        assert_eq!(token_amount(&context.banks_client.get_account(buyer_ata).await?.unwrap().data), bought_amount);
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_classic_spl_token_mint() -> Result<(), MintFixtureError> {
+    let program: ProgramTest = ProgramTest::new(
+        "ido_with_vesting", 
+        IDO_PROGRAM_ID,
+        processor!(entrypoint::process_instruction)
+    );
+
+    let mut context: ProgramTestContext = program.start_with_context().await;
+    let payer: Keypair = context.payer.insecure_clone();
+    let payer_pkey: Pubkey = payer.pubkey();
+    let rent: Rent = context.banks_client.get_sysvar::<Rent>().await?;
+    let latest_blockhash: Hash = context.last_blockhash;
+
+    // 0. Create & Initialize the classic SPL Token Mint; Create & Initialize ATA; Mint tokens to ATA
+    let mint_fixture: MintFixture = MintFixture::new(
+        MintFixtureClient::Banks(&context.banks_client),
+        &payer,
+        &payer_pkey,
+        &rent
+    ).with_token_program(SPL_TOKEN_ID);
+    let mint_decimals: u8 = 6;
+    let mint_amount: u64 = 1_000_000 * 10u64.pow(mint_decimals as u32);
+
+    let mint_pkey: Pubkey = mint_fixture.create_and_intiialize_mint(mint_decimals, &latest_blockhash).await?;
+    let ata_pda: Pubkey = mint_fixture.create_and_intiialize_ata(&mint_pkey, &latest_blockhash).await?;
+    mint_fixture.mint_to_ata(&mint_pkey, &ata_pda, mint_amount, &latest_blockhash).await?;
+
+    let treasury_pda: Pubkey = pda::find_treasury(&mint_pkey).0;
+    let config_pda: Pubkey = pda::find_config(&mint_pkey).0;
+    let vesting_pda: Pubkey = pda::find_vesting(&payer_pkey, &mint_pkey).0;
+
+    // 1. Token program that doesn't own the mint must be rejected
+    let vesting_strategy: LinearVestingStrategy = LinearVestingStrategy::new_without_cliff(60 * 5, 60);
+    let create_initialize_ix = |token_program_id: &Pubkey| instruction::create_initialize_with_vesting(
+        mint_amount, 
+        1_000, 
+        &vesting_strategy, 
+        TransferFeePayer::Recipient,
+        &payer_pkey, 
+        &ata_pda, 
+        &treasury_pda, 
+        &config_pda, 
+        &mint_pkey,
+        token_program_id
+    );
+
+    let err = process_ixs(&mut context, &[create_initialize_ix(&SPL_TOKEN_2022_ID)], &payer).await.unwrap_err();
+    assert!(matches!(
+        err,
+        BanksClientError::TransactionError(TransactionError::InstructionError(_, InstructionError::IncorrectProgramId))
+    ));
+
+    // 2. Initialize, Buy & Claim with the classic SPL Token program
+    process_ixs(&mut context, &[create_initialize_ix(&SPL_TOKEN_ID)], &payer).await?;
+
+    let buy_amount: u64 = 1_000;
+    let buy_ix: Instruction = instruction::create_buy_with_vesting(buy_amount, &payer_pkey, &vesting_pda, &treasury_pda, &config_pda, &mint_pkey);
+    process_ixs(&mut context, &[buy_ix], &payer).await?;
+
+    let recipient: Pubkey = Pubkey::new_unique();
+    let recipient_ata: Pubkey = pda::find_ata(&recipient, &mint_pkey, &SPL_TOKEN_ID).0;
+    let claim_ix: Instruction = instruction::create_claim(
+        &payer_pkey, 
+        &recipient, 
+        &recipient_ata, 
+        &vesting_pda, 
+        &treasury_pda, 
+        &config_pda, 
+        &mint_pkey,
+        &SPL_TOKEN_ID
+    );
+    process_ixs(&mut context, &[claim_ix], &payer).await?;
+
+    let recipient_ata_account = context.banks_client.get_account(recipient_ata).await?.unwrap();
+    assert_eq!(recipient_ata_account.owner, SPL_TOKEN_ID);
+    assert_eq!(
+        StateWithExtensions::<Account>::unpack(&recipient_ata_account.data).unwrap().base.amount,
+        buy_amount / 5 * 10u64.pow(mint_decimals as u32)
+    );
+
+    // 3. The proceeds are held by the config, so they're withdrawn although SPL Token can't release the treasury's LAMPORTS
+    let withdraw_ix: Instruction = instruction::create_withdraw(&payer_pkey, &ata_pda, &treasury_pda, &config_pda, &mint_pkey, &SPL_TOKEN_ID);
+    let logs: Vec<String> = process_ixs_with_logs(&mut context, &[withdraw_ix], &payer).await?;
+    assert_eq!(IDOEvent::parse_logs(&logs), vec![
+        IDOEvent::Withdrawn { mint: mint_pkey, authority: payer_pkey, lamports: buy_amount * 1_000, amount: 0 }
+    ]);
+    assert_eq!(context.banks_client.get_balance(config_pda).await?, rent.minimum_balance(IDOConfigAccount::LEN));
+
+    Ok(())
+}
+
+/// Minimal transfer hook, which requires `TRANSFER_HOOK_EXTRA_ACCOUNT` to be forwarded on every transfer.
+const TRANSFER_HOOK_PROGRAM_ID: Pubkey = Pubkey::new_from_array([7; 32]);
+const TRANSFER_HOOK_EXTRA_ACCOUNT: Pubkey = Pubkey::new_from_array([8; 32]);
+
+fn process_transfer_hook_instruction(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _data: &[u8]
+) -> ProgramResult {
+    // Execute accounts: source, mint, destination, authority, validation PDA, extra accounts..
+    match accounts.get(5) {
+        Some(extra_account_info) if *extra_account_info.key == TRANSFER_HOOK_EXTRA_ACCOUNT => Ok(()),
+        _ => Err(ProgramError::NotEnoughAccountKeys)
+    }
+}
+
+#[tokio::test]
+async fn test_transfer_hook_mint() -> Result<(), MintFixtureError> {
+    let mut program: ProgramTest = ProgramTest::new(
+        "ido_with_vesting", 
+        IDO_PROGRAM_ID,
+        processor!(entrypoint::process_instruction)
+    );
+    program.add_program("transfer_hook", TRANSFER_HOOK_PROGRAM_ID, processor!(process_transfer_hook_instruction));
+
+    let mut context: ProgramTestContext = program.start_with_context().await;
+    let payer: Keypair = context.payer.insecure_clone();
+    let payer_pkey: Pubkey = payer.pubkey();
+    let rent: Rent = context.banks_client.get_sysvar::<Rent>().await?;
+    let latest_blockhash: Hash = context.last_blockhash;
+
+    // 0. Create Mint with TransferHook; Create & Initialize ATA; Mint tokens to ATA
+    let mint_decimals: u8 = 9;
+    let mint_amount: u64 = 1_000_000 * 10u64.pow(mint_decimals as u32);
+
+    let (mint_pkey, ata_pda) = {
+        let mint_fixture: MintFixture = MintFixture::new(
+            MintFixtureClient::Banks(&context.banks_client),
+            &payer,
+            &payer_pkey,
+            &rent
+        );
+
+        let mint_pkey: Pubkey = mint_fixture.create_and_initialize_mint_with_transfer_hook(mint_decimals, &TRANSFER_HOOK_PROGRAM_ID, &latest_blockhash).await?;
+        let ata_pda: Pubkey = mint_fixture.create_and_intiialize_ata(&mint_pkey, &latest_blockhash).await?;
+        mint_fixture.mint_to_ata(&mint_pkey, &ata_pda, mint_amount, &latest_blockhash).await?;
+
+        (mint_pkey, ata_pda)
+    };
+
+    // 1. Initialize the hook's validation PDA, which requires the single extra account
+    set_transfer_hook_validation_account(&mut context, &mint_pkey, &rent);
+
+    let treasury_pda: Pubkey = pda::find_treasury(&mint_pkey).0;
+    let config_pda: Pubkey = pda::find_config(&mint_pkey).0;
+    let vesting_pda: Pubkey = pda::find_vesting(&payer_pkey, &mint_pkey).0;
+
+    let banks_client: BanksClient = context.banks_client.clone();
+    let fetch_account_data = |address: Pubkey| {
+        let banks_client: BanksClient = banks_client.clone();
+        async move {
+            banks_client
+                .get_account(address)
+                .await
+                .map(|account| account.map(|account| account.data))
+                .map_err(AccountFetchError::from)
+        }
+    };
+
+    // 2. Deposit without the extra accounts must be rejected by the hook, resolved ones must pass
+    let vesting_strategy: LinearVestingStrategy = LinearVestingStrategy::new_without_cliff(60 * 5, 60);
+    let mut initialize_ido_ix: Instruction = instruction::create_initialize_with_vesting(
+        mint_amount, 
+        1_000, 
+        &vesting_strategy, 
+        TransferFeePayer::Recipient,
+        &payer_pkey, 
+        &ata_pda, 
+        &treasury_pda, 
+        &config_pda, 
+        &mint_pkey,
+        &SPL_TOKEN_2022_ID
+    );
+    assert!(process_ixs(&mut context, std::slice::from_ref(&initialize_ido_ix), &payer).await.is_err());
+
+    instruction::add_transfer_hook_accounts(&mut initialize_ido_ix, fetch_account_data).await.unwrap();
+    process_ixs(&mut context, &[initialize_ido_ix], &payer).await?;
+
+    // 3. Buy & Claim with the resolved extra accounts
+    let buy_amount: u64 = 1_000;
+    let buy_ix: Instruction = instruction::create_buy_with_vesting(buy_amount, &payer_pkey, &vesting_pda, &treasury_pda, &config_pda, &mint_pkey);
+    process_ixs(&mut context, &[buy_ix], &payer).await?;
+
+    let recipient: Pubkey = Pubkey::new_unique();
+    let recipient_ata: Pubkey = pda::find_ata(&recipient, &mint_pkey, &SPL_TOKEN_2022_ID).0;
+    let mut claim_ix: Instruction = instruction::create_claim(
+        &payer_pkey, 
+        &recipient, 
+        &recipient_ata, 
+        &vesting_pda, 
+        &treasury_pda, 
+        &config_pda, 
+        &mint_pkey,
+        &SPL_TOKEN_2022_ID
+    );
+    instruction::add_transfer_hook_accounts(&mut claim_ix, fetch_account_data).await.unwrap();
+    process_ixs(&mut context, &[claim_ix], &payer).await?;
+
+    let recipient_ata_account = context.banks_client.get_account(recipient_ata).await?.unwrap();
+    assert_eq!(
+        StateWithExtensions::<Account>::unpack(&recipient_ata_account.data).unwrap().base.amount,
+        buy_amount / 5 * 10u64.pow(mint_decimals as u32)
+    );
+
+    // 4. ProgramClient resolves the extra accounts on its own
+    let client_mint_pkey: Pubkey = {
+        let mint_fixture: MintFixture = MintFixture::new(
+            MintFixtureClient::Banks(&context.banks_client),
+            &payer,
+            &payer_pkey,
+            &rent
+        );
+
+        let mint_pkey: Pubkey = mint_fixture.create_and_initialize_mint_with_transfer_hook(mint_decimals, &TRANSFER_HOOK_PROGRAM_ID, &latest_blockhash).await?;
+        let ata_pda: Pubkey = mint_fixture.create_and_intiialize_ata(&mint_pkey, &latest_blockhash).await?;
+        mint_fixture.mint_to_ata(&mint_pkey, &ata_pda, mint_amount, &latest_blockhash).await?;
+
+        mint_pkey
+    };
+    set_transfer_hook_validation_account(&mut context, &client_mint_pkey, &rent);
+
+    let mut program_client: ProgramClient = ProgramClient::new(
+        ProgramClientTransport::ProgramTest(&mut context),
+        &payer,
+        client_mint_pkey,
+        SPL_TOKEN_2022_ID
+    );
+    program_client.initialize(mint_amount, 1_000, &vesting_strategy, TransferFeePayer::Recipient).await.unwrap();
+    program_client.buy(buy_amount).await.unwrap();
+    program_client.claim(&recipient).await.unwrap();
+
+    let recipient_ata: Pubkey = pda::find_ata(&recipient, &client_mint_pkey, &SPL_TOKEN_2022_ID).0;
+    let recipient_ata_data: Vec<u8> = program_client.fetch_account_data(&recipient_ata).await.unwrap();
+    assert_eq!(
+        StateWithExtensions::<Account>::unpack(&recipient_ata_data).unwrap().base.amount,
+        buy_amount / 5 * 10u64.pow(mint_decimals as u32)
+    );
+
+    Ok(())
+}
+
+/// Validation PDA of the test hook, which requires the single extra account.
+fn set_transfer_hook_validation_account(context: &mut ProgramTestContext, mint_pkey: &Pubkey, rent: &Rent) {
+    let validation_pda: Pubkey = get_extra_account_metas_address(mint_pkey, &TRANSFER_HOOK_PROGRAM_ID);
+    let extra_account_metas: [ExtraAccountMeta; 1] = [ExtraAccountMeta::new_with_pubkey(&TRANSFER_HOOK_EXTRA_ACCOUNT, false, false).unwrap()];
+    let validation_len: usize = ExtraAccountMetaList::size_of(extra_account_metas.len()).unwrap();
+    let mut validation_data: Vec<u8> = vec![0; validation_len];
+    ExtraAccountMetaList::init::<ExecuteInstruction>(&mut validation_data, &extra_account_metas).unwrap();
+
+    context.set_account(&validation_pda, &SolanaAccount {
+        lamports: rent.minimum_balance(validation_len),
+        data: validation_data,
+        owner: TRANSFER_HOOK_PROGRAM_ID,
+        executable: false,
+        rent_epoch: 0
+    }.into());
+}
+
+#[tokio::test]
+async fn test_recipient_account_extensions() -> Result<(), MintFixtureError> {
+    let program: ProgramTest = ProgramTest::new(
+        "ido_with_vesting", 
+        IDO_PROGRAM_ID,
+        processor!(entrypoint::process_instruction)
+    );
+
+    let mut context: ProgramTestContext = program.start_with_context().await;
+    let payer: Keypair = context.payer.insecure_clone();
+    let payer_pkey: Pubkey = payer.pubkey();
+    let rent: Rent = context.banks_client.get_sysvar::<Rent>().await?;
+    let latest_blockhash: Hash = context.last_blockhash;
+
+    // 0. Create Mint with DefaultAccountState (initially `Initialized`); Create & Initialize ATA; Mint tokens to ATA
+    let mint_decimals: u8 = 9;
+    let mint_amount: u64 = 1_000_000 * 10u64.pow(mint_decimals as u32);
+
+    let (mint_pkey, ata_pda) = {
+        let mint_fixture: MintFixture = MintFixture::new(
+            MintFixtureClient::Banks(&context.banks_client),
+            &payer,
+            &payer_pkey,
+            &rent
+        );
+
+        let mint_pkey: Pubkey = mint_fixture.create_and_initialize_mint_with_default_account_state(mint_decimals, AccountState::Initialized, &latest_blockhash).await?;
+        let ata_pda: Pubkey = mint_fixture.create_and_intiialize_ata(&mint_pkey, &latest_blockhash).await?;
+        mint_fixture.mint_to_ata(&mint_pkey, &ata_pda, mint_amount, &latest_blockhash).await?;
+
+        (mint_pkey, ata_pda)
+    };
+
+    let treasury_pda: Pubkey = pda::find_treasury(&mint_pkey).0;
+    let config_pda: Pubkey = pda::find_config(&mint_pkey).0;
+    let vesting_pda: Pubkey = pda::find_vesting(&payer_pkey, &mint_pkey).0;
+
+    // 1. Initialize IDO & Buy
+    let vesting_strategy: LinearVestingStrategy = LinearVestingStrategy::new_without_cliff(60 * 5, 60);
+    let initialize_ido_ix: Instruction = instruction::create_initialize_with_vesting(
+        mint_amount, 
+        1_000, 
+        &vesting_strategy, 
+        TransferFeePayer::Recipient,
+        &payer_pkey, 
+        &ata_pda, 
+        &treasury_pda, 
+        &config_pda, 
+        &mint_pkey,
+        &SPL_TOKEN_2022_ID
+    );
+    let buy_amount: u64 = 1_000;
+    let buy_ix: Instruction = instruction::create_buy_with_vesting(buy_amount, &payer_pkey, &vesting_pda, &treasury_pda, &config_pda, &mint_pkey);
+    process_ixs(&mut context, &[initialize_ido_ix, buy_ix], &payer).await?;
+
+    // 2. Create recipient token account, which requires incoming transfer memos & has CPI Guard enabled
+    let recipient: Keypair = Keypair::new();
+    let recipient_account: Keypair = Keypair::new();
+    let recipient_account_len: usize = ExtensionType::try_calculate_account_len::<Account>(&[
+        ExtensionType::MemoTransfer,
+        ExtensionType::CpiGuard
+    ]).unwrap();
+
+    let create_recipient_account_ixs: [Instruction; 4] = [
+        solana_sdk::system_instruction::create_account(
+            &payer_pkey, 
+            &recipient_account.pubkey(), 
+            rent.minimum_balance(recipient_account_len), 
+            recipient_account_len as u64, 
+            &SPL_TOKEN_2022_ID
+        ),
+        spl_token_2022::instruction::initialize_account3(&SPL_TOKEN_2022_ID, &recipient_account.pubkey(), &mint_pkey, &recipient.pubkey()).unwrap(),
+        enable_required_transfer_memos(&SPL_TOKEN_2022_ID, &recipient_account.pubkey(), &recipient.pubkey(), &[]).unwrap(),
+        enable_cpi_guard(&SPL_TOKEN_2022_ID, &recipient_account.pubkey(), &recipient.pubkey(), &[]).unwrap()
+    ];
+    let latest_blockhash: Hash = context.get_new_latest_blockhash().await.unwrap();
+    let create_recipient_account_tx: Transaction = Transaction::new_signed_with_payer(
+        &create_recipient_account_ixs, 
+        Some(&payer_pkey), 
+        &[&payer, &recipient_account, &recipient], 
+        latest_blockhash
+    );
+    process_tx(&context.banks_client, create_recipient_account_tx).await?;
+
+    // 3. Every new token account is frozen from now on => claim to the new recipient must be rejected
+    let update_default_state_ix: Instruction = update_default_account_state(
+        &SPL_TOKEN_2022_ID, 
+        &mint_pkey, 
+        &payer_pkey, 
+        &[], 
+        &AccountState::Frozen
+    ).unwrap();
+    process_ixs(&mut context, &[update_default_state_ix], &payer).await?;
+
+    let frozen_recipient: Pubkey = Pubkey::new_unique();
+    let frozen_recipient_ata: Pubkey = pda::find_ata(&frozen_recipient, &mint_pkey, &SPL_TOKEN_2022_ID).0;
+    let claim_ix: Instruction = instruction::create_claim(
+        &payer_pkey, 
+        &frozen_recipient, 
+        &frozen_recipient_ata, 
+        &vesting_pda, 
+        &treasury_pda, 
+        &config_pda, 
+        &mint_pkey,
+        &SPL_TOKEN_2022_ID
+    );
+    let err = process_ixs(&mut context, &[claim_ix], &payer).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(IDOProgramError::RecipientAccountFrozen as u32));
+
+    // 4. Claim to the memo-required & CPI guarded account passes
+    let claim_ix: Instruction = instruction::create_claim(
+        &payer_pkey, 
+        &recipient.pubkey(), 
+        &recipient_account.pubkey(), 
+        &vesting_pda, 
+        &treasury_pda, 
+        &config_pda, 
+        &mint_pkey,
+        &SPL_TOKEN_2022_ID
+    );
+    process_ixs(&mut context, &[claim_ix], &payer).await?;
+
+    let recipient_token_account = context.banks_client.get_account(recipient_account.pubkey()).await?.unwrap();
+    assert_eq!(
+        StateWithExtensions::<Account>::unpack(&recipient_token_account.data).unwrap().base.amount,
+        buy_amount / 5 * 10u64.pow(mint_decimals as u32)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_event_decoder() {
+    let mint: Pubkey = Pubkey::new_unique();
+    let buyer: Pubkey = Pubkey::new_unique();
+    let other_program_id: Pubkey = Pubkey::new_unique();
+
+    let purchased: IDOEvent = IDOEvent::Purchased { mint, buyer, amount: 1_000, lamports: 1_000_000 };
+    let claimed: IDOEvent = IDOEvent::Claimed { mint, buyer, recipient: Pubkey::new_unique(), amount: 200 };
+    let bid_settled: IDOEvent = IDOEvent::BidSettled { mint, bidder: buyer, filled_amount: 400, filled_cost: 480_000, refund_amount: 1_000 };
+
+    let migrated: IDOEvent = IDOEvent::Migrated { account: Pubkey::new_unique(), account_type: AccountType::Vesting, from_version: 1, to_version: ACCOUNT_VERSION };
+
+    let withdrawn: IDOEvent = IDOEvent::Withdrawn { mint, authority: Pubkey::new_unique(), lamports: u64::MAX, amount: 900 };
+
+    for event in [&purchased, &claimed, &bid_settled, &migrated, &withdrawn] {
+        assert_eq!(IDOEvent::unpack(&event.pack()).unwrap(), *event);
+    }
+
+    // the runtime logs `sol_log_data` fields as base64
+    let program_data_log = |event: &IDOEvent| format!("Program data: {}", BASE64_STANDARD.encode(event.pack()));
+
+    let logs: Vec<String> = vec![
+        format!("Program {} invoke [1]", IDO_PROGRAM_ID),
+        format!("Program {} invoke [2]", other_program_id),
+        // data logged by the invoked program must be skipped, even if it looks like the IDO event
+        program_data_log(&claimed),
+        format!("Program {} success", other_program_id),
+        program_data_log(&purchased),
+        format!("Program {} consumed 10000 of 200000 compute units", IDO_PROGRAM_ID),
+        format!("Program {} success", IDO_PROGRAM_ID),
+        format!("Program {} invoke [1]", IDO_PROGRAM_ID),
+        "Program log: unrelated message".to_string(),
+        program_data_log(&claimed),
+        "Program data: not-base64".to_string(),
+        format!("Program {} success", IDO_PROGRAM_ID),
+        format!("Program {} invoke [1]", IDO_PROGRAM_ID),
+        program_data_log(&bid_settled),
+        format!("Program {} failed: custom program error: 0x0", IDO_PROGRAM_ID),
+        program_data_log(&purchased),
+    ];
+
+    assert_eq!(IDOEvent::parse_logs(&logs), vec![purchased, claimed, bid_settled]);
+}
+
+#[test]
+fn test_derived_builders() {
+    let (payer, bidder, recipient, mint) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+    let vesting_strategy: LinearVestingStrategy = LinearVestingStrategy::new_without_cliff(60 * 5, 60);
+
+    let (treasury_pda, _) = pda::find_treasury(&mint);
+    let (config_pda, _) = pda::find_config(&mint);
+    let (auction_pda, _) = pda::find_auction(&mint);
+    let payer_ata: Pubkey = pda::find_ata(&payer, &mint, &SPL_TOKEN_ID).0;
+
+    assert_eq!(
+        instruction::create_initialize_with_vesting_for_mint(1_000, 10, &vesting_strategy, TransferFeePayer::Treasury, &payer, &mint, &SPL_TOKEN_ID),
+        instruction::create_initialize_with_vesting(1_000, 10, &vesting_strategy, TransferFeePayer::Treasury, &payer, &payer_ata, &treasury_pda, &config_pda, &mint, &SPL_TOKEN_ID)
+    );
+    assert_eq!(
+        instruction::create_buy_with_vesting_for_mint(7, &payer, &mint),
+        instruction::create_buy_with_vesting(7, &payer, &pda::find_vesting(&payer, &mint).0, &treasury_pda, &config_pda, &mint)
+    );
+    assert_eq!(
+        instruction::create_claim_for_mint(&payer, &recipient, &mint, &SPL_TOKEN_2022_ID),
+        instruction::create_claim(
+            &payer,
+            &recipient,
+            &pda::find_ata(&recipient, &mint, &SPL_TOKEN_2022_ID).0,
+            &pda::find_vesting(&payer, &mint).0,
+            &treasury_pda,
+            &config_pda,
+            &mint,
+            &SPL_TOKEN_2022_ID
+        )
+    );
+    assert_eq!(
+        instruction::create_initialize_auction_for_mint(1_000, 10, 1, 60, &vesting_strategy, TransferFeePayer::Recipient, &payer, &mint, &SPL_TOKEN_ID),
+        instruction::create_initialize_auction(1_000, 10, 1, 60, &vesting_strategy, TransferFeePayer::Recipient, &payer, &payer_ata, &auction_pda, &treasury_pda, &config_pda, &mint, &SPL_TOKEN_ID)
+    );
+    assert_eq!(
+        instruction::create_place_bid_for_mint(5, 20, &bidder, &mint),
+        instruction::create_place_bid(5, 20, &bidder, &pda::find_bid(&bidder, &mint).0, pda::find_bid(&bidder, &mint).1, &auction_pda, &treasury_pda, &config_pda, &mint)
+    );
+    assert_eq!(
+        instruction::create_settle_auction_for_mint(&mint),
+        instruction::create_settle_auction(&auction_pda, &treasury_pda, &config_pda, &mint)
+    );
+    assert_eq!(
+        instruction::create_settle_bid_for_mint(&payer, &bidder, &mint),
+        instruction::create_settle_bid(
+            &payer,
+            &bidder,
+            &pda::find_bid(&bidder, &mint).0,
+            &pda::find_vesting(&bidder, &mint).0,
+            &auction_pda,
+            &treasury_pda,
+            &config_pda,
+            &mint
+        )
+    );
+    assert_eq!(
+        instruction::create_withdraw_for_mint(&payer, &mint, &SPL_TOKEN_2022_ID),
+        instruction::create_withdraw(&payer, &pda::find_ata(&payer, &mint, &SPL_TOKEN_2022_ID).0, &treasury_pda, &config_pda, &mint, &SPL_TOKEN_2022_ID)
+    );
+}
+
+#[test]
+fn test_instruction_round_trip() {
+    let vesting_strategy: LinearVestingStrategy = LinearVestingStrategy {
+        cliff_end_ts: 1_000,
+        vesting_end_ts: 1_600,
+        unlock_period: 60
+    };
+    let [payer, ata, treasury, config, mint, vesting, auction, bid, recipient] = [(); 9].map(|_| Pubkey::new_unique());
+
+    // every variant & the data assembled by its builder
+    let ixs: [(IDOInstruction, Instruction); 9] = [
+        (
+            IDOInstruction::InitializeWithVesting { amount: u64::MAX, lamports_per_token: 1_000, vesting_strategy, transfer_fee_payer: TransferFeePayer::Treasury },
+            instruction::create_initialize_with_vesting(u64::MAX, 1_000, &vesting_strategy, TransferFeePayer::Treasury, &payer, &ata, &treasury, &config, &mint, &SPL_TOKEN_2022_ID)
+        ),
+        (
+            IDOInstruction::BuyWithVesting { amount: 1_000 },
+            instruction::create_buy_with_vesting(1_000, &payer, &vesting, &treasury, &config, &mint)
+        ),
+        (
+            IDOInstruction::Claim,
+            instruction::create_claim(&payer, &recipient, &ata, &vesting, &treasury, &config, &mint, &SPL_TOKEN_2022_ID)
+        ),
+        (
+            IDOInstruction::InitializeAuction { amount: 1, min_price: 1_000, tick_size: 100, bidding_end_ts: i64::MIN, vesting_strategy, transfer_fee_payer: TransferFeePayer::Recipient },
+            instruction::create_initialize_auction(1, 1_000, 100, i64::MIN, &vesting_strategy, TransferFeePayer::Recipient, &payer, &ata, &auction, &treasury, &config, &mint, &SPL_TOKEN_2022_ID)
+        ),
+        (
+            IDOInstruction::PlaceBid { quantity: 600, max_price: u32::MAX, bump: 253 },
+            instruction::create_place_bid(600, u32::MAX, &payer, &bid, 253, &auction, &treasury, &config, &mint)
+        ),
+        (
+            IDOInstruction::SettleAuction,
+            instruction::create_settle_auction(&auction, &treasury, &config, &mint)
+        ),
+        (
+            IDOInstruction::SettleBid,
+            instruction::create_settle_bid(&payer, &recipient, &bid, &vesting, &auction, &treasury, &config, &mint)
+        ),
+        (
+            IDOInstruction::Migrate,
+            instruction::create_migrate(&payer, &config, &mint)
+        ),
+        (
+            IDOInstruction::Withdraw,
+            instruction::create_withdraw(&payer, &ata, &treasury, &config, &mint, &SPL_TOKEN_2022_ID)
+        )
+    ];
+
+    for (discriminator, (ix, builder_ix)) in ixs.iter().enumerate() {
+        let data: Vec<u8> = ix.pack();
+
+        assert_eq!(data[0] as usize, discriminator);
+        assert_eq!(data, builder_ix.data);
+        assert_eq!(IDOInstruction::unpack(&data), Ok(*ix));
+
+        // the wire format is exactly the Borsh encoding
+        assert_eq!(borsh::to_vec(ix).unwrap(), data);
+        assert_eq!(borsh::from_slice::<IDOInstruction>(&data).unwrap(), *ix);
+
+        // truncated & extended payloads are rejected
+        assert_eq!(IDOInstruction::unpack(&data[..data.len() - 1]), Err(ProgramError::InvalidInstructionData));
+        assert_eq!(IDOInstruction::unpack(&[&data[..], &[0]].concat()), Err(ProgramError::InvalidInstructionData));
+    }
+
+    assert_eq!(IDOInstruction::unpack(&[9]), Err(ProgramError::InvalidInstructionData));
+
+    // account data is the Borsh encoding as well, Auction & Bid are prefixed with the header
+    let vesting_account: IDOVestingAccount = IDOVestingAccount::new(payer, mint, 1_000, 100, 255);
+    assert_eq!(borsh::to_vec(&vesting_account).unwrap(), bytemuck::bytes_of(&vesting_account));
+
+    let mut config_data: Vec<u8> = vec![0; IDOConfigAccount::LEN];
+    let config_account: &mut IDOConfigAccount = IDOConfigAccount::init(&mut config_data).unwrap();
+    config_account.vesting_strategy = vesting_strategy;
+    config_account.mint = mint;
+    config_account.treasury = treasury;
+    assert_eq!(borsh::to_vec(config_account).unwrap(), config_data);
+
+    let mut auction_account: IDOAuctionAccount = IDOAuctionAccount::new(1_600, 1_000, 1_000, 100, 254);
+    auction_account.demand[3] = 400;
+    let mut auction_data: Vec<u8> = vec![0; IDOAuctionAccount::LEN];
+    auction_account.pack_into_slice(&mut auction_data);
+    assert_eq!(borsh::to_vec(&auction_account).unwrap(), auction_data[ACCOUNT_HEADER_LEN..]);
+
+    let bid_account: IDOBidAccount = IDOBidAccount { quantity: 600, max_price: 1_200, bump: 253, is_initialized: true };
+    let mut bid_data: Vec<u8> = vec![0; IDOBidAccount::LEN];
+    bid_account.pack_into_slice(&mut bid_data);
+    assert_eq!(borsh::to_vec(&bid_account).unwrap(), bid_data[ACCOUNT_HEADER_LEN..]);
+}
+
+#[test]
+fn test_serde() {
+    let vesting_strategy: LinearVestingStrategy = LinearVestingStrategy {
+        cliff_end_ts: 1_000,
+        vesting_end_ts: 1_600,
+        unlock_period: 60
+    };
+    let [mint, treasury] = [(); 2].map(|_| Pubkey::new_unique());
+
+    let json: serde_json::Value = serde_json::to_value(vesting_strategy).unwrap();
+    assert_eq!(json, serde_json::json!({ "cliff_end_ts": 1_000, "vesting_end_ts": 1_600, "unlock_period": 60 }));
+    assert_eq!(serde_json::from_value::<LinearVestingStrategy>(json).unwrap(), vesting_strategy);
+
+    let mut config_data: Vec<u8> = vec![0; IDOConfigAccount::LEN];
+    let config_account: &mut IDOConfigAccount = IDOConfigAccount::init(&mut config_data).unwrap();
+    config_account.vesting_strategy = vesting_strategy;
+    config_account.mint = mint;
+    config_account.treasury = treasury;
+
+    // pubkeys are base58 strings & the padding isn't exposed
+    let json: serde_json::Value = serde_json::to_value(*config_account).unwrap();
+    assert_eq!(json["mint"], mint.to_string());
+    assert_eq!(json["treasury"], treasury.to_string());
+    assert!(json.get("_padding0").is_none());
+    assert_eq!(serde_json::from_value::<IDOConfigAccount>(json).unwrap(), *config_account);
+
+    let mut invalid_json: serde_json::Value = serde_json::to_value(*config_account).unwrap();
+    invalid_json["mint"] = "not a pubkey".into();
+    assert!(serde_json::from_value::<IDOConfigAccount>(invalid_json).is_err());
+
+    let vesting_account: IDOVestingAccount = IDOVestingAccount::new(treasury, mint, 1_000, 100, 255);
+    let json: serde_json::Value = serde_json::to_value(vesting_account).unwrap();
+    assert_eq!(json["owner"], treasury.to_string());
+    assert_eq!(json["mint"], mint.to_string());
+    assert_eq!(serde_json::from_value::<IDOVestingAccount>(json).unwrap(), vesting_account);
+
+    let mut auction_account: IDOAuctionAccount = IDOAuctionAccount::new(1_600, 1_000, 1_000, 100, 254);
+    auction_account.demand[63] = 400;
+    let json: String = serde_json::to_string(&auction_account).unwrap();
+    assert_eq!(serde_json::from_str::<IDOAuctionAccount>(&json).unwrap(), auction_account);
+
+    let bid_account: IDOBidAccount = IDOBidAccount { quantity: 600, max_price: 1_200, bump: 253, is_initialized: true };
+    let json: String = serde_json::to_string(&bid_account).unwrap();
+    assert_eq!(serde_json::from_str::<IDOBidAccount>(&json).unwrap(), bid_account);
+}
+
+#[test]
+fn test_decoders_reject_malformed_input() {
+    // 1. Every prefix of valid data (including the empty one) & the overflowing offsets are rejected, not panicked on
+    let vesting_strategy: LinearVestingStrategy = LinearVestingStrategy { cliff_end_ts: 1_000, vesting_end_ts: 1_600, unlock_period: 60 };
+    let instruction_data: Vec<u8> = IDOInstruction::InitializeAuction {
+        amount: 1_000,
+        min_price: 1_000,
+        tick_size: 100,
+        bidding_end_ts: 900,
+        vesting_strategy,
+        transfer_fee_payer: TransferFeePayer::Recipient
+    }.pack();
+
+    let mut config_data: Vec<u8> = vec![0; IDOConfigAccount::LEN];
+    IDOConfigAccount::init(&mut config_data).unwrap();
+    let vesting_data: Vec<u8> = bytemuck::bytes_of(&IDOVestingAccount::new(Pubkey::new_unique(), Pubkey::new_unique(), 1_000, 100, 255)).to_vec();
+
+    let mut auction_data: Vec<u8> = vec![0; IDOAuctionAccount::LEN];
+    IDOAuctionAccount::new(900, 1_000, 1_000, 100, 254).pack_into_slice(&mut auction_data);
+    let mut bid_data: Vec<u8> = vec![0; IDOBidAccount::LEN];
+    IDOBidAccount { quantity: 600, max_price: 1_200, bump: 253, is_initialized: true }.pack_into_slice(&mut bid_data);
+
+    for len in 0..instruction_data.len() {
+        assert_eq!(IDOInstruction::unpack(&instruction_data[..len]), Err(ProgramError::InvalidInstructionData));
+    }
+
+    for len in 0..config_data.len() {
+        assert!(IDOConfigAccount::unpack(&config_data[..len]).is_err());
+        assert!(IDOConfigAccount::from_v1(&config_data[..len.min(30)]).is_err());
+    }
+
+    for len in 0..vesting_data.len() {
+        assert!(IDOVestingAccount::unpack(&vesting_data[..len]).is_err());
+        assert!(IDOVestingAccount::from_v1(&vesting_data[..len.min(33)], Pubkey::default(), Pubkey::default()).is_err());
+    }
+
+    for len in 0..auction_data.len() {
+        assert!(IDOAuctionAccount::unpack_from_slice(&auction_data[..len]).is_err());
+    }
+
+    for len in 0..bid_data.len() {
+        assert!(IDOBidAccount::unpack_from_slice(&bid_data[..len]).is_err());
+    }
+
+    assert!(IDOAuctionAccount::unpack_from_slice(&auction_data).is_ok());
+    assert!(IDOBidAccount::unpack_from_slice(&bid_data).is_ok());
+
+    for start in [usize::MAX - 7, usize::MAX - 16, usize::MAX] {
+        assert_eq!(read_u64_slice(&instruction_data, start), Err(ProgramError::InvalidInstructionData));
+        assert_eq!(read_linear_vesting_strategy_slice(&instruction_data, start).map(|_| ()), Err(ProgramError::InvalidInstructionData));
+    }
+
+    // 2. Values that don't fit the field's domain are rejected as well
+    let mut data: Vec<u8> = instruction_data.clone();
+    *data.last_mut().unwrap() = 2;
+    assert_eq!(IDOInstruction::unpack(&data), Err(ProgramError::InvalidArgument));
+
+    config_data[std::mem::offset_of!(IDOConfigAccount, transfer_fee_payer)] = 2;
+    assert_eq!(IDOConfigAccount::unpack(&config_data).err(), Some(ProgramError::InvalidArgument));
+}
+
+/// Fails once the checked-in IDL drifts from the code, run `UPDATE_IDL=1 cargo test --test program test_idl` to regenerate it.
+#[test]
+fn test_idl() {
+    let idl_path: std::path::PathBuf = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("idl/ido_with_vesting.json");
+    let idl_json: String = idl::generate_json();
+
+    if std::env::var_os("UPDATE_IDL").is_some() {
+        std::fs::write(&idl_path, &idl_json).unwrap();
+    }
+
+    let checked_in_idl_json: String = std::fs::read_to_string(&idl_path).unwrap_or_default();
+    assert!(checked_in_idl_json == idl_json, "idl/ido_with_vesting.json is out of date, run `UPDATE_IDL=1 cargo test --test program test_idl`");
+
+    // the hand-written parts (arg & field types) must agree with the sizes taken from the code
+    let idl: serde_json::Value = idl::generate();
+    let type_size = |ty: &serde_json::Value| -> u64 {
+        if let Some([element_ty, len]) = ty["array"].as_array().map(Vec::as_slice) {
+            return type_size_of(&idl, element_ty) * len.as_u64().unwrap();
+        }
+
+        type_size_of(&idl, ty)
+    };
+
+    for ix in idl["instructions"].as_array().unwrap() {
+        let args_len: u64 = ix["args"].as_array().unwrap().iter().map(|arg| type_size(&arg["type"])).sum();
+        assert_eq!(1 + args_len, ix["data_len"].as_u64().unwrap(), "{}", ix["name"]);
+    }
+
+    for account in idl["accounts"].as_array().unwrap() {
+        let mut end: u64 = 0;
+
+        for field in account["fields"].as_array().unwrap() {
+            let offset: u64 = field["offset"].as_u64().unwrap();
+            assert!(offset >= end, "{} overlaps in {}", field["name"], account["name"]);
+            end = offset + type_size(&field["type"]);
+        }
+
+        assert!(end <= account["size"].as_u64().unwrap(), "{} overflows", account["name"]);
+    }
+
+    // packed accounts read back at the IDL offsets
+    let auction: IDOAuctionAccount = IDOAuctionAccount {
+        bidding_end_ts: -2,
+        supply: 3,
+        min_price: 4,
+        tick_size: 5,
+        clearing_price: 6,
+        clearing_level: 7,
+        bump: 8,
+        is_settled: true,
+        is_initialized: true,
+        clearing_level_fill: 11,
+        demand: std::array::from_fn(|level| 100 + level as u64)
+    };
+    let bid: IDOBidAccount = IDOBidAccount { quantity: 2, max_price: 3, bump: 4, is_initialized: true };
+
+    let packed_accounts: [(&str, Vec<u8>, serde_json::Value); 2] = [
+        ("IDOAuctionAccount", pack_account(&auction), serde_json::json!({
+            "account_type": AccountType::Auction as u8,
+            "version": ACCOUNT_VERSION,
+            "bidding_end_ts": -2,
+            "supply": 3,
+            "min_price": 4,
+            "tick_size": 5,
+            "clearing_price": 6,
+            "clearing_level": 7,
+            "bump": 8,
+            "is_settled": true,
+            "is_initialized": true,
+            "clearing_level_fill": 11,
+            "demand": auction.demand.to_vec()
+        })),
+        ("IDOBidAccount", pack_account(&bid), serde_json::json!({
+            "account_type": AccountType::Bid as u8,
+            "version": ACCOUNT_VERSION,
+            "quantity": 2,
+            "max_price": 3,
+            "bump": 4,
+            "is_initialized": true
+        }))
+    ];
+
+    for (account_name, data, expected_fields) in packed_accounts {
+        let account: &serde_json::Value = idl["accounts"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|account| account["name"] == account_name)
+            .unwrap();
+
+        for field in account["fields"].as_array().unwrap() {
+            let offset: usize = field["offset"].as_u64().unwrap() as usize;
+
+            let value: serde_json::Value = match field["type"]["array"].as_array() {
+                Some(array) => (0..array[1].as_u64().unwrap() as usize)
+                    .map(|index| read_idl_value(&data, &array[0], offset + index * type_size(&array[0]) as usize))
+                    .collect(),
+                None => read_idl_value(&data, &field["type"], offset)
+            };
+
+            assert_eq!(value, expected_fields[field["name"].as_str().unwrap()], "{account_name}.{}", field["name"]);
+        }
+    }
+}
+
+fn pack_account<T: Pack>(account: &T) -> Vec<u8> {
+    let mut data: Vec<u8> = vec![0; T::LEN];
+    account.pack_into_slice(&mut data);
+    data
+}
+
+/// Reads a primitive IDL type at `offset`.
+fn read_idl_value(data: &[u8], ty: &serde_json::Value, offset: usize) -> serde_json::Value {
+    match ty.as_str().unwrap() {
+        "u8" => data[offset].into(),
+        "bool" => (data[offset] != 0).into(),
+        "u32" => u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap()).into(),
+        "u64" => u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap()).into(),
+        "i64" => i64::from_le_bytes(data[offset..offset + 8].try_into().unwrap()).into(),
+        ty => panic!("unexpected type {ty}")
+    }
+}
+
+fn type_size_of(idl: &serde_json::Value, ty: &serde_json::Value) -> u64 {
+    match ty.as_str().unwrap() {
+        "u8" | "bool" => 1,
+        "u32" => 4,
+        "u64" | "i64" => 8,
+        "pubkey" => 32,
+        defined_ty => idl["types"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|ty| ty["name"] == defined_ty)
+            .and_then(|ty| ty["size"].as_u64())
+            .unwrap_or_else(|| panic!("unknown type {defined_ty}"))
+    }
+}
+
+#[tokio::test]
+async fn test_invalid_accounts() -> Result<(), MintFixtureError> {
+    let program: ProgramTest = ProgramTest::new(
+        "ido_with_vesting", 
+        IDO_PROGRAM_ID,
+        processor!(entrypoint::process_instruction)
+    );
+
+    let mut context: ProgramTestContext = program.start_with_context().await;
+    let payer: Keypair = context.payer.insecure_clone();
+    let payer_pkey: Pubkey = payer.pubkey();
+    let rent: Rent = context.banks_client.get_sysvar::<Rent>().await?;
+    let latest_blockhash: Hash = context.last_blockhash;
+
+    // 0. Create Mint; Create & Initialize ATA; Mint tokens to ATA
+    let mint_decimals: u8 = 9;
+    let mint_amount: u64 = 1_000_000 * 10u64.pow(mint_decimals as u32);
+
+    let (mint_pkey, ata_pda, foreign_mint_pkey, foreign_ata_pda) = {
+        let mint_fixture: MintFixture = MintFixture::new(
+            MintFixtureClient::Banks(&context.banks_client),
+            &payer,
+            &payer_pkey,
+            &rent
+        );
+
+        let mint_pkey: Pubkey = mint_fixture.create_and_intiialize_mint(mint_decimals, &latest_blockhash).await?;
+        let ata_pda: Pubkey = mint_fixture.create_and_intiialize_ata(&mint_pkey, &latest_blockhash).await?;
+        mint_fixture.mint_to_ata(&mint_pkey, &ata_pda, mint_amount, &latest_blockhash).await?;
+
+        let foreign_mint_pkey: Pubkey = mint_fixture.create_and_intiialize_mint(mint_decimals, &latest_blockhash).await?;
+        let foreign_ata_pda: Pubkey = mint_fixture.create_and_intiialize_ata(&foreign_mint_pkey, &latest_blockhash).await?;
+
+        (mint_pkey, ata_pda, foreign_mint_pkey, foreign_ata_pda)
+    };
+
+    let treasury_pda: Pubkey = pda::find_treasury(&mint_pkey).0;
+    let config_pda: Pubkey = pda::find_config(&mint_pkey).0;
+    let vesting_pda: Pubkey = pda::find_vesting(&payer_pkey, &mint_pkey).0;
+    let vesting_strategy: LinearVestingStrategy = LinearVestingStrategy::new_without_cliff(60 * 5, 60);
+
+    // 1. Every mismatched account is reported with its own error
+    let initialize_ido_ix = |treasury_pda: &Pubkey, config_pda: &Pubkey| instruction::create_initialize_with_vesting(
+        mint_amount, 
+        1_000, 
+        &vesting_strategy, 
+        TransferFeePayer::Recipient,
+        &payer_pkey, 
+        &ata_pda, 
+        treasury_pda, 
+        config_pda, 
+        &mint_pkey,
+        &SPL_TOKEN_2022_ID
+    );
+
+    let err = process_ixs(&mut context, &[initialize_ido_ix(&Pubkey::new_unique(), &config_pda)], &payer).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(IDOProgramError::InvalidTreasuryAccount as u32));
+
+    let err = process_ixs(&mut context, &[initialize_ido_ix(&treasury_pda, &Pubkey::new_unique())], &payer).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(IDOProgramError::InvalidConfigAccount as u32));
+
+    process_ixs(&mut context, &[initialize_ido_ix(&treasury_pda, &config_pda)], &payer).await?;
+
+    let buy_ix: Instruction = instruction::create_buy_with_vesting(1_000, &payer_pkey, &Pubkey::new_unique(), &treasury_pda, &config_pda, &mint_pkey);
+    let err = process_ixs(&mut context, &[buy_ix], &payer).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(IDOProgramError::InvalidVestingAccount as u32));
+
+    // 2. Clients can turn the custom code back into the readable error
+    let code: u32 = custom_error_code(&err).unwrap();
+    assert_eq!(IDOProgramError::try_from(code).unwrap(), IDOProgramError::InvalidVestingAccount);
+
+    let buy_ix: Instruction = instruction::create_buy_with_vesting(1_000, &payer_pkey, &vesting_pda, &treasury_pda, &config_pda, &mint_pkey);
+    process_ixs(&mut context, &[buy_ix], &payer).await?;
+
+    // 3. Contexts validate signers, owners & program ids before any PDA derivation
+    let claim_ix: Instruction = instruction::create_claim(
+        &payer_pkey, 
+        &payer_pkey, 
+        &ata_pda, 
+        &vesting_pda, 
+        &treasury_pda, 
+        &Pubkey::new_unique(), 
+        &mint_pkey,
+        &SPL_TOKEN_2022_ID
+    );
+    let err = process_ixs(&mut context, &[claim_ix], &payer).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(IDOProgramError::InvalidAccountOwner as u32));
+
+    let mut buy_ix: Instruction = instruction::create_buy_with_vesting(1_000, &payer_pkey, &vesting_pda, &treasury_pda, &config_pda, &mint_pkey);
+    buy_ix.accounts[0] = AccountMeta::new(Pubkey::new_unique(), false);
+    let err = process_ixs(&mut context, &[buy_ix], &payer).await.unwrap_err();
+    assert!(matches!(
+        err,
+        BanksClientError::TransactionError(TransactionError::InstructionError(_, InstructionError::MissingRequiredSignature))
+    ));
+
+    let mut buy_ix: Instruction = instruction::create_buy_with_vesting(1_000, &payer_pkey, &vesting_pda, &treasury_pda, &config_pda, &mint_pkey);
+    buy_ix.accounts[5] = AccountMeta::new_readonly(Pubkey::new_unique(), false);
+    let err = process_ixs(&mut context, &[buy_ix], &payer).await.unwrap_err();
+    assert!(matches!(
+        err,
+        BanksClientError::TransactionError(TransactionError::InstructionError(_, InstructionError::IncorrectProgramId))
+    ));
+
+    // 4. Config can't be mixed with accounts of another IDO
+    let buy_ix: Instruction = instruction::create_buy_with_vesting(1_000, &payer_pkey, &vesting_pda, &treasury_pda, &config_pda, &foreign_mint_pkey);
+    let err = process_ixs(&mut context, &[buy_ix], &payer).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(IDOProgramError::ConfigMintMismatch as u32));
+
+    let buy_ix: Instruction = instruction::create_buy_with_vesting(1_000, &payer_pkey, &vesting_pda, &ata_pda, &config_pda, &mint_pkey);
+    let err = process_ixs(&mut context, &[buy_ix], &payer).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(IDOProgramError::ConfigTreasuryMismatch as u32));
+
+    // 5. Lamports to pay are checked for overflow
+    let buy_ix: Instruction = instruction::create_buy_with_vesting(u64::MAX / 1_000 + 1, &payer_pkey, &vesting_pda, &treasury_pda, &config_pda, &mint_pkey);
+    let err = process_ixs(&mut context, &[buy_ix], &payer).await.unwrap_err();
+    assert!(matches!(
+        err,
+        BanksClientError::TransactionError(TransactionError::InstructionError(_, InstructionError::ArithmeticOverflow))
+    ));
+
+    // 6. Existing recipient token account must belong to the recipient & the mint
+    let claim_ix = |recipient: &Pubkey, recipient_ata: &Pubkey| instruction::create_claim(
+        &payer_pkey, 
+        recipient, 
+        recipient_ata, 
+        &vesting_pda, 
+        &treasury_pda, 
+        &config_pda, 
+        &mint_pkey,
+        &SPL_TOKEN_2022_ID
+    );
+
+    let err = process_ixs(&mut context, &[claim_ix(&Pubkey::new_unique(), &ata_pda)], &payer).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(IDOProgramError::RecipientOwnerMismatch as u32));
+
+    let err = process_ixs(&mut context, &[claim_ix(&payer_pkey, &foreign_ata_pda)], &payer).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(IDOProgramError::RecipientMintMismatch as u32));
+
+    process_ixs(&mut context, &[claim_ix(&payer_pkey, &ata_pda)], &payer).await?;
+
+    Ok(())
+}
+
+#[test]
+fn test_arithmetic_boundaries() {
+    let clock = |unix_timestamp: i64| Clock { unix_timestamp, ..Clock::default() };
+    let strategy = |cliff_end_ts: i64, vesting_end_ts: i64, unlock_period: i64| LinearVestingStrategy { 
+        cliff_end_ts, 
+        vesting_end_ts, 
+        unlock_period 
+    };
+
+    // 1. Unlocks must fit into `MAX_UNLOCKS`, the unlock period must be positive
+    assert_eq!(strategy(0, MAX_UNLOCKS as i64 * 60, 60).unlocks(), Ok(MAX_UNLOCKS));
+    assert_eq!(strategy(0, (MAX_UNLOCKS as i64 + 1) * 60, 60).unlocks(), Err(IDOProgramError::MaxUnlocksOverflow.into()));
+    assert_eq!(strategy(0, i64::MAX, 1).unlocks(), Err(IDOProgramError::MaxUnlocksOverflow.into()));
+    assert_eq!(strategy(-1, i64::MAX, 1).unlocks(), Err(ProgramError::ArithmeticOverflow));
+    assert_eq!(strategy(0, 60, 0).unlocks(), Err(IDOProgramError::UnlockPeriodMustBeGreaterThanZero.into()));
+    assert_eq!(strategy(0, 60, -60).unlocks(), Err(IDOProgramError::UnlockPeriodMustBeGreaterThanZero.into()));
+
+    // 2. Whole tokens are converted into base units without overflow
+    assert_eq!(to_base_units(1, 19), Ok(10u64.pow(19)));
+    assert_eq!(to_base_units(1, 20), Err(ProgramError::ArithmeticOverflow));
+    assert_eq!(to_base_units(u64::MAX / 10u64.pow(9), 9), Ok(u64::MAX / 10u64.pow(9) * 10u64.pow(9)));
+    assert_eq!(to_base_units(u64::MAX / 10u64.pow(9) + 1, 9), Err(ProgramError::ArithmeticOverflow));
+
+    // 3. Claimed portions are never counted twice & the whole bought amount is released at the end
+    let vesting_strategy: LinearVestingStrategy = strategy(1_000, 1_400, 100);
+    let mut vesting_account: IDOVestingAccount = IDOVestingAccount::new(Pubkey::default(), Pubkey::default(), u64::MAX, u64::MAX / 4, 0);
+
+    assert_eq!(allow_claim_and_define_portion(&clock(999), &vesting_strategy, &mut vesting_account), Err(IDOProgramError::CliffIsActive.into()));
+    assert_eq!(allow_claim_and_define_portion(&clock(1_000), &vesting_strategy, &mut vesting_account), Ok(u64::MAX / 4));
+    assert_eq!(allow_claim_and_define_portion(&clock(1_099), &vesting_strategy, &mut vesting_account), Err(IDOProgramError::VestingIsActive.into()));
+    assert_eq!(allow_claim_and_define_portion(&clock(1_100), &vesting_strategy, &mut vesting_account), Err(IDOProgramError::VestingIsActive.into()));
+    assert_eq!(allow_claim_and_define_portion(&clock(1_399), &vesting_strategy, &mut vesting_account), Ok(u64::MAX / 4 * 2));
+    assert_eq!(vesting_account.claimed_amount, u64::MAX / 4 * 3);
+    assert_eq!(allow_claim_and_define_portion(&clock(1_400), &vesting_strategy, &mut vesting_account), Ok(u64::MAX - u64::MAX / 4 * 3));
+    assert_eq!(vesting_account.claimed_amount, u64::MAX);
+    assert_eq!(allow_claim_and_define_portion(&clock(i64::MAX), &vesting_strategy, &mut vesting_account), Err(IDOProgramError::AlreadyClaimed.into()));
+
+    // 4. Inconsistent accounting is rejected instead of wrapping around
+    let mut vesting_account: IDOVestingAccount = IDOVestingAccount::new(Pubkey::default(), Pubkey::default(), 100, 25, 0);
+    vesting_account.claimed_amount = 101;
+    assert_eq!(allow_claim_and_define_portion(&clock(1_400), &vesting_strategy, &mut vesting_account), Err(ProgramError::ArithmeticOverflow));
+
+    vesting_account.claimed_amount = 26;
+    assert_eq!(allow_claim_and_define_portion(&clock(1_000), &vesting_strategy, &mut vesting_account), Err(ProgramError::ArithmeticOverflow));
+}
+
+#[test]
+fn test_claim_pays_only_new_unlocks() {
+    let claim = |ts: i64, vesting_strategy: &LinearVestingStrategy, vesting_account: &mut IDOVestingAccount| {
+        allow_claim_and_define_portion(&Clock { unix_timestamp: ts, ..Clock::default() }, vesting_strategy, vesting_account)
+    };
+
+    // 10 unlocks of 100 tokens: at the cliff, then at the 2nd, 3rd, ... unlock period after it
+    let vesting_strategy: LinearVestingStrategy = LinearVestingStrategy { cliff_end_ts: 1_000, vesting_end_ts: 2_000, unlock_period: 100 };
+    let mut vesting_account: IDOVestingAccount = IDOVestingAccount::new(Pubkey::default(), Pubkey::default(), 1_000, 100, 0);
+
+    // every claim pays only the unlocks reached since the previous one, the ones claimed before aren't paid again
+    for (ts, payout, claimed_amount) in [
+        (1_000, 100, 100),
+        (1_200, 100, 200),
+        (1_500, 300, 500),
+        (1_999, 400, 900),
+        (2_000, 100, 1_000)
+    ] {
+        assert_eq!(claim(ts, &vesting_strategy, &mut vesting_account), Ok(payout), "claim at {ts}");
+        assert_eq!(vesting_account.claimed_amount, claimed_amount, "claimed at {ts}");
+        assert_eq!(vesting_account.last_claim_ts, if ts < 2_000 { ts } else { 1_999 });
+    }
+}
+
+proptest! {
+    /// Payouts of `allow_claim_and_define_portion` & `claimable_at` match an independent model built from `unlock_schedule`
+    /// at any timestamp, regardless of the claims made before: `bps * bought / MAX_BPS` (rounded per unlock) minus the claimed amount.
+    #[test]
+    fn test_unlock_schedule_and_claimable_at(
+        cliff_end_ts in 1i64..4_000_000_000,
+        unlock_period in 1i64..1_000_000,
+        unlocks in 1..=MAX_UNLOCKS,
+        remainder in 0i64..1_000_000,
+        bought_amount in prop_oneof![1u64..1_000_000, any::<u64>()],
+        claim_intervals in prop::collection::vec(0i64..3_000_000, 1..64)
+    ) {
+        let vesting_strategy: LinearVestingStrategy = LinearVestingStrategy {
+            cliff_end_ts,
+            vesting_end_ts: cliff_end_ts + unlocks as i64 * unlock_period + remainder % unlock_period,
+            unlock_period
+        };
+        prop_assert_eq!(vesting_strategy.unlocks(), Ok(unlocks));
+
+        let mut config_data: Vec<u8> = vec![0; IDOConfigAccount::LEN];
+        let config_account: &mut IDOConfigAccount = IDOConfigAccount::init(&mut config_data).unwrap();
+        config_account.vesting_strategy = vesting_strategy;
+        config_account.unlocks = unlocks;
+        let config_account: IDOConfigAccount = *config_account;
+
+        // 1. Schedule starts at the cliff, its steps increase & the last one releases the whole amount exactly at the end of the vesting
+        let schedule: Vec<(i64, u16)> = vesting_strategy.unlock_schedule().unwrap().collect();
+        prop_assert_eq!(schedule[0].0, cliff_end_ts);
+        prop_assert_eq!(*schedule.last().unwrap(), (vesting_strategy.vesting_end_ts, MAX_BPS));
+        prop_assert!(schedule.windows(2).all(|steps| steps[0].0 < steps[1].0 && steps[0].1 <= steps[1].1));
+
+        // the model: the bps of the step reached are `k` unlocks of `bought / unlocks` each, the whole amount at the end of the vesting,
+        // nothing is claimable within an unlock period after the previous claim, the period is cut by the end of the vesting
+        let bps_per_unlock: u64 = (MAX_BPS / unlocks as u16) as u64;
+        let scheduled_bps = |ts: i64| schedule.iter().rev().find(|(step_ts, _)| *step_ts <= ts).map_or(0, |(_, bps)| *bps as u64);
+        let model_claimable = |ts: i64, claimed_amount: u64, last_claim_ts: Option<i64>| -> u64 {
+            if last_claim_ts.is_some_and(|last_claim_ts| ts < (last_claim_ts + unlock_period).min(vesting_strategy.vesting_end_ts)) {
+                return 0;
+            }
+
+            let unlocked_amount: u64 = if ts >= vesting_strategy.vesting_end_ts {
+                bought_amount
+            } else {
+                bought_amount / unlocks as u64 * (scheduled_bps(ts) / bps_per_unlock)
+            };
+
+            unlocked_amount - claimed_amount
+        };
+
+        // 2. The fresh vesting claims as modeled both at & right before every step
+        let vesting_account: IDOVestingAccount = IDOVestingAccount::new(Pubkey::default(), Pubkey::default(), bought_amount, bought_amount / unlocks as u64, 0);
+
+        for ts in schedule.iter().flat_map(|(step_ts, _)| [step_ts - 1, *step_ts, step_ts + unlock_period - 1]) {
+            let mut claimed_vesting_account: IDOVestingAccount = vesting_account;
+            let payout: u64 = allow_claim_and_define_portion(&Clock { unix_timestamp: ts, ..Clock::default() }, &vesting_strategy, &mut claimed_vesting_account).unwrap_or(0);
+
+            prop_assert_eq!(payout, model_claimable(ts, 0, None));
+            prop_assert_eq!(vesting_account.claimable_at(&config_account, ts), payout);
+        }
+
+        // 3. A claim right before the end of the vesting doesn't delay the final step: the rest is claimable exactly at the end,
+        // as both the schedule & `VestingProgress` report
+        let mut claimed_vesting_account: IDOVestingAccount = vesting_account;
+        let last_ts: i64 = vesting_strategy.vesting_end_ts - 1;
+        let payout: u64 = allow_claim_and_define_portion(&Clock { unix_timestamp: last_ts, ..Clock::default() }, &vesting_strategy, &mut claimed_vesting_account).unwrap_or(0);
+        prop_assert_eq!(payout, model_claimable(last_ts, 0, None));
+
+        let last_claim_ts: Option<i64> = (payout > 0).then_some(last_ts);
+        let final_payout: u64 = model_claimable(vesting_strategy.vesting_end_ts, payout, last_claim_ts);
+        prop_assert_eq!(final_payout, bought_amount - payout);
+        prop_assert_eq!(claimed_vesting_account.claimable_at(&config_account, vesting_strategy.vesting_end_ts), final_payout);
+        prop_assert_eq!(
+            VestingProgress::new(&claimed_vesting_account, &config_account, last_ts).next_unlock_ts,
+            (final_payout > 0).then_some(vesting_strategy.vesting_end_ts)
+        );
+
+        // 4. Any sequence of claims is paid out as modeled & the payouts sum to the bought amount at the end of the vesting
+        let mut vesting_account: IDOVestingAccount = vesting_account;
+        let mut ts: i64 = cliff_end_ts - unlock_period;
+        let mut paid_amount: u64 = 0;
+        let mut last_claim_ts: Option<i64> = None;
+
+        for interval in claim_intervals.into_iter().chain([i64::from(u32::MAX)]) {
+            ts += interval;
+            let claimable_amount: u64 = model_claimable(ts, paid_amount, last_claim_ts);
+            prop_assert_eq!(vesting_account.claimable_at(&config_account, ts), claimable_amount);
+
+            let payout: u64 = allow_claim_and_define_portion(&Clock { unix_timestamp: ts, ..Clock::default() }, &vesting_strategy, &mut vesting_account).unwrap_or(0);
+            prop_assert_eq!(payout, claimable_amount);
+
+            if payout > 0 {
+                paid_amount += payout;
+                last_claim_ts = Some(ts);
+            }
+        }
+
+        prop_assert!(ts >= vesting_strategy.vesting_end_ts);
+        prop_assert_eq!(paid_amount, bought_amount);
+    }
+}
+
+#[test]
+fn test_error_codes() {
+    let mut code: u32 = 0;
+
+    while let Ok(error) = IDOProgramError::try_from(code) {
+        assert_eq!(error as u32, code);
+        assert_eq!(ProgramError::from(error), ProgramError::Custom(code));
+        code += 1;
+    }
+
+    assert_eq!(code, IDOProgramError::SaleIsActive as u32 + 1);
+}
+
+#[tokio::test]
+async fn test_migrate_v1_accounts() -> Result<(), MintFixtureError> {
+    let program: ProgramTest = ProgramTest::new(
+        "ido_with_vesting", 
+        IDO_PROGRAM_ID,
+        processor!(entrypoint::process_instruction)
+    );
+
+    let mut context: ProgramTestContext = program.start_with_context().await;
+    let payer: Keypair = context.payer.insecure_clone();
+    let payer_pkey: Pubkey = payer.pubkey();
+    let rent: Rent = context.banks_client.get_sysvar::<Rent>().await?;
+    let latest_blockhash: Hash = context.last_blockhash;
+
+    // 0. Create Mint; Create & Initialize ATA; Mint tokens to ATA
+    let mint_decimals: u8 = 9;
+    let mint_amount: u64 = 1_000_000 * 10u64.pow(mint_decimals as u32);
+
+    let (mint_pkey, ata_pda) = {
+        let mint_fixture: MintFixture = MintFixture::new(
+            MintFixtureClient::Banks(&context.banks_client),
+            &payer,
+            &payer_pkey,
+            &rent
+        );
+
+        let mint_pkey: Pubkey = mint_fixture.create_and_intiialize_mint(mint_decimals, &latest_blockhash).await?;
+        let ata_pda: Pubkey = mint_fixture.create_and_intiialize_ata(&mint_pkey, &latest_blockhash).await?;
+        mint_fixture.mint_to_ata(&mint_pkey, &ata_pda, mint_amount, &latest_blockhash).await?;
+
+        (mint_pkey, ata_pda)
+    };
+
+    let treasury_pda: Pubkey = pda::find_treasury(&mint_pkey).0;
+    let config_pda: Pubkey = pda::find_config(&mint_pkey).0;
+    let vesting_pda: Pubkey = pda::find_vesting(&payer_pkey, &mint_pkey).0;
+
+    // 1. Initialize IDO & Buy with the current layouts
+    let vesting_strategy: LinearVestingStrategy = LinearVestingStrategy::new_without_cliff(60 * 5, 60);
+    let initialize_ido_ix: Instruction = instruction::create_initialize_with_vesting(
+        mint_amount, 
+        1_000, 
+        &vesting_strategy, 
+        TransferFeePayer::Recipient,
+        &payer_pkey, 
+        &ata_pda, 
+        &treasury_pda, 
+        &config_pda, 
+        &mint_pkey,
+        &SPL_TOKEN_2022_ID
+    );
+    let buy_ix: Instruction = instruction::create_buy_with_vesting(1_000, &payer_pkey, &vesting_pda, &treasury_pda, &config_pda, &mint_pkey);
+    process_ixs(&mut context, &[initialize_ido_ix, buy_ix], &payer).await?;
+
+    // 2. Downgrade both accounts to v1 layouts: no header, no alignment & the first (31 bytes) config layout
+    let config_data: Vec<u8> = context.banks_client.get_account(config_pda).await?.unwrap().data;
+    let vesting_data: Vec<u8> = context.banks_client.get_account(vesting_pda).await?.unwrap().data;
+
+    let config_v1_data: Vec<u8> = {
+        let config_account: IDOConfigAccount = IDOConfigAccount::unpack(&config_data).unwrap();
+
+        [
+            config_account.vesting_strategy.as_ref(),
+            &config_account.lamports_per_token.to_le_bytes(),
+            &[config_account.bump, config_account.unlocks, config_account.is_initialized]
+        ].concat()
+    };
+
+    let vesting_v1_data: Vec<u8> = {
+        let vesting_account: IDOVestingAccount = IDOVestingAccount::unpack(&vesting_data).unwrap();
+
+        [
+            &vesting_account.last_claim_ts.to_le_bytes()[..],
+            &vesting_account.claimed_amount.to_le_bytes(),
+            &vesting_account.bought_amount.to_le_bytes(),
+            &vesting_account.amount_per_unlock.to_le_bytes(),
+            &[vesting_account.bump, vesting_account.is_initialized]
+        ].concat()
+    };
+
+    for (address, v1_data) in [
+        (config_pda, &config_v1_data),
+        (vesting_pda, &vesting_v1_data)
+    ] {
+        context.set_account(&address, &SolanaAccount {
+            lamports: rent.minimum_balance(v1_data.len()),
+            data: v1_data.to_vec(),
+            owner: IDO_PROGRAM_ID,
+            executable: false,
+            rent_epoch: 0
+        }.into());
+    }
+
+    let recipient: Pubkey = Pubkey::new_unique();
+    let recipient_ata: Pubkey = pda::find_ata(&recipient, &mint_pkey, &SPL_TOKEN_2022_ID).0;
+    let claim_ix: Instruction = instruction::create_claim(
+        &payer_pkey, 
+        &recipient, 
+        &recipient_ata, 
+        &vesting_pda, 
+        &treasury_pda, 
+        &config_pda, 
+        &mint_pkey,
+        &SPL_TOKEN_2022_ID
+    );
+    assert!(process_ixs(&mut context, std::slice::from_ref(&claim_ix), &payer).await.is_err());
+
+    // 3. v1 Vesting has no Owner, so it must be provided & derive the Vesting PDA
+    let err = process_ixs(&mut context, &[instruction::create_migrate(&payer_pkey, &vesting_pda, &mint_pkey)], &payer).await.unwrap_err();
+    assert!(matches!(err, BanksClientError::TransactionError(TransactionError::InstructionError(0, InstructionError::NotEnoughAccountKeys))));
+
+    // foreign owner either doesn't derive a valid PDA with the stored bump or derives another one
+    let migrate_foreign_vesting_ix: Instruction = instruction::create_migrate_vesting(&payer_pkey, &vesting_pda, &recipient, &mint_pkey);
+    assert!(process_ixs(&mut context, &[migrate_foreign_vesting_ix], &payer).await.is_err());
+
+    // 4. v1 Config didn't track the sold supply, so it's migrated only once the sale is over
+    let migrate_ixs: [Instruction; 2] = [
+        instruction::create_migrate(&payer_pkey, &config_pda, &mint_pkey),
+        instruction::create_migrate_vesting(&payer_pkey, &vesting_pda, &payer_pkey, &mint_pkey)
+    ];
+    let err = process_ixs(&mut context, &migrate_ixs[..1], &payer).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(IDOProgramError::SaleIsActive as u32));
+
+    let mut clock: Clock = context.banks_client.get_sysvar::<Clock>().await?;
+    clock.unix_timestamp = vesting_strategy.vesting_end_ts;
+    context.set_sysvar(&clock);
+
+    // 5. Migrate both accounts: the rent is topped up & v1 fields are kept
+    let logs: Vec<String> = process_ixs_with_logs(&mut context, &migrate_ixs, &payer).await?;
+    assert_eq!(IDOEvent::parse_logs(&logs), vec![
+        IDOEvent::Migrated { account: config_pda, account_type: AccountType::Config, from_version: 1, to_version: ACCOUNT_VERSION },
+        IDOEvent::Migrated { account: vesting_pda, account_type: AccountType::Vesting, from_version: 1, to_version: ACCOUNT_VERSION }
+    ]);
+
+    let migrated_config = context.banks_client.get_account(config_pda).await?.unwrap();
+    let migrated_vesting = context.banks_client.get_account(vesting_pda).await?.unwrap();
+    assert_eq!(migrated_vesting.data, vesting_data);
+    assert_eq!(migrated_vesting.lamports, rent.minimum_balance(IDOVestingAccount::LEN));
+    assert_eq!(migrated_config.lamports, rent.minimum_balance(IDOConfigAccount::LEN));
+
+    let config_account: IDOConfigAccount = IDOConfigAccount::unpack(&migrated_config.data).unwrap();
+    assert_eq!(config_account.transfer_fee_payer(), Ok(TransferFeePayer::Recipient));
+    assert_eq!((config_account.deposited_amount, config_account.sold_amount, config_account.reserved_amount), (0, 0, 0));
+    assert_eq!(config_account.lamports_per_token, 1_000);
+    assert_eq!(config_account.mint, mint_pkey);
+    assert_eq!(config_account.treasury, treasury_pda);
+
+    process_ixs(&mut context, &[claim_ix], &payer).await?;
+
+    // 6. Current layouts can't be migrated again & unpack rejects foreign or outdated headers
+    let err = process_ixs(&mut context, &migrate_ixs[1..], &payer).await.unwrap_err();
+    assert_eq!(custom_error_code(&err), Some(IDOProgramError::AccountAlreadyMigrated as u32));
+
+    // only the released v1 layouts are migrated: the extended (40 bytes) Config, Auction & Bid were never released
+    for unreleased_len in [40, 40 + AUCTION_PRICE_LEVELS * 8, 14] {
+        let account: Pubkey = Pubkey::new_unique();
+        context.set_account(&account, &SolanaAccount {
+            lamports: rent.minimum_balance(unreleased_len),
+            data: vec![1; unreleased_len],
+            owner: IDO_PROGRAM_ID,
+            executable: false,
+            rent_epoch: 0
+        }.into());
+
+        let err = process_ixs(&mut context, &[instruction::create_migrate(&payer_pkey, &account, &mint_pkey)], &payer).await.unwrap_err();
+        assert_eq!(custom_error_code(&err), Some(IDOProgramError::InvalidAccountType as u32));
+    }
+
+    let mut data: Vec<u8> = config_data.clone();
+    AccountType::Vesting.pack_header(&mut data);
+    assert_eq!(IDOConfigAccount::unpack(&data).err(), Some(IDOProgramError::InvalidAccountType.into()));
+
+    data[0] = AccountType::Config as u8;
+    data[1] = ACCOUNT_VERSION - 1;
+    assert_eq!(IDOConfigAccount::unpack(&data).err(), Some(IDOProgramError::UnsupportedAccountVersion.into()));
+
+    Ok(())
+}
+
+async fn process_ixs(
+    context: &mut ProgramTestContext, 
+    ixs: &[Instruction], 
+    payer: &Keypair
+) -> Result<(), BanksClientError> {
+    let latest_blockhash: Hash = context.get_new_latest_blockhash().await?;
+    let message: Message = Message::new(ixs, Some(&payer.pubkey()));
+    let mut tx: Transaction = Transaction::new_unsigned(message);
+
+    tx.sign(&[payer], latest_blockhash);
+
+    process_tx(&context.banks_client, tx).await
+}
+
+/// `BanksClient::process_transaction` polls the status of the sent transaction, which may be visible before its accounts
+/// are unlocked (the next transaction fails with `AccountInUse`), so the transactions are processed right by the bank.
+async fn process_tx(banks_client: &BanksClient, tx: Transaction) -> Result<(), BanksClientError> {
+    banks_client.process_transaction_with_metadata(tx).await?.result.map_err(BanksClientError::TransactionError)
+}
+
+/// Same as `process_ixs`, but returns the log messages of the transaction metadata, so the emitted events can be decoded.
+///
+/// `processor!` runs the program natively, where the events are logged as `Program log: Program data: ...` (see `IDOEvent::emit`),
+/// so they are turned back into the `Program data: ...` lines.
+async fn process_ixs_with_logs(
+    context: &mut ProgramTestContext, 
+    ixs: &[Instruction], 
+    payer: &Keypair
+) -> Result<Vec<String>, BanksClientError> {
+    let latest_blockhash: Hash = context.get_new_latest_blockhash().await?;
+    let message: Message = Message::new(ixs, Some(&payer.pubkey()));
+    let mut tx: Transaction = Transaction::new_unsigned(message);
+
+    tx.sign(&[payer], latest_blockhash);
+
+    let BanksTransactionResultWithMetadata { result, metadata } = context.banks_client.process_transaction_with_metadata(tx).await?;
+    result?;
+
+    let logs: Vec<String> = metadata.map(|metadata| metadata.log_messages).unwrap_or_default();
+
+    Ok(logs
+        .into_iter()
+        .map(|log| match log.strip_prefix("Program log: ") {
+            Some(forwarded_log) if forwarded_log.starts_with("Program data: ") => forwarded_log.to_string(),
+            _ => log
+        })
+        .collect())
+}
+
+fn custom_error_code(err: &BanksClientError) -> Option<u32> {
+    match err {
+        BanksClientError::TransactionError(TransactionError::InstructionError(_, InstructionError::Custom(code))) => Some(*code),
+        _ => None
+    }
+}
@@ -0,0 +1,239 @@
+//! The scenario of `examples/client.rs` without a live validator: the Clock sysvar is warped between the claims,
+//! so the cliff, every unlock & the end of the vesting are reached deterministically.
+use mint_fixture::{
+    MintFixture,
+    MintFixtureClient
+};
+use ido_with_vesting::{
+    ID as IDO_PROGRAM_ID,
+    entrypoint,
+    pda,
+    error::IDOProgramError,
+    token::to_base_units,
+    vesting::LinearVestingStrategy,
+    state::{
+        IDOConfigAccount,
+        IDOVestingAccount,
+        TransferFeePayer
+    },
+    client::{
+        ProgramClient,
+        ProgramClientError,
+        ProgramClientTransport
+    }
+};
+use spl_token_2022::{
+    state::Account,
+    extension::StateWithExtensions,
+    ID as SPL_TOKEN_2022_ID
+};
+use solana_program_test::{
+    ProgramTest,
+    ProgramTestContext,
+    processor
+};
+use solana_program::{
+    rent::Rent,
+    hash::Hash,
+    clock::Clock,
+    pubkey::Pubkey
+};
+use solana_sdk::signer::{keypair::Keypair, Signer};
+
+
+const MINT_DECIMALS: u8 = 6;
+const UNLOCK_PERIOD: i64 = 60;
+const LAMPORTS_PER_TOKEN: u32 = 1_000;
+
+#[tokio::test]
+async fn test_vesting_time_travel() -> Result<(), Box<dyn std::error::Error>> {
+    let program: ProgramTest = ProgramTest::new(
+        "ido_with_vesting",
+        IDO_PROGRAM_ID,
+        processor!(entrypoint::process_instruction)
+    );
+
+    let mut context: ProgramTestContext = program.start_with_context().await;
+    let payer: Keypair = context.payer.insecure_clone();
+    let payer_pkey: Pubkey = payer.pubkey();
+    let rent: Rent = context.banks_client.get_sysvar::<Rent>().await?;
+    let latest_blockhash: Hash = context.last_blockhash;
+    let start_ts: i64 = context.banks_client.get_sysvar::<Clock>().await?.unix_timestamp;
+
+    // 0. Create Mint; Create & Initialize ATA; Mint tokens to ATA
+    let mint_amount: u64 = to_base_units(1_000_000, MINT_DECIMALS)?;
+    let (mint_pkey, ata_pda) = {
+        let mint_fixture: MintFixture = MintFixture::new(
+            MintFixtureClient::Banks(&context.banks_client),
+            &payer,
+            &payer_pkey,
+            &rent
+        );
+
+        let mint_pkey: Pubkey = mint_fixture.create_and_intiialize_mint(MINT_DECIMALS, &latest_blockhash).await?;
+        let ata_pda: Pubkey = mint_fixture.create_and_intiialize_ata(&mint_pkey, &latest_blockhash).await?;
+        mint_fixture.mint_to_ata(&mint_pkey, &ata_pda, mint_amount, &latest_blockhash).await?;
+
+        (mint_pkey, ata_pda)
+    };
+
+    let recipient: Pubkey = Pubkey::new_unique();
+    let recipient_ata: Pubkey = pda::find_ata(&recipient, &mint_pkey, &SPL_TOKEN_2022_ID).0;
+    let mut program_client: ProgramClient = ProgramClient::new(
+        ProgramClientTransport::ProgramTest(&mut context),
+        &payer,
+        mint_pkey,
+        SPL_TOKEN_2022_ID
+    );
+    let treasury_pda: Pubkey = program_client.treasury_pda();
+
+    // 1. Initialize IDO: 1 min cliff followed by 4 unlocks every minute, timestamps are taken from the cluster clock
+    let cliff_end_ts: i64 = start_ts + UNLOCK_PERIOD;
+    let vesting_strategy: LinearVestingStrategy = LinearVestingStrategy {
+        cliff_end_ts,
+        vesting_end_ts: cliff_end_ts + UNLOCK_PERIOD * 4,
+        unlock_period: UNLOCK_PERIOD
+    };
+    program_client.initialize(mint_amount, LAMPORTS_PER_TOKEN, &vesting_strategy, TransferFeePayer::Recipient).await?;
+
+    assert_eq!(token_balance(&mut program_client, &ata_pda).await?, 0);
+    assert_eq!(token_balance(&mut program_client, &treasury_pda).await?, mint_amount);
+
+    // 2. Claim before buy
+    let result = program_client.claim(&recipient).await;
+    assert!(matches!(result, Err(ProgramClientError::Program(IDOProgramError::ClaimBeforeBuy))));
+
+    // 3. Buy during the cliff is allowed, claim is not
+    program_client.buy(1_000).await?;
+    assert_vesting(&mut program_client, &payer_pkey, 1_000, 250, 0).await?;
+
+    warp_to(&mut program_client, cliff_end_ts - 1).await?;
+    assert_claim_rejected(&mut program_client, &payer_pkey, &recipient, IDOProgramError::CliffIsActive).await?;
+    assert_eq!(token_balance(&mut program_client, &recipient_ata).await?, 0);
+
+    // 4. The 1st unlock is released right at the end of the cliff
+    warp_to(&mut program_client, cliff_end_ts).await?;
+    assert_claim(&mut program_client, &payer_pkey, &recipient, 250).await?;
+    assert_eq!(token_balance(&mut program_client, &recipient_ata).await?, to_base_units(250, MINT_DECIMALS)?);
+
+    warp_to(&mut program_client, cliff_end_ts + UNLOCK_PERIOD / 2).await?;
+    assert_claim_rejected(&mut program_client, &payer_pkey, &recipient, IDOProgramError::VestingIsActive).await?;
+
+    // 5. The 1st claim has already released the 1st unlock, so nothing new is unlocked after a single unlock period
+    warp_to(&mut program_client, cliff_end_ts + UNLOCK_PERIOD).await?;
+    assert_claim_rejected(&mut program_client, &payer_pkey, &recipient, IDOProgramError::VestingIsActive).await?;
+
+    warp_to(&mut program_client, cliff_end_ts + UNLOCK_PERIOD * 2).await?;
+    assert_claim(&mut program_client, &payer_pkey, &recipient, 250).await?;
+    assert_eq!(token_balance(&mut program_client, &recipient_ata).await?, to_base_units(500, MINT_DECIMALS)?);
+
+    // 6. Top-up buy after the claims advances the portion, the claimed amount is kept
+    warp_to(&mut program_client, cliff_end_ts + UNLOCK_PERIOD * 2 + 10).await?;
+    program_client.buy(1_000).await?;
+    assert_vesting(&mut program_client, &payer_pkey, 2_000, 500, 500).await?;
+
+    assert_claim_rejected(&mut program_client, &payer_pkey, &recipient, IDOProgramError::VestingIsActive).await?;
+
+    // 3 unlocks of the topped-up portion minus the claimed amount
+    warp_to(&mut program_client, cliff_end_ts + UNLOCK_PERIOD * 3).await?;
+    assert_claim(&mut program_client, &payer_pkey, &recipient, 1_000).await?;
+    assert_eq!(token_balance(&mut program_client, &recipient_ata).await?, to_base_units(1_500, MINT_DECIMALS)?);
+
+    // 7. The rest is released at the end of the vesting
+    warp_to(&mut program_client, vesting_strategy.vesting_end_ts - 1).await?;
+    assert_claim_rejected(&mut program_client, &payer_pkey, &recipient, IDOProgramError::VestingIsActive).await?;
+
+    warp_to(&mut program_client, vesting_strategy.vesting_end_ts).await?;
+    assert_claim(&mut program_client, &payer_pkey, &recipient, 500).await?;
+    assert_vesting(&mut program_client, &payer_pkey, 2_000, 500, 2_000).await?;
+    assert_eq!(token_balance(&mut program_client, &recipient_ata).await?, to_base_units(2_000, MINT_DECIMALS)?);
+    assert_eq!(token_balance(&mut program_client, &treasury_pda).await?, mint_amount - to_base_units(2_000, MINT_DECIMALS)?);
+
+    assert_claim_rejected(&mut program_client, &payer_pkey, &recipient, IDOProgramError::AlreadyClaimed).await?;
+
+    // 8. Buy after the vesting is over
+    let result = program_client.buy(500).await;
+    assert!(matches!(result, Err(ProgramClientError::Program(IDOProgramError::VestingPeriodEnded))));
+    assert_vesting(&mut program_client, &payer_pkey, 2_000, 500, 2_000).await?;
+
+    Ok(())
+}
+
+/// Only the timestamp is warped, the slot is kept, so the transactions are still processed by the same bank.
+async fn warp_to(program_client: &mut ProgramClient<'_>, unix_timestamp: i64) -> Result<(), Box<dyn std::error::Error>> {
+    let ProgramClientTransport::ProgramTest(context) = program_client.transport() else {
+        unreachable!();
+    };
+    let mut clock: Clock = context.banks_client.get_sysvar::<Clock>().await?;
+    clock.unix_timestamp = unix_timestamp;
+    context.set_sysvar(&clock);
+
+    Ok(())
+}
+
+/// `amount` of tokens without decimals, it's also predicted by `claimable_at` before the claim.
+async fn assert_claim(
+    program_client: &mut ProgramClient<'_>,
+    owner: &Pubkey,
+    recipient: &Pubkey,
+    amount: u64
+) -> Result<(), Box<dyn std::error::Error>> {
+    let config_account: IDOConfigAccount = program_client.fetch_config().await?;
+    let vesting_account: IDOVestingAccount = program_client.fetch_vesting(owner).await?;
+    let now_ts: i64 = program_client.fetch_clock().await?.unix_timestamp;
+    assert_eq!(vesting_account.claimable_at(&config_account, now_ts), amount);
+
+    let recipient_ata: Pubkey = pda::find_ata(recipient, &config_account.mint, &SPL_TOKEN_2022_ID).0;
+    let balance_before: u64 = token_balance(program_client, &recipient_ata).await?;
+    program_client.claim(recipient).await?;
+
+    assert_eq!(token_balance(program_client, &recipient_ata).await?, balance_before + to_base_units(amount, MINT_DECIMALS)?);
+    assert_eq!(program_client.fetch_vesting(owner).await?.claimed_amount, vesting_account.claimed_amount + amount);
+
+    Ok(())
+}
+
+/// The rejected claim doesn't modify the vesting.
+async fn assert_claim_rejected(
+    program_client: &mut ProgramClient<'_>,
+    owner: &Pubkey,
+    recipient: &Pubkey,
+    expected_error: IDOProgramError
+) -> Result<(), Box<dyn std::error::Error>> {
+    let vesting_account: IDOVestingAccount = program_client.fetch_vesting(owner).await?;
+
+    match program_client.claim(recipient).await {
+        Err(ProgramClientError::Program(error)) => assert_eq!(error, expected_error),
+        result => panic!("expected {:?}, got {:?}", expected_error, result)
+    }
+    assert_eq!(program_client.fetch_vesting(owner).await?, vesting_account);
+
+    Ok(())
+}
+
+/// Amounts of tokens without decimals.
+async fn assert_vesting(
+    program_client: &mut ProgramClient<'_>,
+    owner: &Pubkey,
+    bought_amount: u64,
+    amount_per_unlock: u64,
+    claimed_amount: u64
+) -> Result<(), Box<dyn std::error::Error>> {
+    let vesting_account: IDOVestingAccount = program_client.fetch_vesting(owner).await?;
+
+    assert_eq!(
+        (vesting_account.bought_amount, vesting_account.amount_per_unlock, vesting_account.claimed_amount),
+        (bought_amount, amount_per_unlock, claimed_amount)
+    );
+
+    Ok(())
+}
+
+/// Token account that doesn't exist yet holds nothing.
+async fn token_balance(program_client: &mut ProgramClient<'_>, address: &Pubkey) -> Result<u64, Box<dyn std::error::Error>> {
+    match program_client.fetch_account_data(address).await {
+        Ok(data) => Ok(StateWithExtensions::<Account>::unpack(&data)?.base.amount),
+        Err(ProgramClientError::AccountNotFound(_)) => Ok(0),
+        Err(e) => Err(e.into())
+    }
+}
@@ -3,7 +3,7 @@ use solana_sdk::{
     rent::Rent,
     hash::Hash,
     pubkey::Pubkey,
-    program_pack::Pack,
+    program_error::ProgramError,
     message::Message,
     system_program::ID as SYSTEM_PROGRAM_ID,
     system_transaction,
@@ -13,7 +13,13 @@ use solana_sdk::{
     signature::Signer,
 };
 use spl_token_2022::{
-    state::Mint,
+    state::{Mint, AccountState},
+    extension::{
+        ExtensionType,
+        transfer_hook,
+        default_account_state::instruction::initialize_default_account_state,
+        transfer_fee::instruction::initialize_transfer_fee_config
+    },
     ID as SPL_TOKEN_2022_ID
 };
 use solana_program_test::{
@@ -24,26 +30,27 @@ use solana_client::{
     client_error::ClientError,
     nonblocking::rpc_client::RpcClient
 };
+use ido_with_vesting::{
+    pda::find_ata,
+    external_ids::ATA_PROGRAM_ID
+};
 
 
-// since i've implemented ix crafting & ATA derivation manually, importing `spl_associated_token_account_client` only for ID is an overkill
-const ATA_PROGRAM_ID: Pubkey = Pubkey::from_str_const("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
-
 #[derive(Debug)]
 pub enum MintFixtureError {
-    Client(ClientError),
-    Banks(BanksClientError)
+    Client(Box<ClientError>),
+    Banks(Box<BanksClientError>)
 }
 
 impl From<ClientError> for MintFixtureError {
     fn from(value: ClientError) -> Self {
-        Self::Client(value)
+        Self::Client(Box::new(value))
     }
 }
 
 impl From<BanksClientError> for MintFixtureError {
     fn from(value: BanksClientError) -> Self {
-        Self::Banks(value)
+        Self::Banks(Box::new(value))
     }
 }
 
@@ -57,6 +64,59 @@ impl std::fmt::Display for MintFixtureError {
     }
 }
 
+/// Token-2022 mint extensions supported by the fixture.
+enum MintExtension {
+    TransferFee {
+        transfer_fee_basis_points: u16,
+        maximum_fee: u64
+    },
+    TransferHook {
+        program_id: Pubkey
+    },
+    DefaultAccountState {
+        state: AccountState
+    }
+}
+
+impl MintExtension {
+    fn extension_type(&self) -> ExtensionType {
+        match self {
+            Self::TransferFee { .. } => ExtensionType::TransferFeeConfig,
+            Self::TransferHook { .. } => ExtensionType::TransferHook,
+            Self::DefaultAccountState { .. } => ExtensionType::DefaultAccountState
+        }
+    }
+
+    /// DefaultAccountState can be updated only by the mint's freeze authority.
+    fn requires_freeze_authority(&self) -> bool {
+        matches!(self, Self::DefaultAccountState { .. })
+    }
+
+    fn create_initialize_ix(&self, mint_pkey: &Pubkey, authority: &Pubkey) -> Result<Instruction, ProgramError> {
+        match *self {
+            Self::TransferFee { transfer_fee_basis_points, maximum_fee } => initialize_transfer_fee_config(
+                &SPL_TOKEN_2022_ID, 
+                mint_pkey, 
+                Some(authority), 
+                Some(authority), 
+                transfer_fee_basis_points, 
+                maximum_fee
+            ),
+            Self::TransferHook { program_id } => transfer_hook::instruction::initialize(
+                &SPL_TOKEN_2022_ID, 
+                mint_pkey, 
+                Some(*authority), 
+                Some(program_id)
+            ),
+            Self::DefaultAccountState { state } => initialize_default_account_state(
+                &SPL_TOKEN_2022_ID, 
+                mint_pkey, 
+                &state
+            )
+        }
+    }
+}
+
 pub enum MintFixtureClient<'a> {
     Rpc(&'a RpcClient),
     Banks(&'a BanksClient),
@@ -66,7 +126,8 @@ pub struct MintFixture<'a> {
     client: MintFixtureClient<'a>,
     payer: &'a Keypair,
     payer_pkey: &'a Pubkey,
-    rent: &'a Rent
+    rent: &'a Rent,
+    token_program_id: Pubkey
 }
 
 impl<'a> MintFixture<'a> {
@@ -76,52 +137,128 @@ impl<'a> MintFixture<'a> {
         payer_pkey: &'a Pubkey,
         rent: &'a Rent 
     ) -> Self {        
-        Self { client, payer, payer_pkey, rent }
+        Self { client, payer, payer_pkey, rent, token_program_id: SPL_TOKEN_2022_ID }
+    }
+
+    /// By default `SPL Token 2022` is used, this method allows to switch to the classic `SPL Token` program.
+    /// 
+    /// Note, mint extensions are supported only by `SPL Token 2022`.
+    pub fn with_token_program(self, token_program_id: Pubkey) -> Self {
+        Self { token_program_id, ..self }
+    }
+
+    pub fn token_program_id(&self) -> &Pubkey {
+        &self.token_program_id
     }
     
     pub async fn create_and_intiialize_mint(&self, mint_decimals: u8, latest_blockhash: &Hash) -> Result<Pubkey, MintFixtureError> {
+        self.create_and_initialize_mint_with_extensions(mint_decimals, &[], latest_blockhash).await
+    }
+
+    /// Creates Token-2022 mint with `TransferFeeConfig` extension, where the payer is both the fee config & withdraw authority.
+    pub async fn create_and_initialize_mint_with_transfer_fee(
+        &self, 
+        mint_decimals: u8, 
+        transfer_fee_basis_points: u16,
+        maximum_fee: u64,
+        latest_blockhash: &Hash
+    ) -> Result<Pubkey, MintFixtureError> {
+        self.create_and_initialize_mint_with_extensions(
+            mint_decimals, 
+            &[MintExtension::TransferFee { transfer_fee_basis_points, maximum_fee }], 
+            latest_blockhash
+        ).await
+    }
+
+    /// Creates Token-2022 mint with `TransferHook` extension, which points to `transfer_hook_program_id`.
+    /// 
+    /// Note, the hook's validation PDA must be initialized separately.
+    pub async fn create_and_initialize_mint_with_transfer_hook(
+        &self, 
+        mint_decimals: u8, 
+        transfer_hook_program_id: &Pubkey,
+        latest_blockhash: &Hash
+    ) -> Result<Pubkey, MintFixtureError> {
+        self.create_and_initialize_mint_with_extensions(
+            mint_decimals, 
+            &[MintExtension::TransferHook { program_id: *transfer_hook_program_id }], 
+            latest_blockhash
+        ).await
+    }
+
+    /// Creates Token-2022 mint with `DefaultAccountState` extension, where the payer is the freeze authority.
+    pub async fn create_and_initialize_mint_with_default_account_state(
+        &self, 
+        mint_decimals: u8, 
+        state: AccountState,
+        latest_blockhash: &Hash
+    ) -> Result<Pubkey, MintFixtureError> {
+        self.create_and_initialize_mint_with_extensions(
+            mint_decimals, 
+            &[MintExtension::DefaultAccountState { state }], 
+            latest_blockhash
+        ).await
+    }
+
+    async fn create_and_initialize_mint_with_extensions(
+        &self, 
+        mint_decimals: u8, 
+        extensions: &[MintExtension],
+        latest_blockhash: &Hash
+    ) -> Result<Pubkey, MintFixtureError> {
         // 1. create account using system program
         let mint_keypair: Keypair = Keypair::new();
+        let mint_pkey: Pubkey = mint_keypair.pubkey();
+
+        let extension_types: Vec<ExtensionType> = extensions.iter().map(MintExtension::extension_type).collect();
+        let mint_len: usize = ExtensionType::try_calculate_account_len::<Mint>(&extension_types)
+            .map_err(|_| BanksClientError::ClientError("Failed to calculate Mint length!"))?;
 
         let create_tx: Transaction = system_transaction::create_account(
             self.payer, 
             &mint_keypair, 
             *latest_blockhash, 
-            self.rent.minimum_balance(Mint::LEN), 
-            Mint::LEN as u64, 
-            &SPL_TOKEN_2022_ID
+            self.rent.minimum_balance(mint_len), 
+            mint_len as u64, 
+            &self.token_program_id
         );
 
         self.process_transaction(create_tx).await?;
 
-        // 2. initialize Mint account using SPL program
-        // 2.1 craft initialize mint ix & tx
+        // 2. initialize extensions (must precede InitializeMint) & Mint account using SPL program
+        // 2.1 craft initialize ixs & tx
+        let mut initialize_mint_ixs: Vec<Instruction> = extensions
+            .iter()
+            .map(|extension| extension.create_initialize_ix(&mint_pkey, self.payer_pkey))
+            .collect::<Result<_, _>>()
+            .map_err(|_| BanksClientError::ClientError("Failed to craft mint extension ix!"))?;
+
+        let freeze_authority: Option<&Pubkey> = extensions
+            .iter()
+            .any(MintExtension::requires_freeze_authority)
+            .then_some(self.payer_pkey);
+
         let initialize_mint_ix: Instruction = spl_token_2022::instruction::initialize_mint(
-            &SPL_TOKEN_2022_ID, 
-            &mint_keypair.pubkey(), 
+            &self.token_program_id, 
+            &mint_pkey, 
             self.payer_pkey, 
-            None, 
+            freeze_authority, 
             mint_decimals
         ).map_err(|_| BanksClientError::ClientError("Failed to craft InitializeMint ix!"))?;
-        let message: Message = Message::new(&[initialize_mint_ix], Some(self.payer_pkey));
+        initialize_mint_ixs.push(initialize_mint_ix);
+
+        let message: Message = Message::new(&initialize_mint_ixs, Some(self.payer_pkey));
         let mut initialize_mint_tx: Transaction = Transaction::new_unsigned(message);
         
         // 2.2 sign & send tx
         initialize_mint_tx.sign(&[self.payer], *latest_blockhash);
         self.process_transaction(initialize_mint_tx).await?;
 
-        Ok(mint_keypair.pubkey())
+        Ok(mint_pkey)
     }
 
     pub async fn create_and_intiialize_ata(&self, mint_pkey: &Pubkey, latest_blockhash: &Hash) -> Result<Pubkey, MintFixtureError> {
-        let ata_pda: Pubkey = Pubkey::find_program_address(
-            &[
-                self.payer_pkey.as_ref(),
-                SPL_TOKEN_2022_ID.as_ref(),
-                mint_pkey.as_ref()
-            ], 
-            &ATA_PROGRAM_ID
-        ).0;
+        let ata_pda: Pubkey = find_ata(self.payer_pkey, mint_pkey, &self.token_program_id).0;
 
         let create_ata_ix: Instruction = Instruction::new_with_bytes(
             ATA_PROGRAM_ID, 
@@ -132,7 +269,7 @@ impl<'a> MintFixture<'a> {
                 AccountMeta::new_readonly(*self.payer_pkey, false),
                 AccountMeta::new_readonly(*mint_pkey, false),
                 AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
-                AccountMeta::new_readonly(SPL_TOKEN_2022_ID, false)
+                AccountMeta::new_readonly(self.token_program_id, false)
             ]
         );
 
@@ -157,7 +294,7 @@ impl<'a> MintFixture<'a> {
         mint_to_ix_payload.extend_from_slice(&u64::to_le_bytes(mint_amount));
         
         let mint_to_ix: Instruction = Instruction::new_with_bytes(
-            SPL_TOKEN_2022_ID, 
+            self.token_program_id, 
             &mint_to_ix_payload, 
             vec![
                 AccountMeta::new(*mint_pkey, false),
@@ -179,8 +316,10 @@ impl<'a> MintFixture<'a> {
             MintFixtureClient::Rpc(client) => {
                 client.send_and_confirm_transaction(&tx).await?;
             },
+            // processed right by the bank, unlike `process_transaction`, which polls the status & may return
+            // before the accounts are unlocked (the next transaction fails with `AccountInUse`) or time out under load
             MintFixtureClient::Banks(client) => {
-                client.process_transaction(tx).await?;
+                client.process_transaction_with_metadata(tx).await?.result.map_err(BanksClientError::TransactionError)?;
             },
         }
 